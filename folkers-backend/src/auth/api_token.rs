@@ -0,0 +1,35 @@
+//! Personal access tokens - long-lived credentials for scripts and integrations.
+
+use base64::{Engine as _, engine::general_purpose};
+use rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+
+/// Every personal access token starts with this prefix (JWT never does)
+pub const TOKEN_PREFIX: &str = "fkr_";
+/// Length of token beginning stored for display
+const DISPLAY_PREFIX_LENGTH: usize = 12;
+
+/// Generates new token (prefix + random 256 bits, base64url encoded)
+pub fn generate() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+
+    format!(
+        "{TOKEN_PREFIX}{}",
+        general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+    )
+}
+
+/// Personal access tokens are stored as SHA256 hashes
+pub fn hash(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Token beginning which is safe to display
+pub fn display_prefix(token: &str) -> String {
+    token.chars().take(DISPLAY_PREFIX_LENGTH).collect()
+}
+
+pub fn is_api_token(token: &str) -> bool {
+    token.starts_with(TOKEN_PREFIX)
+}
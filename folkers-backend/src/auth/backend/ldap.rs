@@ -0,0 +1,282 @@
+//! LDAP / Active Directory accounts - password is verified by bind as user's directory entry.
+//!
+//! User entry is found with service account (or anonymously), groups are searched by user's DN
+//! and mapped to roles. Accounts removed from directory or from all mapped groups are disabled
+//! on the next token refresh.
+
+use super::{AccountStatus, AuthBackend, BoxFuture, ExternalAccount, Identity, Outcome};
+use crate::auth::user;
+use ldap3::{Ldap, LdapConnAsync, LdapConnSettings, Scope, SearchEntry, ldap_escape};
+
+/// Prefix of `external_id` for directory users
+const EXTERNAL_ID_PREFIX: &str = "ldap:";
+/// `invalidCredentials` result code of bind operation
+const INVALID_CREDENTIALS: u32 = 49;
+/// Directory connection timeout (seconds)
+const CONNECTION_TIMEOUT_SECONDS: u64 = 5;
+
+const DEFAULT_USERNAME_ATTRIBUTE: &str = "uid";
+const DEFAULT_GROUP_FILTER: &str = "(|(member={dn})(uniqueMember={dn})(memberUid={username}))";
+const GROUP_NAME_ATTRIBUTE: &str = "cn";
+
+#[derive(Debug, Clone)]
+pub struct LdapConfig {
+    /// `ldap://` or `ldaps://` URL
+    pub url: String,
+    /// Upgrade `ldap://` connection with StartTLS
+    pub starttls: bool,
+    /// Service account for searches (anonymous if empty)
+    pub bind_dn: Option<String>,
+    pub bind_password: Option<String>,
+    /// Users search base
+    pub base_dn: String,
+    /// Attribute with username (`uid`, `sAMAccountName` for Active Directory)
+    pub username_attribute: String,
+    /// Additional users filter, e.g. `(objectClass=person)`
+    pub user_filter: Option<String>,
+    /// Groups search base
+    pub group_base_dn: String,
+    /// Groups filter with `{dn}` and `{username}` placeholders
+    pub group_filter: String,
+    /// Group (`cn`) to role pairs, the first matching group wins
+    pub role_mapping: Vec<(String, String)>,
+    /// Role of users without any mapped group (login is denied if empty)
+    pub default_role: Option<String>,
+    /// Create user on first login
+    pub auto_provision: bool,
+    /// Link local user with the same username on first login
+    pub link_existing: bool,
+}
+
+impl LdapConfig {
+    /// Reads configuration from `FOLKERS_LDAP_*` environment variables
+    pub fn from_env() -> Result<Self, anyhow::Error> {
+        let required = |name: &str| -> Result<String, anyhow::Error> {
+            std::env::var(name).map_err(|_| {
+                anyhow::Error::msg(format!(
+                    "{name} is required for `ldap` authentication backend"
+                ))
+            })
+        };
+
+        let base_dn = required("FOLKERS_LDAP_BASE_DN")?;
+
+        Ok(Self {
+            url: required("FOLKERS_LDAP_URL")?,
+            starttls: super::env_bool("FOLKERS_LDAP_STARTTLS", false)?,
+            bind_dn: std::env::var("FOLKERS_LDAP_BIND_DN").ok(),
+            bind_password: std::env::var("FOLKERS_LDAP_BIND_PASSWORD").ok(),
+            username_attribute: std::env::var("FOLKERS_LDAP_USERNAME_ATTRIBUTE")
+                .unwrap_or_else(|_| DEFAULT_USERNAME_ATTRIBUTE.to_owned()),
+            user_filter: std::env::var("FOLKERS_LDAP_USER_FILTER").ok(),
+            group_base_dn: std::env::var("FOLKERS_LDAP_GROUP_BASE_DN")
+                .unwrap_or_else(|_| base_dn.clone()),
+            group_filter: std::env::var("FOLKERS_LDAP_GROUP_FILTER")
+                .unwrap_or_else(|_| DEFAULT_GROUP_FILTER.to_owned()),
+            base_dn,
+            role_mapping: super::role_mapping_from_env("FOLKERS_LDAP_ROLE_MAPPING")?,
+            default_role: std::env::var("FOLKERS_LDAP_DEFAULT_ROLE").ok(),
+            auto_provision: super::env_bool("FOLKERS_LDAP_AUTO_PROVISION", true)?,
+            link_existing: super::env_bool("FOLKERS_LDAP_LINK_EXISTING", false)?,
+        })
+    }
+}
+
+pub struct LdapBackend {
+    config: LdapConfig,
+}
+
+impl LdapBackend {
+    pub fn new(config: LdapConfig) -> Self {
+        Self { config }
+    }
+
+    async fn connect(&self) -> Result<Ldap, anyhow::Error> {
+        let settings = LdapConnSettings::new()
+            .set_starttls(self.config.starttls)
+            .set_conn_timeout(std::time::Duration::from_secs(CONNECTION_TIMEOUT_SECONDS));
+
+        let (connection, mut ldap) =
+            LdapConnAsync::with_settings(settings, &self.config.url).await?;
+        ldap3::drive!(connection);
+
+        self.service_bind(&mut ldap).await?;
+        Ok(ldap)
+    }
+
+    /// Binds as service account (anonymously if it isn't configured)
+    async fn service_bind(&self, ldap: &mut Ldap) -> Result<(), anyhow::Error> {
+        ldap.simple_bind(
+            self.config.bind_dn.as_deref().unwrap_or_default(),
+            self.config.bind_password.as_deref().unwrap_or_default(),
+        )
+        .await?
+        .success()?;
+
+        Ok(())
+    }
+
+    /// Finds user's entry by username (`None` if user doesn't exist)
+    async fn find_user(
+        &self,
+        ldap: &mut Ldap,
+        username: &str,
+    ) -> Result<Option<SearchEntry>, anyhow::Error> {
+        let filter = format!(
+            "(&({}={}){})",
+            self.config.username_attribute,
+            ldap_escape(username),
+            self.config.user_filter.as_deref().unwrap_or_default()
+        );
+
+        let (entries, _) = ldap
+            .search(
+                &self.config.base_dn,
+                Scope::Subtree,
+                &filter,
+                vec![self.config.username_attribute.as_str()],
+            )
+            .await?
+            .success()?;
+
+        if entries.len() > 1 {
+            return Err(anyhow::Error::msg(format!(
+                "username `{username}` matches {} directory entries",
+                entries.len()
+            )));
+        }
+
+        Ok(entries.into_iter().next().map(SearchEntry::construct))
+    }
+
+    /// Names (`cn`) of groups user is member of
+    async fn find_groups(
+        &self,
+        ldap: &mut Ldap,
+        entry: &SearchEntry,
+        username: &str,
+    ) -> Result<Vec<String>, anyhow::Error> {
+        let filter = self
+            .config
+            .group_filter
+            .replace("{dn}", &ldap_escape(&entry.dn))
+            .replace("{username}", &ldap_escape(username));
+
+        let (entries, _) = ldap
+            .search(
+                &self.config.group_base_dn,
+                Scope::Subtree,
+                &filter,
+                vec![GROUP_NAME_ATTRIBUTE],
+            )
+            .await?
+            .success()?;
+
+        Ok(entries
+            .into_iter()
+            .map(SearchEntry::construct)
+            .filter_map(|group| group.attrs.get(GROUP_NAME_ATTRIBUTE)?.first().cloned())
+            .collect())
+    }
+
+    /// Username as stored in directory (login may differ in case)
+    fn entry_username(&self, entry: &SearchEntry, fallback: &str) -> String {
+        entry
+            .attrs
+            .get(&self.config.username_attribute)
+            .and_then(|values| values.first())
+            .cloned()
+            .unwrap_or_else(|| fallback.to_owned())
+    }
+
+    fn map_role(&self, groups: &[String]) -> Option<String> {
+        super::map_role(
+            &self.config.role_mapping,
+            self.config.default_role.as_deref(),
+            groups,
+        )
+    }
+}
+
+impl AuthBackend for LdapBackend {
+    fn name(&self) -> &'static str {
+        "ldap"
+    }
+
+    fn authenticate<'a>(
+        &'a self,
+        username: &'a str,
+        password: &'a str,
+    ) -> BoxFuture<'a, Result<Outcome, anyhow::Error>> {
+        Box::pin(async move {
+            // bind with empty password is anonymous and always succeeds
+            if username.is_empty() || password.is_empty() {
+                return Ok(Outcome::NotFound);
+            }
+
+            let mut ldap = self.connect().await?;
+
+            let Some(entry) = self.find_user(&mut ldap, username).await? else {
+                let _ = ldap.unbind().await;
+                return Ok(Outcome::NotFound);
+            };
+
+            let bind = ldap.simple_bind(&entry.dn, password).await?;
+
+            if bind.rc == INVALID_CREDENTIALS {
+                let _ = ldap.unbind().await;
+                return Ok(Outcome::Rejected);
+            }
+
+            bind.success()?;
+
+            // user may be not allowed to search groups
+            self.service_bind(&mut ldap).await?;
+
+            let username = self.entry_username(&entry, username);
+            let groups = self.find_groups(&mut ldap, &entry, &username).await?;
+            let _ = ldap.unbind().await;
+
+            Ok(Outcome::Authenticated(Identity::External(
+                ExternalAccount {
+                    external_id: format!("{EXTERNAL_ID_PREFIX}{}", username.to_lowercase()),
+                    username,
+                    role: self.map_role(&groups),
+                    link_existing: self.config.link_existing,
+                    auto_provision: self.config.auto_provision,
+                },
+            )))
+        })
+    }
+
+    fn account_status<'a>(
+        &'a self,
+        user: &'a user::User,
+    ) -> BoxFuture<'a, Result<AccountStatus, anyhow::Error>> {
+        Box::pin(async move {
+            let Some(username) = user
+                .external_id
+                .as_deref()
+                .and_then(|external_id| external_id.strip_prefix(EXTERNAL_ID_PREFIX))
+            else {
+                return Ok(AccountStatus::Unmanaged);
+            };
+
+            let mut ldap = self.connect().await?;
+
+            let Some(entry) = self.find_user(&mut ldap, username).await? else {
+                let _ = ldap.unbind().await;
+                return Ok(AccountStatus::Disabled);
+            };
+
+            let username = self.entry_username(&entry, username);
+            let groups = self.find_groups(&mut ldap, &entry, &username).await?;
+            let _ = ldap.unbind().await;
+
+            Ok(match self.map_role(&groups) {
+                Some(role) => AccountStatus::Active { role },
+                None => AccountStatus::Disabled,
+            })
+        })
+    }
+}
@@ -0,0 +1,132 @@
+//! Local accounts - Argon2 password hashes stored in SurrealDB.
+
+use super::{AuthBackend, BoxFuture, Identity, Outcome};
+use crate::{
+    DATABASE,
+    auth::{ARGON2_PARAMS, UserRepository, user},
+};
+use argon2::{
+    Algorithm, Argon2, Params,
+    password_hash::{PasswordHash, PasswordVerifier},
+};
+use std::sync::LazyLock;
+
+/// Hash verified for unknown usernames, so response time doesn't reveal if user exists
+static DUMMY_HASH: LazyLock<String> =
+    LazyLock::new(|| UserRepository::hash_password("folkers-dummy-password").unwrap_or_default());
+
+pub struct LocalBackend;
+
+impl LocalBackend {
+    /// Verifies password of local account, returns user on success
+    pub async fn verify(username: &str, password: &str) -> Option<user::User> {
+        let Ok(Some(record)) = DATABASE.get_user_by_username(username.to_string()).await else {
+            Self::verify_dummy(password);
+            return None;
+        };
+
+        Self::verify_user(UserRepository::user_from_record(record), password).await
+    }
+
+    /// Verifies user's password hash (outdated hashes are upgraded)
+    async fn verify_user(user: user::User, password: &str) -> Option<user::User> {
+        // external accounts have no usable password
+        let password_hash =
+            PasswordHash::parse(&user.password_hash, argon2::password_hash::Encoding::B64).ok()?;
+
+        // parameters are taken from the hash itself, so legacy hashes are verified too
+        Argon2::default()
+            .verify_password(password.as_ref(), &password_hash)
+            .ok()?;
+
+        if Self::needs_rehash(&password_hash) {
+            Self::rehash_password(&user, password).await;
+        }
+
+        Some(user)
+    }
+
+    /// Unknown usernames take the same time as known ones
+    fn verify_dummy(password: &str) {
+        if let Ok(dummy_hash) = PasswordHash::new(&DUMMY_HASH) {
+            let _ = Argon2::default().verify_password(password.as_ref(), &dummy_hash);
+        }
+    }
+
+    /// Checks if hash was made with legacy global salt (`FOLKERS_BASE64_SALT`)
+    /// or with outdated Argon2 parameters
+    fn needs_rehash(password_hash: &PasswordHash) -> bool {
+        let legacy_salt = std::env::var("FOLKERS_BASE64_SALT").ok();
+
+        if let (Some(salt), Some(legacy_salt)) = (password_hash.salt, legacy_salt)
+            && salt.as_str() == legacy_salt
+        {
+            return true;
+        }
+
+        match Params::try_from(password_hash) {
+            Ok(params) => {
+                password_hash.algorithm != Algorithm::Argon2id.ident()
+                    || params.m_cost() != ARGON2_PARAMS.m_cost()
+                    || params.t_cost() != ARGON2_PARAMS.t_cost()
+                    || params.p_cost() != ARGON2_PARAMS.p_cost()
+            }
+            Err(_) => true,
+        }
+    }
+
+    /// Silently upgrades user's password hash (errors are only logged)
+    async fn rehash_password(user: &user::User, password: &str) {
+        let new_hash = match UserRepository::hash_password(password) {
+            Ok(hash) => hash,
+            Err(err) => {
+                log::error!(
+                    "`{} ({})` password rehash got HASHING ERROR: {}",
+                    user.username,
+                    user.id,
+                    err
+                );
+                return;
+            }
+        };
+
+        match DATABASE.update_user_password(&user.id, new_hash).await {
+            Ok(_) => log::info!("`{} ({})` password hash upgraded", user.username, user.id),
+            Err(err) => log::error!(
+                "`{} ({})` password rehash got database error: {}",
+                user.username,
+                user.id,
+                err
+            ),
+        }
+    }
+}
+
+impl AuthBackend for LocalBackend {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    fn authenticate<'a>(
+        &'a self,
+        username: &'a str,
+        password: &'a str,
+    ) -> BoxFuture<'a, Result<Outcome, anyhow::Error>> {
+        Box::pin(async move {
+            let record = DATABASE.get_user_by_username(username.to_string()).await?;
+
+            // unknown users and external accounts are left to other backends
+            let Some(record) = record.filter(|record| record.external_id.is_none()) else {
+                Self::verify_dummy(password);
+                return Ok(Outcome::NotFound);
+            };
+
+            let user = UserRepository::user_from_record(record);
+
+            Ok(match Self::verify_user(user, password).await {
+                Some(user) => Outcome::Authenticated(Identity::Local(user)),
+                None => Outcome::Rejected,
+            })
+        })
+    }
+}
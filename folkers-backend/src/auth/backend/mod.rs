@@ -0,0 +1,165 @@
+//! Authentication backends - sources of user credentials, tried in configured order.
+//!
+//! Order is set with `FOLKERS_AUTH_BACKENDS` (default: `local`). Backend which doesn't know the user
+//! passes it to the next one, backend which knows the user but rejects password stops the login.
+
+use super::user;
+use std::{future::Future, pin::Pin};
+
+pub mod ldap;
+pub mod local;
+
+/// Default value of `FOLKERS_AUTH_BACKENDS`
+const DEFAULT_BACKENDS: &str = "local";
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Account verified by external directory or identity provider
+#[derive(Debug, Clone)]
+pub struct ExternalAccount {
+    pub username: String,
+    /// Value of user's `external_id` field (`<source>:<id>`)
+    pub external_id: String,
+    /// Role mapped from groups (access is denied if empty)
+    pub role: Option<String>,
+    /// Local user with the same username is linked on first login
+    pub link_existing: bool,
+    /// User is created on first login
+    pub auto_provision: bool,
+}
+
+/// Verified user
+#[derive(Debug, Clone)]
+pub enum Identity {
+    Local(user::User),
+    External(ExternalAccount),
+}
+
+#[derive(Debug, Clone)]
+pub enum Outcome {
+    /// Credentials are verified
+    Authenticated(Identity),
+    /// User is known by backend, but credentials are wrong (next backends are not tried)
+    Rejected,
+    /// User is unknown by backend, next one is tried
+    NotFound,
+}
+
+/// State of external account, checked on every token refresh
+#[derive(Debug, Clone)]
+pub enum AccountStatus {
+    /// Account is not managed by backend
+    Unmanaged,
+    /// Account exists, role is mapped from its current groups
+    Active { role: String },
+    /// Account was removed or lost all mapped groups
+    Disabled,
+}
+
+pub trait AuthBackend: Send + Sync {
+    /// Backend name used in `FOLKERS_AUTH_BACKENDS` and logs
+    fn name(&self) -> &'static str;
+
+    /// Verifies username and password
+    fn authenticate<'a>(
+        &'a self,
+        username: &'a str,
+        password: &'a str,
+    ) -> BoxFuture<'a, Result<Outcome, anyhow::Error>>;
+
+    /// Checks that account of already authenticated user is still active
+    fn account_status<'a>(
+        &'a self,
+        _user: &'a user::User,
+    ) -> BoxFuture<'a, Result<AccountStatus, anyhow::Error>> {
+        Box::pin(async { Ok(AccountStatus::Unmanaged) })
+    }
+}
+
+/// Builds backends in order from `FOLKERS_AUTH_BACKENDS` (comma separated: `local`, `ldap`)
+pub fn from_env() -> Result<Vec<Box<dyn AuthBackend>>, anyhow::Error> {
+    let names =
+        std::env::var("FOLKERS_AUTH_BACKENDS").unwrap_or_else(|_| DEFAULT_BACKENDS.to_owned());
+
+    let mut backends: Vec<Box<dyn AuthBackend>> = Vec::new();
+
+    for name in names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        if backends.iter().any(|backend| backend.name() == name) {
+            return Err(anyhow::Error::msg(format!(
+                "Authentication backend `{name}` is listed twice in FOLKERS_AUTH_BACKENDS"
+            )));
+        }
+
+        let backend: Box<dyn AuthBackend> = match name {
+            "local" => Box::new(local::LocalBackend),
+            "ldap" => Box::new(ldap::LdapBackend::new(ldap::LdapConfig::from_env()?)),
+            _ => {
+                return Err(anyhow::Error::msg(format!(
+                    "Unknown authentication backend `{name}` in FOLKERS_AUTH_BACKENDS"
+                )));
+            }
+        };
+
+        backends.push(backend);
+    }
+
+    if backends.is_empty() {
+        return Err(anyhow::Error::msg(
+            "FOLKERS_AUTH_BACKENDS must contain at least one backend",
+        ));
+    }
+
+    Ok(backends)
+}
+
+/// Reads group to role pairs (`group=role,group=role`) from environment variable
+pub fn role_mapping_from_env(name: &str) -> Result<Vec<(String, String)>, anyhow::Error> {
+    let Ok(mapping) = std::env::var(name) else {
+        return Ok(Vec::new());
+    };
+
+    mapping
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((group, role)) if !group.trim().is_empty() && !role.trim().is_empty() => {
+                Ok((group.trim().to_owned(), role.trim().to_owned()))
+            }
+            _ => Err(anyhow::Error::msg(format!(
+                "Invalid {name} entry `{pair}` (expected `group=role`)"
+            ))),
+        })
+        .collect()
+}
+
+/// Role of the first mapped group (case insensitive) or default one
+pub fn map_role(
+    mapping: &[(String, String)],
+    default_role: Option<&str>,
+    groups: &[String],
+) -> Option<String> {
+    mapping
+        .iter()
+        .find(|(group, _)| {
+            groups
+                .iter()
+                .any(|candidate| candidate.eq_ignore_ascii_case(group))
+        })
+        .map(|(_, role)| role.clone())
+        .or_else(|| default_role.map(str::to_owned))
+}
+
+/// Reads boolean environment variable
+pub fn env_bool(name: &str, default: bool) -> Result<bool, anyhow::Error> {
+    match std::env::var(name) {
+        Ok(value) => value
+            .parse()
+            .map_err(|error| anyhow::Error::msg(format!("Unable to parse {name}: {error}"))),
+        Err(_) => Ok(default),
+    }
+}
@@ -0,0 +1,141 @@
+//! Cookie session mode - access and refresh tokens are kept in `HttpOnly` cookies, so scripts
+//! (and XSS in rendered markdown) can't read them.
+//!
+//! Enabled by `FOLKERS_SESSION_COOKIES`. Cookies are sent by browser automatically, so
+//! state-changing requests authorized with them must repeat value of readable CSRF cookie
+//! in `X-CSRF-Token` header (double-submit). `Authorization` header is still accepted
+//! and doesn't require CSRF token.
+
+use axum::http::{HeaderMap, Method, header};
+use base64::{Engine as _, engine::general_purpose};
+use rand_core::{OsRng, RngCore};
+
+use super::backend::env_bool;
+
+/// Access token cookie (`HttpOnly`)
+pub const ACCESS_COOKIE: &str = "folkers_session";
+/// Refresh token cookie (`HttpOnly`)
+pub const REFRESH_COOKIE: &str = "folkers_refresh";
+/// CSRF token cookie, readable by frontend
+pub const CSRF_COOKIE: &str = "folkers_csrf";
+/// Header which must repeat CSRF cookie value
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+#[derive(Debug, Clone)]
+pub struct CookieConfig {
+    /// `Secure` attribute (disable only for local development over plain HTTP)
+    pub secure: bool,
+    /// `SameSite` attribute: `Strict` or `Lax`
+    pub same_site: String,
+}
+
+impl CookieConfig {
+    /// Reads configuration from `FOLKERS_SESSION_COOKIES`/`FOLKERS_COOKIE_*` variables
+    /// (`None` if cookie session mode is disabled)
+    pub fn from_env() -> Result<Option<Self>, anyhow::Error> {
+        if !env_bool("FOLKERS_SESSION_COOKIES", false)? {
+            return Ok(None);
+        }
+
+        let same_site = match std::env::var("FOLKERS_COOKIE_SAME_SITE") {
+            Ok(value) if value.eq_ignore_ascii_case("strict") => "Strict",
+            Ok(value) if value.eq_ignore_ascii_case("lax") => "Lax",
+            Ok(value) => {
+                return Err(anyhow::Error::msg(format!(
+                    "Unable to parse FOLKERS_COOKIE_SAME_SITE: `{value}` (expected Strict or Lax)"
+                )));
+            }
+            Err(_) => "Strict",
+        };
+
+        Ok(Some(Self {
+            secure: env_bool("FOLKERS_COOKIE_SECURE", true)?,
+            same_site: same_site.to_owned(),
+        }))
+    }
+
+    fn cookie(&self, name: &str, value: &str, max_age: i64, http_only: bool) -> String {
+        let mut cookie = format!(
+            "{name}={value}; Path=/; Max-Age={max_age}; SameSite={}",
+            self.same_site
+        );
+
+        if http_only {
+            cookie.push_str("; HttpOnly");
+        }
+
+        if self.secure {
+            cookie.push_str("; Secure");
+        }
+
+        cookie
+    }
+
+    /// `Set-Cookie` values for new token pair and fresh CSRF token. <br/>
+    /// Cookies live as long as refresh token, expired access token is rejected with
+    /// `401 UNAUTHORIZED`, so client knows it must refresh.
+    pub fn session_cookies(&self, token: &str, refresh_token: &str, lifetime: i64) -> [String; 3] {
+        [
+            self.cookie(ACCESS_COOKIE, token, lifetime, true),
+            self.cookie(REFRESH_COOKIE, refresh_token, lifetime, true),
+            self.cookie(CSRF_COOKIE, &generate_csrf_token(), lifetime, false),
+        ]
+    }
+
+    /// `Set-Cookie` values removing session cookies
+    pub fn clear_cookies(&self) -> [String; 3] {
+        [
+            self.cookie(ACCESS_COOKIE, "", 0, true),
+            self.cookie(REFRESH_COOKIE, "", 0, true),
+            self.cookie(CSRF_COOKIE, "", 0, false),
+        ]
+    }
+}
+
+/// Random 256 bits, base64url encoded
+fn generate_csrf_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+
+    general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Finds cookie value in `Cookie` headers
+pub fn get(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value.to_owned())
+        .filter(|value| !value.is_empty())
+}
+
+/// Requests which don't change state don't need CSRF token
+pub fn is_safe_method(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+/// Double-submit check: `X-CSRF-Token` header must equal CSRF cookie
+pub fn verify_csrf(headers: &HeaderMap) -> bool {
+    let Some(cookie) = get(headers, CSRF_COOKIE) else {
+        return false;
+    };
+
+    let Some(header) = headers
+        .get(CSRF_HEADER)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return false;
+    };
+
+    // constant time comparison
+    cookie.len() == header.len()
+        && cookie
+            .bytes()
+            .zip(header.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
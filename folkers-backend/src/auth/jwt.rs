@@ -1,8 +1,32 @@
 //! JWT - Json Web Tokens, used for users auth and verification (to avoid external access).
 
-use super::user;
-use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use super::{keys, user};
+use base64::{Engine as _, engine::general_purpose};
+use jsonwebtoken::{
+    Algorithm, Header, Validation, decode, decode_header, encode,
+    errors::{Error, ErrorKind},
+    jwk::JwkSet,
+};
+use rand_core::{OsRng, RngCore};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Default access token lifetime (minutes)
+const DEFAULT_ACCESS_EXPIRATION_MINUTES: i64 = 15;
+/// Default refresh token lifetime (days)
+const DEFAULT_REFRESH_EXPIRATION_DAYS: i64 = 14;
+/// Two-factor challenge lifetime (minutes)
+const CHALLENGE_EXPIRATION_MINUTES: i64 = 5;
+/// Audience of two-factor challenge tokens (so they can't be used as access tokens)
+const CHALLENGE_AUDIENCE: &str = "folkers-2fa";
+/// Default `iss` claim
+const DEFAULT_ISSUER: &str = "folkers";
+/// Default `aud` claim of access tokens
+const DEFAULT_AUDIENCE: &str = "folkers-api";
+/// Allowed clock difference between services (seconds)
+const LEEWAY_SECONDS: u64 = 60;
+/// Impersonation token lifetime (minutes), it can't be refreshed
+pub const IMPERSONATION_EXPIRATION_MINUTES: i64 = 15;
 
 /// Claims for JWT token
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,31 +37,104 @@ pub struct Claims {
     pub username: String,
     /// User Role
     pub role: String,
+    /// Token Issuer
+    pub iss: String,
+    /// Token Audience
+    pub aud: String,
+    /// Token Issue Timestamp
+    pub iat: usize,
+    /// Token Is Not Valid Before This Timestamp
+    pub nbf: usize,
+    /// Token Expiration Timestamp
+    pub exp: usize,
+    /// Unique Token Identifier (used for revocation)
+    pub jti: String,
+    /// Session Identifier
+    pub sid: String,
+    /// Real actor when token impersonates user (RFC 8693)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<ActorClaim>,
+}
+
+/// Admin acting on behalf of impersonated user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActorClaim {
+    /// Admin ID
+    pub sub: String,
+    /// Admin Name
+    pub username: String,
+}
+
+/// Claims for pending two-factor challenge token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChallengeClaims {
+    /// User ID
+    pub sub: String,
+    /// User Name
+    pub username: String,
+    /// Challenge Kind (`totp` or `totp_enrollment`)
+    pub challenge: String,
+    /// Token Issuer
+    pub iss: String,
+    /// Audience, always `folkers-2fa`
+    pub aud: String,
+    /// Token Issue Timestamp
+    pub iat: usize,
+    /// Token Is Not Valid Before This Timestamp
+    pub nbf: usize,
     /// Token Expiration Timestamp
     pub exp: usize,
+    /// Unique Token Identifier (challenge is answered only once)
+    pub jti: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct JwtConfig {
-    pub secret: String,
+    /// Ed25519 signing and verification keys
+    pub keys: keys::KeyStore,
+    /// `iss` claim
+    pub issuer: String,
+    /// `aud` claim of access tokens
+    pub audience: String,
+    /// Access token lifetime
     pub expiration: chrono::Duration,
+    /// Refresh token lifetime
+    pub refresh_expiration: chrono::Duration,
 }
 
 impl JwtConfig {
     pub fn new() -> Result<Self, anyhow::Error> {
-        match std::env::var("FOLKERS_JWT_SECRET") {
-            Ok(secret_var) => Ok(Self {
-                secret: secret_var,
-                expiration: chrono::Duration::hours(2),
-            }),
-            Err(error) => Err(anyhow::Error::msg(format!(
-                "Unable to fetch FOLKERS_JWT_SECRET: {error}"
-            ))),
+        let expiration = Self::env_number(
+            "FOLKERS_JWT_EXPIRATION_MINUTES",
+            DEFAULT_ACCESS_EXPIRATION_MINUTES,
+        )?;
+        let refresh_expiration = Self::env_number(
+            "FOLKERS_JWT_REFRESH_EXPIRATION_DAYS",
+            DEFAULT_REFRESH_EXPIRATION_DAYS,
+        )?;
+
+        Ok(Self {
+            keys: keys::KeyStore::load()?,
+            issuer: std::env::var("FOLKERS_JWT_ISSUER")
+                .unwrap_or_else(|_| DEFAULT_ISSUER.to_owned()),
+            audience: std::env::var("FOLKERS_JWT_AUDIENCE")
+                .unwrap_or_else(|_| DEFAULT_AUDIENCE.to_owned()),
+            expiration: chrono::Duration::minutes(expiration),
+            refresh_expiration: chrono::Duration::days(refresh_expiration),
+        })
+    }
+
+    fn env_number(name: &str, default: i64) -> Result<i64, anyhow::Error> {
+        match std::env::var(name) {
+            Ok(value) => value
+                .parse()
+                .map_err(|error| anyhow::Error::msg(format!("Unable to parse {name}: {error}"))),
+            Err(_) => Ok(default),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct JwtService {
     config: JwtConfig,
 }
@@ -47,9 +144,45 @@ impl JwtService {
         Self { config }
     }
 
-    pub fn generate_token(&self, user: &user::User) -> Result<String, jsonwebtoken::errors::Error> {
-        let expiration = chrono::Utc::now()
-            .checked_add_signed(self.config.expiration)
+    /// Access token lifetime
+    pub fn expiration(&self) -> chrono::Duration {
+        self.config.expiration
+    }
+
+    /// Refresh token lifetime
+    pub fn refresh_expiration(&self) -> chrono::Duration {
+        self.config.refresh_expiration
+    }
+
+    pub fn generate_token(&self, user: &user::User, session_id: &str) -> Result<String, Error> {
+        self.generate_access_token(user, session_id, self.config.expiration, None)
+    }
+
+    /// Generates short-lived token of `user` on behalf of `actor` (`act` claim)
+    pub fn generate_impersonation_token(
+        &self,
+        user: &user::User,
+        session_id: &str,
+        actor: ActorClaim,
+    ) -> Result<String, Error> {
+        self.generate_access_token(
+            user,
+            session_id,
+            chrono::Duration::minutes(IMPERSONATION_EXPIRATION_MINUTES),
+            Some(actor),
+        )
+    }
+
+    fn generate_access_token(
+        &self,
+        user: &user::User,
+        session_id: &str,
+        lifetime: chrono::Duration,
+        act: Option<ActorClaim>,
+    ) -> Result<String, Error> {
+        let now = chrono::Utc::now();
+        let expiration = now
+            .checked_add_signed(lifetime)
             .expect("unable to calculate expiration")
             .timestamp() as usize;
 
@@ -57,24 +190,120 @@ impl JwtService {
             sub: user.id.clone(),
             username: user.username.clone(),
             role: user.role.to_string(),
+            iss: self.config.issuer.clone(),
+            aud: self.config.audience.clone(),
+            iat: now.timestamp() as usize,
+            nbf: now.timestamp() as usize,
             exp: expiration,
+            jti: uuid::Uuid::new_v4().to_string(),
+            sid: session_id.to_owned(),
+            act,
         };
 
-        encode(
-            &Header::default(),
-            &claims,
-            &EncodingKey::from_secret(self.config.secret.as_ref()),
-        )
+        self.sign(&claims)
     }
 
-    pub fn verify_token(&self, token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
-        let validation = Validation::new(Algorithm::HS256);
+    pub fn verify_token(&self, token: &str) -> Result<Claims, Error> {
+        let claims: Claims = self.verify(token, &self.config.audience)?;
+        Self::verify_issued_at(claims.iat)?;
 
-        decode::<Claims>(
-            token,
-            &DecodingKey::from_secret(self.config.secret.as_ref()),
-            &validation,
-        )
-        .map(|data| data.claims)
+        Ok(claims)
+    }
+
+    /// Generates short-lived token which proves that password was already verified,
+    /// returns it with its claims (so challenge can be stored as pending)
+    pub fn generate_challenge_token(
+        &self,
+        user: &user::User,
+        challenge: &str,
+    ) -> Result<(String, ChallengeClaims), Error> {
+        let now = chrono::Utc::now();
+        let expiration = now
+            .checked_add_signed(chrono::Duration::minutes(CHALLENGE_EXPIRATION_MINUTES))
+            .expect("unable to calculate expiration")
+            .timestamp() as usize;
+
+        let claims = ChallengeClaims {
+            sub: user.id.clone(),
+            username: user.username.clone(),
+            challenge: challenge.to_owned(),
+            iss: self.config.issuer.clone(),
+            aud: CHALLENGE_AUDIENCE.to_owned(),
+            iat: now.timestamp() as usize,
+            nbf: now.timestamp() as usize,
+            exp: expiration,
+            jti: uuid::Uuid::new_v4().to_string(),
+        };
+
+        Ok((self.sign(&claims)?, claims))
+    }
+
+    pub fn verify_challenge_token(&self, token: &str) -> Result<ChallengeClaims, Error> {
+        let claims: ChallengeClaims = self.verify(token, CHALLENGE_AUDIENCE)?;
+        Self::verify_issued_at(claims.iat)?;
+
+        Ok(claims)
+    }
+
+    /// Public verification keys for other services (`/.well-known/jwks.json`)
+    pub fn jwks(&self) -> JwkSet {
+        self.config.keys.jwks()
+    }
+
+    /// Signs claims with active key and puts its id into `kid` header
+    fn sign(&self, claims: &impl Serialize) -> Result<String, Error> {
+        let (kid, key) = self.config.keys.signing_key();
+
+        let mut header = Header::new(Algorithm::EdDSA);
+        header.kid = Some(kid.to_owned());
+
+        encode(&header, claims, key)
+    }
+
+    /// Verifies signature with key from `kid` header and validates `iss`, `aud`, `nbf`, `exp`
+    fn verify<T: serde::de::DeserializeOwned + Clone>(
+        &self,
+        token: &str,
+        audience: &str,
+    ) -> Result<T, Error> {
+        let header = decode_header(token)?;
+        let key = header
+            .kid
+            .as_deref()
+            .and_then(|kid| self.config.keys.decoding_key(kid))
+            .ok_or_else(|| Error::from(ErrorKind::InvalidSignature))?;
+
+        let mut validation = Validation::new(Algorithm::EdDSA);
+        validation.leeway = LEEWAY_SECONDS;
+        validation.validate_nbf = true;
+        validation.set_issuer(&[&self.config.issuer]);
+        validation.set_audience(&[audience]);
+        validation.set_required_spec_claims(&["exp", "nbf", "iss", "aud", "sub"]);
+
+        decode::<T>(token, key, &validation).map(|data| data.claims)
+    }
+
+    /// Tokens issued in the future are rejected
+    fn verify_issued_at(iat: usize) -> Result<(), Error> {
+        let now = chrono::Utc::now().timestamp() as usize;
+
+        if iat > now + LEEWAY_SECONDS as usize {
+            return Err(Error::from(ErrorKind::ImmatureSignature));
+        }
+
+        Ok(())
+    }
+
+    /// Generates opaque refresh token (random 256 bits, base64url encoded)
+    pub fn generate_refresh_token(&self) -> String {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+
+        general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    /// Refresh tokens are stored as SHA256 hashes
+    pub fn hash_refresh_token(token: &str) -> String {
+        hex::encode(Sha256::digest(token.as_bytes()))
     }
 }
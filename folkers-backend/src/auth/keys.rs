@@ -0,0 +1,225 @@
+//! JWT signing keys - Ed25519 key pairs stored as PEM files in keys directory.
+//!
+//! Every `<kid>.pem` file is a PKCS#8 private key, every `<kid>.pub.pem` file is a public key
+//! which is only accepted for verification (retired key). New tokens are signed with the active
+//! key, so keys can be rotated by adding a new one while old tokens stay valid until expiration.
+
+use base64::{Engine as _, engine::general_purpose};
+use ed25519_dalek::{
+    SigningKey, VerifyingKey,
+    pkcs8::{DecodePrivateKey, DecodePublicKey, EncodePrivateKey, spki::der::pem::LineEnding},
+};
+use jsonwebtoken::{
+    DecodingKey, EncodingKey,
+    jwk::{
+        AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+        OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse,
+    },
+};
+use rand_core::OsRng;
+use std::path::Path;
+
+const PRIVATE_KEY_EXTENSION: &str = ".pem";
+const PUBLIC_KEY_EXTENSION: &str = ".pub.pem";
+
+#[derive(Clone)]
+struct JwtKey {
+    kid: String,
+    /// Empty for retired keys (verification only)
+    encoding: Option<EncodingKey>,
+    decoding: DecodingKey,
+    public_key: [u8; 32],
+}
+
+#[derive(Clone)]
+pub struct KeyStore {
+    active: String,
+    keys: Vec<JwtKey>,
+}
+
+impl KeyStore {
+    /// Loads keys from `FOLKERS_JWT_KEYS_DIR`, generates first key if directory is empty
+    pub fn load() -> Result<Self, anyhow::Error> {
+        let keys_dir = std::env::var("FOLKERS_JWT_KEYS_DIR").unwrap_or_else(|_| {
+            log::warn!(
+                "Environment variable `FOLKERS_JWT_KEYS_DIR` is not found, default is `./jwt_keys`"
+            );
+            String::from("./jwt_keys")
+        });
+
+        let keys_dir = Path::new(&keys_dir);
+        std::fs::create_dir_all(keys_dir)?;
+
+        let mut keys = Self::read_keys(keys_dir)?;
+
+        if !keys.iter().any(|key| key.encoding.is_some()) {
+            log::warn!(
+                "No JWT signing keys found in `{}`, generating new one",
+                keys_dir.display()
+            );
+            keys.push(Self::generate_key(keys_dir)?);
+        }
+
+        let active = match std::env::var("FOLKERS_JWT_ACTIVE_KEY_ID") {
+            Ok(kid) => kid,
+            // key ids are sortable, the newest one is used by default
+            Err(_) => keys
+                .iter()
+                .filter(|key| key.encoding.is_some())
+                .map(|key| key.kid.clone())
+                .max()
+                .unwrap_or_default(),
+        };
+
+        if !keys
+            .iter()
+            .any(|key| key.kid == active && key.encoding.is_some())
+        {
+            return Err(anyhow::Error::msg(format!(
+                "Active JWT signing key `{active}` is not found"
+            )));
+        }
+
+        log::info!(
+            "- Loaded {} JWT key(s), active key: `{}`",
+            keys.len(),
+            active
+        );
+
+        Ok(Self { active, keys })
+    }
+
+    fn read_keys(keys_dir: &Path) -> Result<Vec<JwtKey>, anyhow::Error> {
+        let mut keys: Vec<JwtKey> = Vec::new();
+        let mut entries = std::fs::read_dir(keys_dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .collect::<Vec<_>>();
+
+        // private keys (`.pem`) go before public ones (`.pub.pem`) with same id
+        entries.sort();
+
+        for path in entries {
+            let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+
+            let key = if let Some(kid) = file_name.strip_suffix(PUBLIC_KEY_EXTENSION) {
+                let pem = std::fs::read_to_string(&path)?;
+                let verifying_key = VerifyingKey::from_public_key_pem(&pem).map_err(|err| {
+                    anyhow::Error::msg(format!("Unable to read `{}`: {err}", path.display()))
+                })?;
+
+                Self::make_key(kid, None, verifying_key)?
+            } else if let Some(kid) = file_name.strip_suffix(PRIVATE_KEY_EXTENSION) {
+                let pem = std::fs::read_to_string(&path)?;
+                let signing_key = SigningKey::from_pkcs8_pem(&pem).map_err(|err| {
+                    anyhow::Error::msg(format!("Unable to read `{}`: {err}", path.display()))
+                })?;
+
+                Self::make_key(kid, Some(&signing_key), signing_key.verifying_key())?
+            } else {
+                continue;
+            };
+
+            if keys.iter().any(|existing| existing.kid == key.kid) {
+                continue;
+            }
+
+            keys.push(key);
+        }
+
+        Ok(keys)
+    }
+
+    fn generate_key(keys_dir: &Path) -> Result<JwtKey, anyhow::Error> {
+        let signing_key = SigningKey::generate(&mut OsRng);
+        let kid = chrono::Utc::now().format("%Y%m%d%H%M%S").to_string();
+
+        let pem = signing_key
+            .to_pkcs8_pem(LineEnding::LF)
+            .map_err(|err| anyhow::Error::msg(format!("Unable to encode JWT key: {err}")))?;
+
+        let path = keys_dir.join(format!("{kid}{PRIVATE_KEY_EXTENSION}"));
+        std::fs::write(&path, pem.as_bytes())?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+        }
+
+        Self::make_key(&kid, Some(&signing_key), signing_key.verifying_key())
+    }
+
+    fn make_key(
+        kid: &str,
+        signing_key: Option<&SigningKey>,
+        verifying_key: VerifyingKey,
+    ) -> Result<JwtKey, anyhow::Error> {
+        let encoding = match signing_key {
+            Some(signing_key) => {
+                let der = signing_key.to_pkcs8_der().map_err(|err| {
+                    anyhow::Error::msg(format!("Unable to encode JWT key: {err}"))
+                })?;
+
+                Some(EncodingKey::from_ed_der(der.as_bytes()))
+            }
+            None => None,
+        };
+
+        Ok(JwtKey {
+            kid: kid.to_owned(),
+            encoding,
+            decoding: DecodingKey::from_ed_der(verifying_key.as_bytes()),
+            public_key: verifying_key.to_bytes(),
+        })
+    }
+
+    /// Active key id and its encoding key
+    pub fn signing_key(&self) -> (&str, &EncodingKey) {
+        let key = self
+            .keys
+            .iter()
+            .find(|key| key.kid == self.active)
+            .expect("active key is verified on load");
+
+        (
+            &key.kid,
+            key.encoding
+                .as_ref()
+                .expect("active key is verified on load"),
+        )
+    }
+
+    /// Verification key by its id (`kid` header)
+    pub fn decoding_key(&self, kid: &str) -> Option<&DecodingKey> {
+        self.keys
+            .iter()
+            .find(|key| key.kid == kid)
+            .map(|key| &key.decoding)
+    }
+
+    /// Public keys in JWK Set format
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self
+                .keys
+                .iter()
+                .map(|key| Jwk {
+                    common: CommonParameters {
+                        public_key_use: Some(PublicKeyUse::Signature),
+                        key_algorithm: Some(KeyAlgorithm::EdDSA),
+                        key_id: Some(key.kid.clone()),
+                        ..Default::default()
+                    },
+                    algorithm: AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                        key_type: OctetKeyPairType::OctetKeyPair,
+                        curve: EllipticCurve::Ed25519,
+                        x: general_purpose::URL_SAFE_NO_PAD.encode(key.public_key),
+                    }),
+                })
+                .collect(),
+        }
+    }
+}
@@ -2,13 +2,27 @@
 
 use crate::DATABASE;
 use argon2::{
-    Argon2,
-    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Algorithm, Argon2, Params, Version,
+    password_hash::{PasswordHasher, SaltString, rand_core::OsRng},
 };
 use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock, RwLock},
+};
 
+pub mod api_token;
+pub mod backend;
+pub mod cookie;
 pub mod jwt;
+pub mod keys;
+pub mod oidc;
+pub mod password_policy;
+pub mod permission;
+pub mod throttle;
+pub mod totp;
 pub mod user;
+pub mod webauthn;
 
 /// Login Request Payload
 #[derive(Debug, Deserialize)]
@@ -20,58 +34,322 @@ pub struct LoginRequest {
 /// Login Server Response
 #[derive(Debug, Serialize)]
 pub struct AuthResponse {
+    /// Omitted in cookie session mode (sent in `HttpOnly` cookie)
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub token: String,
+    pub token_type: String,
+    /// Access token lifetime in seconds
+    pub expires_in: i64,
+    /// Opaque token to obtain new access token via `/refresh` (omitted in cookie session mode)
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub refresh_token: String,
+    /// Only `/me/password` is available until password is changed
+    pub password_change_required: bool,
+}
+
+/// Impersonation Server Response (token can't be refreshed)
+#[derive(Debug, Serialize)]
+pub struct ImpersonationResponse {
     pub token: String,
     pub token_type: String,
+    /// Access token lifetime in seconds
+    pub expires_in: i64,
+}
+
+/// Login Server Response when second factor is required
+#[derive(Debug, Serialize)]
+pub struct TwoFactorChallenge {
+    /// Short-lived token for `/login/2fa` endpoints
+    pub challenge_token: String,
+    /// `totp` - code is required, `totp_enrollment` - user must enroll first (required by role)
+    pub challenge: String,
+}
+
+/// Two-Factor Login Request Payload
+#[derive(Debug, Deserialize)]
+pub struct TwoFactorRequest {
+    pub challenge_token: String,
+    /// Code from authenticator app
+    pub code: Option<String>,
+    /// One-time recovery code (alternative to `code`)
+    pub recovery_code: Option<String>,
+}
+
+/// Two-Factor Enrollment During Login Payload
+#[derive(Debug, Deserialize)]
+pub struct ChallengeRequest {
+    pub challenge_token: String,
+}
+
+/// TOTP Code Payload
+#[derive(Debug, Deserialize)]
+pub struct TotpCodeRequest {
+    /// Code from authenticator app or one-time recovery code
+    pub code: String,
+}
+
+/// Current Password Payload (confirms sensitive actions)
+#[derive(Debug, Deserialize)]
+pub struct PasswordConfirmation {
+    pub password: String,
+}
+
+/// TOTP Enrollment Response (shown only once)
+#[derive(Debug, Serialize)]
+pub struct TotpEnrollment {
+    /// Base32 encoded secret
+    pub secret: String,
+    /// `otpauth://` provisioning URI
+    pub otpauth_uri: String,
+    /// One-time recovery codes
+    pub recovery_codes: Vec<String>,
 }
 
+/// Refresh Request Payload
+#[derive(Debug, Deserialize)]
+pub struct RefreshRequest {
+    /// Can be empty in cookie session mode (refresh token cookie is used)
+    #[serde(default)]
+    pub refresh_token: String,
+}
+
+/// Password field of users authenticated by external identity provider (never verified)
+pub const EXTERNAL_PASSWORD: &str = "!";
+
+/// Argon2 parameters for new hashes (already checked in main function)
+static ARGON2_PARAMS: LazyLock<Params> =
+    LazyLock::new(|| UserRepository::argon2_params().unwrap_or_default());
+
+/// Users and roles resolved for request authorization are cached for this time
+/// (mutations invalidate cache immediately, TTL only limits staleness between instances)
+const CACHE_TTL_SECONDS: i64 = 30;
+
+#[derive(Debug, Clone)]
+struct Cached<T> {
+    value: T,
+    cached_at: chrono::DateTime<chrono::Utc>,
+}
+
+type Cache<T> = Arc<RwLock<HashMap<String, Cached<T>>>>;
+
 /// Wrapper for encapsulating database access to authenticate user
 #[derive(Clone)]
-pub struct UserRepository;
+pub struct UserRepository {
+    users: Cache<user::User>,
+    roles: Cache<Vec<permission::Permission>>,
+    /// External accounts state by user identifier
+    statuses: Cache<backend::AccountStatus>,
+    /// Authentication backends in order they are tried
+    backends: Arc<Vec<Box<dyn backend::AuthBackend>>>,
+}
 
 impl UserRepository {
-    pub fn new() -> Self {
-        Self
+    pub fn new(backends: Vec<Box<dyn backend::AuthBackend>>) -> Self {
+        Self {
+            users: Arc::new(RwLock::new(HashMap::new())),
+            roles: Arc::new(RwLock::new(HashMap::new())),
+            statuses: Arc::new(RwLock::new(HashMap::new())),
+            backends: Arc::new(backends),
+        }
+    }
+
+    /// Reads Argon2 parameters from environment variables: <br/>
+    /// `FOLKERS_ARGON2_MEMORY_COST` (KiB), `FOLKERS_ARGON2_TIME_COST`, `FOLKERS_ARGON2_PARALLELISM`
+    pub fn argon2_params() -> Result<Params, anyhow::Error> {
+        let env_param = |name: &str, default: u32| -> Result<u32, anyhow::Error> {
+            match std::env::var(name) {
+                Ok(value) => value
+                    .parse()
+                    .map_err(|error| anyhow::Error::msg(format!("Unable to parse {name}: {error}"))),
+                Err(_) => Ok(default),
+            }
+        };
+
+        Params::new(
+            env_param("FOLKERS_ARGON2_MEMORY_COST", Params::DEFAULT_M_COST)?,
+            env_param("FOLKERS_ARGON2_TIME_COST", Params::DEFAULT_T_COST)?,
+            env_param("FOLKERS_ARGON2_PARALLELISM", Params::DEFAULT_P_COST)?,
+            None,
+        )
+        .map_err(|error| anyhow::Error::msg(format!("Invalid Argon2 parameters: {error}")))
     }
 
+    fn argon2() -> Argon2<'static> {
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, ARGON2_PARAMS.clone())
+    }
+
+    /// Hashes password with its own random salt
     pub fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
-        let env_var = std::env::var("FOLKERS_BASE64_SALT").unwrap(); // already
-        // checked in main function
+        let salt = SaltString::generate(&mut OsRng);
 
-        let salt = SaltString::from_b64(&env_var).unwrap();
-        Argon2::default()
+        Self::argon2()
             .hash_password(password.as_ref(), &salt)
             .map(|hash| hash.to_string())
     }
 
     pub async fn find_by_username(&self, username: &str) -> Option<user::User> {
         if let Ok(opt) = DATABASE.get_user_by_username(username.to_string()).await {
-            return opt.map(|record| user::User {
-                id: record
-                    .id
-                    .map(|id| id.id.to_string())
-                    .unwrap_or("none".to_string()),
-                username: record.username,
-                password_hash: record.password,
-                role: user::UserRole::from_str(record.role),
-            });
+            return opt.map(Self::user_from_record);
         }
 
         None
     }
 
+    pub async fn find_by_id(&self, id: &str) -> Option<user::User> {
+        DATABASE.get_user(id).await.map(Self::user_from_record)
+    }
+
+    /// Resolves current user state for request authorization (cached)
+    pub async fn current_user(&self, id: &str) -> Option<user::User> {
+        if let Some(user) = Self::cache_get(&self.users, id) {
+            return Some(user);
+        }
+
+        // deleted users are not cached, their requests are rejected anyway
+        let user = self.find_by_id(id).await?;
+        Self::cache_put(&self.users, id, user.clone());
+
+        Some(user)
+    }
+
+    /// Resolves permissions granted by role (cached), unknown role grants nothing
+    pub async fn role_permissions(
+        &self,
+        role: &str,
+    ) -> Result<Vec<permission::Permission>, surrealdb::Error> {
+        if let Some(permissions) = Self::cache_get(&self.roles, role) {
+            return Ok(permissions);
+        }
+
+        let permissions = DATABASE
+            .get_role(role)
+            .await?
+            .map(|record| record.permissions)
+            .unwrap_or_default();
+
+        Self::cache_put(&self.roles, role, permissions.clone());
+        Ok(permissions)
+    }
+
+    /// Must be called after every user mutation (role, username, deletion)
+    pub fn invalidate(&self, id: &str) {
+        if let Ok(mut cache) = self.users.write() {
+            cache.remove(id);
+        }
+
+        if let Ok(mut cache) = self.statuses.write() {
+            cache.remove(id);
+        }
+    }
+
+    /// Must be called after every role mutation
+    pub fn invalidate_role(&self, role: &str) {
+        if let Ok(mut cache) = self.roles.write() {
+            cache.remove(role);
+        }
+    }
+
+    fn cache_get<T: Clone>(cache: &Cache<T>, key: &str) -> Option<T> {
+        let now = chrono::Utc::now();
+
+        cache
+            .read()
+            .ok()?
+            .get(key)
+            .filter(|cached| (now - cached.cached_at).num_seconds() < CACHE_TTL_SECONDS)
+            .map(|cached| cached.value.clone())
+    }
+
+    fn cache_put<T>(cache: &Cache<T>, key: &str, value: T) {
+        let now = chrono::Utc::now();
+
+        if let Ok(mut cache) = cache.write() {
+            cache.retain(|_, cached| (now - cached.cached_at).num_seconds() < CACHE_TTL_SECONDS);
+            cache.insert(
+                key.to_owned(),
+                Cached {
+                    value,
+                    cached_at: now,
+                },
+            );
+        }
+    }
+
+    pub fn user_from_record(record: crate::database::user::UserRecord) -> user::User {
+        user::User {
+            id: record
+                .id
+                .map(|id| id.id.to_string())
+                .unwrap_or("none".to_string()),
+            username: record.username,
+            password_hash: record.password,
+            role: record.role,
+            two_factor_enabled: record.totp_enabled,
+            must_change_password: record.must_change_password,
+            password_changed_at: record.password_changed_at.map(|datetime| datetime.0),
+            external_id: record.external_id,
+            protected: record.protected,
+            disabled: record.disabled,
+            expires_at: record.expires_at.map(|datetime| datetime.0),
+        }
+    }
+
+    /// Verifies password of local account (used to confirm sensitive actions)
     pub async fn verify_password(&self, username: &str, password: &str) -> bool {
-        if let Some(user) = self.find_by_username(username).await {
-            let password_hash =
-                PasswordHash::parse(&user.password_hash, argon2::password_hash::Encoding::B64);
-            if password_hash.is_err() {
-                return false;
-            };
-
-            return Argon2::default()
-                .verify_password(password.as_ref(), &password_hash.unwrap())
-                .is_ok();
+        backend::local::LocalBackend::verify(username, password)
+            .await
+            .is_some()
+    }
+
+    /// Authenticates user with configured backends in their order
+    pub async fn authenticate(&self, username: &str, password: &str) -> Option<backend::Identity> {
+        for backend in self.backends.iter() {
+            match backend.authenticate(username, password).await {
+                Ok(backend::Outcome::Authenticated(identity)) => return Some(identity),
+                Ok(backend::Outcome::Rejected) => return None,
+                Ok(backend::Outcome::NotFound) => continue,
+                // unavailable backend must not block the others
+                Err(err) => log::error!(
+                    "`{}` got `{}` authentication backend error: {}",
+                    username,
+                    backend.name(),
+                    err
+                ),
+            }
         }
 
-        false
+        None
+    }
+
+    /// Checks external account state in its backend (unavailable backend keeps account active)
+    pub async fn account_status(&self, user: &user::User) -> backend::AccountStatus {
+        for backend in self.backends.iter() {
+            match backend.account_status(user).await {
+                Ok(backend::AccountStatus::Unmanaged) => continue,
+                Ok(status) => return status,
+                Err(err) => log::error!(
+                    "`{} ({})` got `{}` authentication backend error: {}",
+                    user.username,
+                    user.id,
+                    backend.name(),
+                    err
+                ),
+            }
+        }
+
+        backend::AccountStatus::Unmanaged
+    }
+
+    /// Checks external account state for request authorization (cached, directory isn't queried
+    /// on every request)
+    pub async fn current_account_status(&self, user: &user::User) -> backend::AccountStatus {
+        if let Some(status) = Self::cache_get(&self.statuses, &user.id) {
+            return status;
+        }
+
+        let status = self.account_status(user).await;
+        Self::cache_put(&self.statuses, &user.id, status.clone());
+
+        status
     }
 }
@@ -0,0 +1,478 @@
+//! OpenID Connect single sign-on - authorization code flow with PKCE against external identity provider.
+//!
+//! Provider metadata (`/.well-known/openid-configuration`) and its keys are fetched on first login and
+//! cached, keys are fetched again when ID token is signed with unknown key (rotation on IdP side).
+
+use super::backend;
+use base64::{Engine as _, engine::general_purpose};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, Validation, decode, decode_header,
+    jwk::{Jwk, JwkSet},
+};
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Pending login (state, nonce, PKCE verifier) lifetime (minutes)
+pub const LOGIN_EXPIRATION_MINUTES: i64 = 10;
+/// Prefix of `external_id` for users linked with identity provider
+const EXTERNAL_ID_PREFIX: &str = "oidc:";
+/// Allowed clock difference with identity provider (seconds)
+const LEEWAY_SECONDS: u64 = 60;
+/// Identity provider requests timeout (seconds)
+const REQUEST_TIMEOUT_SECONDS: u64 = 10;
+
+const DEFAULT_SCOPES: &str = "openid profile email";
+const DEFAULT_USERNAME_CLAIM: &str = "preferred_username";
+const DEFAULT_GROUPS_CLAIM: &str = "groups";
+
+/// Only asymmetric algorithms are accepted (client secret is never used as signing key)
+const ALLOWED_ALGORITHMS: [Algorithm; 9] = [
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::EdDSA,
+];
+
+#[derive(Debug)]
+pub enum OidcError {
+    /// Identity provider is unreachable or responded with unexpected data
+    Provider(String),
+    /// Authorization code, ID token or claims were rejected
+    Rejected(String),
+}
+
+impl std::fmt::Display for OidcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OidcError::Provider(message) => write!(f, "identity provider error: {message}"),
+            OidcError::Rejected(message) => write!(f, "rejected: {message}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    /// Issuer URL, metadata is discovered from it
+    pub issuer: String,
+    pub client_id: String,
+    /// Confidential clients only (sent with HTTP Basic authentication)
+    pub client_secret: Option<String>,
+    /// Frontend page which receives authorization code
+    pub redirect_uri: String,
+    pub scopes: String,
+    /// Authorization endpoint for browsers, when IdP is reachable by another address from backend
+    pub authorization_endpoint: Option<String>,
+    /// Claim used as Folkers username
+    pub username_claim: String,
+    /// Claim with list of IdP groups
+    pub groups_claim: String,
+    /// Group to role pairs, the first matching group wins
+    pub role_mapping: Vec<(String, String)>,
+    /// Role of users without any mapped group (login is denied if empty)
+    pub default_role: Option<String>,
+    /// Create user on first login
+    pub auto_provision: bool,
+    /// Link local user with the same username on first login
+    pub link_existing: bool,
+}
+
+impl OidcConfig {
+    /// Reads configuration from `FOLKERS_OIDC_*` environment variables,
+    /// returns `None` if `FOLKERS_OIDC_ISSUER` is not set (single sign-on is disabled)
+    pub fn from_env() -> Result<Option<Self>, anyhow::Error> {
+        let Ok(issuer) = std::env::var("FOLKERS_OIDC_ISSUER") else {
+            return Ok(None);
+        };
+
+        let required = |name: &str| -> Result<String, anyhow::Error> {
+            std::env::var(name).map_err(|_| {
+                anyhow::Error::msg(format!(
+                    "{name} is required when FOLKERS_OIDC_ISSUER is set"
+                ))
+            })
+        };
+
+        Ok(Some(Self {
+            issuer: issuer.trim_end_matches('/').to_owned(),
+            client_id: required("FOLKERS_OIDC_CLIENT_ID")?,
+            client_secret: std::env::var("FOLKERS_OIDC_CLIENT_SECRET").ok(),
+            redirect_uri: required("FOLKERS_OIDC_REDIRECT_URI")?,
+            scopes: std::env::var("FOLKERS_OIDC_SCOPES")
+                .unwrap_or_else(|_| DEFAULT_SCOPES.to_owned()),
+            authorization_endpoint: std::env::var("FOLKERS_OIDC_AUTHORIZATION_ENDPOINT").ok(),
+            username_claim: std::env::var("FOLKERS_OIDC_USERNAME_CLAIM")
+                .unwrap_or_else(|_| DEFAULT_USERNAME_CLAIM.to_owned()),
+            groups_claim: std::env::var("FOLKERS_OIDC_GROUPS_CLAIM")
+                .unwrap_or_else(|_| DEFAULT_GROUPS_CLAIM.to_owned()),
+            role_mapping: backend::role_mapping_from_env("FOLKERS_OIDC_ROLE_MAPPING")?,
+            default_role: std::env::var("FOLKERS_OIDC_DEFAULT_ROLE").ok(),
+            auto_provision: backend::env_bool("FOLKERS_OIDC_AUTO_PROVISION", true)?,
+            link_existing: backend::env_bool("FOLKERS_OIDC_LINK_EXISTING", false)?,
+        }))
+    }
+}
+
+/// Part of `/.well-known/openid-configuration` used by Folkers
+#[derive(Debug, Clone, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+    userinfo_endpoint: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct Discovery {
+    metadata: ProviderMetadata,
+    jwks: JwkSet,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+    access_token: String,
+}
+
+/// Single sign-on start response
+#[derive(Debug, Serialize)]
+pub struct OidcAuthorization {
+    /// Identity provider page, browser must be redirected there
+    pub authorization_url: String,
+}
+
+/// Single sign-on callback payload (query parameters of redirect URI)
+#[derive(Debug, Deserialize)]
+pub struct OidcCallbackRequest {
+    pub code: String,
+    pub state: String,
+}
+
+/// Parameters of started login, kept until identity provider redirects back
+#[derive(Debug, Clone)]
+pub struct PendingLogin {
+    pub state: String,
+    pub nonce: String,
+    pub code_verifier: String,
+}
+
+/// User identity verified by identity provider
+#[derive(Debug, Clone)]
+pub struct ExternalIdentity {
+    /// `sub` claim
+    pub subject: String,
+    pub username: String,
+    pub groups: Vec<String>,
+}
+
+impl ExternalIdentity {
+    /// Value of user's `external_id` field
+    pub fn external_id(&self) -> String {
+        format!("{EXTERNAL_ID_PREFIX}{}", self.subject)
+    }
+}
+
+#[derive(Clone)]
+pub struct OidcProvider {
+    config: OidcConfig,
+    http: reqwest::Client,
+    discovery: Arc<RwLock<Option<Discovery>>>,
+}
+
+impl OidcProvider {
+    pub fn new(config: OidcConfig) -> Result<Self, anyhow::Error> {
+        let http = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(REQUEST_TIMEOUT_SECONDS))
+            .build()?;
+
+        Ok(Self {
+            config,
+            http,
+            discovery: Arc::new(RwLock::new(None)),
+        })
+    }
+
+    /// Generates state, nonce and PKCE verifier for new login
+    pub fn begin(&self) -> PendingLogin {
+        PendingLogin {
+            state: random_string(),
+            nonce: random_string(),
+            code_verifier: random_string(),
+        }
+    }
+
+    /// Identity provider URL to redirect browser to
+    pub async fn authorization_url(&self, login: &PendingLogin) -> Result<String, OidcError> {
+        let discovery = self.discovery(false).await?;
+        let endpoint = self
+            .config
+            .authorization_endpoint
+            .as_deref()
+            .unwrap_or(&discovery.metadata.authorization_endpoint);
+
+        let code_challenge =
+            general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(login.code_verifier.as_bytes()));
+
+        reqwest::Url::parse_with_params(
+            endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", self.config.client_id.as_str()),
+                ("redirect_uri", self.config.redirect_uri.as_str()),
+                ("scope", self.config.scopes.as_str()),
+                ("state", login.state.as_str()),
+                ("nonce", login.nonce.as_str()),
+                ("code_challenge", code_challenge.as_str()),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map(|url| url.to_string())
+        .map_err(|err| OidcError::Provider(format!("invalid authorization endpoint: {err}")))
+    }
+
+    /// Exchanges authorization code for tokens and verifies ID token
+    pub async fn exchange(
+        &self,
+        code: &str,
+        login: &PendingLogin,
+    ) -> Result<ExternalIdentity, OidcError> {
+        let discovery = self.discovery(false).await?;
+
+        let mut request = self.http.post(&discovery.metadata.token_endpoint).form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.config.redirect_uri.as_str()),
+            ("client_id", self.config.client_id.as_str()),
+            ("code_verifier", login.code_verifier.as_str()),
+        ]);
+
+        if let Some(client_secret) = &self.config.client_secret {
+            request = request.basic_auth(&self.config.client_id, Some(client_secret));
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|err| OidcError::Provider(format!("token request failed: {err}")))?;
+
+        // invalid, expired or already used code, or wrong verifier
+        if response.status().is_client_error() {
+            return Err(OidcError::Rejected(format!(
+                "token endpoint responded with {}",
+                response.status()
+            )));
+        }
+
+        let tokens: TokenResponse = response
+            .error_for_status()
+            .map_err(|err| OidcError::Provider(format!("token request failed: {err}")))?
+            .json()
+            .await
+            .map_err(|err| OidcError::Provider(format!("invalid token response: {err}")))?;
+
+        let mut claims = self.verify_id_token(&tokens.id_token, &login.nonce).await?;
+
+        // some providers put profile and groups claims only into userinfo
+        if (!claims.contains_key(&self.config.username_claim)
+            || !claims.contains_key(&self.config.groups_claim))
+            && let Some(userinfo_endpoint) = &discovery.metadata.userinfo_endpoint
+        {
+            let userinfo = self
+                .userinfo(userinfo_endpoint, &tokens.access_token)
+                .await?;
+
+            if userinfo.get("sub") != claims.get("sub") {
+                return Err(OidcError::Rejected(String::from(
+                    "userinfo `sub` doesn't match ID token",
+                )));
+            }
+
+            for (claim, value) in userinfo {
+                claims.entry(claim).or_insert(value);
+            }
+        }
+
+        self.identity(&claims)
+    }
+
+    /// Account with Folkers role mapped from identity provider groups
+    pub fn account(&self, identity: &ExternalIdentity) -> backend::ExternalAccount {
+        backend::ExternalAccount {
+            username: identity.username.clone(),
+            external_id: identity.external_id(),
+            role: backend::map_role(
+                &self.config.role_mapping,
+                self.config.default_role.as_deref(),
+                &identity.groups,
+            ),
+            link_existing: self.config.link_existing,
+            auto_provision: self.config.auto_provision,
+        }
+    }
+
+    async fn verify_id_token(
+        &self,
+        id_token: &str,
+        nonce: &str,
+    ) -> Result<serde_json::Map<String, serde_json::Value>, OidcError> {
+        let header = decode_header(id_token)
+            .map_err(|err| OidcError::Rejected(format!("invalid ID token: {err}")))?;
+
+        if !ALLOWED_ALGORITHMS.contains(&header.alg) {
+            return Err(OidcError::Rejected(format!(
+                "ID token algorithm {:?} is not allowed",
+                header.alg
+            )));
+        }
+
+        let mut discovery = self.discovery(false).await?;
+        let jwk = match Self::find_key(&discovery.jwks, header.kid.as_deref()) {
+            Some(jwk) => jwk,
+            None => {
+                // keys were rotated by identity provider
+                discovery = self.discovery(true).await?;
+                Self::find_key(&discovery.jwks, header.kid.as_deref()).ok_or_else(|| {
+                    OidcError::Rejected(String::from("ID token signing key is not found"))
+                })?
+            }
+        };
+
+        let key = DecodingKey::from_jwk(&jwk)
+            .map_err(|err| OidcError::Provider(format!("unsupported signing key: {err}")))?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.leeway = LEEWAY_SECONDS;
+        validation.set_issuer(&[&discovery.metadata.issuer]);
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        let claims =
+            decode::<serde_json::Map<String, serde_json::Value>>(id_token, &key, &validation)
+                .map_err(|err| OidcError::Rejected(format!("invalid ID token: {err}")))?
+                .claims;
+
+        if claims.get("nonce").and_then(|value| value.as_str()) != Some(nonce) {
+            return Err(OidcError::Rejected(String::from("ID token nonce mismatch")));
+        }
+
+        Ok(claims)
+    }
+
+    fn find_key(jwks: &JwkSet, kid: Option<&str>) -> Option<Jwk> {
+        match kid {
+            Some(kid) => jwks.find(kid).cloned(),
+            // key id may be omitted if provider has only one key
+            None if jwks.keys.len() == 1 => jwks.keys.first().cloned(),
+            None => None,
+        }
+    }
+
+    async fn userinfo(
+        &self,
+        endpoint: &str,
+        access_token: &str,
+    ) -> Result<serde_json::Map<String, serde_json::Value>, OidcError> {
+        self.http
+            .get(endpoint)
+            .bearer_auth(access_token)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| OidcError::Provider(format!("userinfo request failed: {err}")))?
+            .json()
+            .await
+            .map_err(|err| OidcError::Provider(format!("invalid userinfo response: {err}")))
+    }
+
+    fn identity(
+        &self,
+        claims: &serde_json::Map<String, serde_json::Value>,
+    ) -> Result<ExternalIdentity, OidcError> {
+        let subject = claims
+            .get("sub")
+            .and_then(|value| value.as_str())
+            .ok_or_else(|| OidcError::Rejected(String::from("`sub` claim is missing")))?;
+
+        let username = claims
+            .get(&self.config.username_claim)
+            .and_then(|value| value.as_str())
+            .map(str::trim)
+            .filter(|username| !username.is_empty())
+            .ok_or_else(|| {
+                OidcError::Rejected(format!("`{}` claim is missing", self.config.username_claim))
+            })?;
+
+        // groups claim may be a list or a single value
+        let groups = match claims.get(&self.config.groups_claim) {
+            Some(serde_json::Value::Array(groups)) => groups
+                .iter()
+                .filter_map(|group| group.as_str().map(str::to_owned))
+                .collect(),
+            Some(serde_json::Value::String(group)) => vec![group.clone()],
+            _ => Vec::new(),
+        };
+
+        Ok(ExternalIdentity {
+            subject: subject.to_owned(),
+            username: username.to_owned(),
+            groups,
+        })
+    }
+
+    /// Cached provider metadata and keys, `refresh` forces new fetch
+    async fn discovery(&self, refresh: bool) -> Result<Discovery, OidcError> {
+        if !refresh && let Some(discovery) = self.discovery.read().await.as_ref() {
+            return Ok(discovery.clone());
+        }
+
+        let mut cached = self.discovery.write().await;
+
+        let metadata: ProviderMetadata = self
+            .fetch_json(&format!(
+                "{}/.well-known/openid-configuration",
+                self.config.issuer
+            ))
+            .await?;
+
+        if metadata.issuer.trim_end_matches('/') != self.config.issuer {
+            return Err(OidcError::Provider(format!(
+                "discovered issuer `{}` doesn't match configured one",
+                metadata.issuer
+            )));
+        }
+
+        let jwks: JwkSet = self.fetch_json(&metadata.jwks_uri).await?;
+        let discovery = Discovery { metadata, jwks };
+
+        *cached = Some(discovery.clone());
+        Ok(discovery)
+    }
+
+    async fn fetch_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, OidcError> {
+        self.http
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| OidcError::Provider(format!("request to `{url}` failed: {err}")))?
+            .json()
+            .await
+            .map_err(|err| OidcError::Provider(format!("invalid response from `{url}`: {err}")))
+    }
+}
+
+/// Random 256 bits, base64url encoded (also valid PKCE verifier)
+fn random_string() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+
+    general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
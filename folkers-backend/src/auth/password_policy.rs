@@ -0,0 +1,122 @@
+//! Password policy - rules for new passwords and their maximum age.
+
+use std::{collections::HashSet, sync::LazyLock};
+
+/// Default minimal password length
+const DEFAULT_MIN_LENGTH: usize = 8;
+/// Longer passwords are rejected to keep hashing cheap
+const MAX_LENGTH: usize = 128;
+
+/// Policy loaded from environment (already checked in main function)
+pub static POLICY: LazyLock<PasswordPolicy> =
+    LazyLock::new(|| PasswordPolicy::from_env().unwrap_or_default());
+
+#[derive(Debug, Clone)]
+pub enum PolicyViolation {
+    TooShort,
+    TooLong,
+    SameAsUsername,
+    /// Password is found in breached passwords list
+    Breached,
+}
+
+#[derive(Debug, Clone)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    /// Passwords older than this must be changed (disabled if empty)
+    pub max_age: Option<chrono::Duration>,
+    /// Lowercased breached passwords
+    breached: HashSet<String>,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: DEFAULT_MIN_LENGTH,
+            max_age: None,
+            breached: HashSet::new(),
+        }
+    }
+}
+
+impl PasswordPolicy {
+    /// Reads policy from environment variables: <br/>
+    /// `FOLKERS_PASSWORD_MIN_LENGTH`, `FOLKERS_PASSWORD_MAX_AGE_DAYS`,
+    /// `FOLKERS_PASSWORD_WORDLIST` (path to file with one password per line)
+    pub fn from_env() -> Result<Self, anyhow::Error> {
+        let env_number = |name: &str| -> Result<Option<usize>, anyhow::Error> {
+            match std::env::var(name) {
+                Ok(value) => value.parse().map(Some).map_err(|error| {
+                    anyhow::Error::msg(format!("Unable to parse {name}: {error}"))
+                }),
+                Err(_) => Ok(None),
+            }
+        };
+
+        let min_length = env_number("FOLKERS_PASSWORD_MIN_LENGTH")?.unwrap_or(DEFAULT_MIN_LENGTH);
+
+        if min_length == 0 || min_length > MAX_LENGTH {
+            return Err(anyhow::Error::msg(format!(
+                "FOLKERS_PASSWORD_MIN_LENGTH must be between 1 and {MAX_LENGTH}"
+            )));
+        }
+
+        let max_age = env_number("FOLKERS_PASSWORD_MAX_AGE_DAYS")?
+            .filter(|days| *days > 0)
+            .map(|days| chrono::Duration::days(days as i64));
+
+        let breached = match std::env::var("FOLKERS_PASSWORD_WORDLIST") {
+            Ok(path) => std::fs::read_to_string(&path)
+                .map_err(|error| {
+                    anyhow::Error::msg(format!("Unable to read FOLKERS_PASSWORD_WORDLIST: {error}"))
+                })?
+                .lines()
+                .map(|line| line.trim().to_lowercase())
+                .filter(|line| !line.is_empty())
+                .collect(),
+            Err(_) => HashSet::new(),
+        };
+
+        Ok(Self {
+            min_length,
+            max_age,
+            breached,
+        })
+    }
+
+    /// Number of passwords in breached list
+    pub fn breached_count(&self) -> usize {
+        self.breached.len()
+    }
+
+    /// Checks new password against policy rules
+    pub fn check(&self, username: &str, password: &str) -> Result<(), PolicyViolation> {
+        let length = password.chars().count();
+
+        if length < self.min_length {
+            return Err(PolicyViolation::TooShort);
+        }
+
+        if length > MAX_LENGTH {
+            return Err(PolicyViolation::TooLong);
+        }
+
+        if password.eq_ignore_ascii_case(username) {
+            return Err(PolicyViolation::SameAsUsername);
+        }
+
+        if self.breached.contains(&password.to_lowercase()) {
+            return Err(PolicyViolation::Breached);
+        }
+
+        Ok(())
+    }
+
+    /// Password was changed before maximum age
+    pub fn is_expired(&self, changed_at: Option<chrono::DateTime<chrono::Utc>>) -> bool {
+        match (self.max_age, changed_at) {
+            (Some(max_age), Some(changed_at)) => changed_at + max_age < chrono::Utc::now(),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! Permissions - capabilities granted to users through their roles.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Permission {
+    /// View persons records and media
+    #[serde(rename = "persons.read")]
+    PersonsRead,
+    /// Create persons records and edit own ones
+    #[serde(rename = "persons.write.own")]
+    PersonsWriteOwn,
+    /// Edit any persons record
+    #[serde(rename = "persons.write.any")]
+    PersonsWriteAny,
+    /// Delete own persons records
+    #[serde(rename = "persons.delete.own")]
+    PersonsDeleteOwn,
+    /// Delete any persons record
+    #[serde(rename = "persons.delete.any")]
+    PersonsDeleteAny,
+    /// Upload media files
+    #[serde(rename = "media.upload")]
+    MediaUpload,
+    /// Manage own signing key, sign and unsign persons records
+    #[serde(rename = "signatures.sign")]
+    SignaturesSign,
+    /// Create, edit and delete users, manage their sessions and security
+    #[serde(rename = "users.manage")]
+    UsersManage,
+    /// Create, edit and delete roles
+    #[serde(rename = "roles.manage")]
+    RolesManage,
+    /// Change instance-wide settings
+    #[serde(rename = "settings.manage")]
+    SettingsManage,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 10] = [
+        Permission::PersonsRead,
+        Permission::PersonsWriteOwn,
+        Permission::PersonsWriteAny,
+        Permission::PersonsDeleteOwn,
+        Permission::PersonsDeleteAny,
+        Permission::MediaUpload,
+        Permission::SignaturesSign,
+        Permission::UsersManage,
+        Permission::RolesManage,
+        Permission::SettingsManage,
+    ];
+}
+
+/// Built-in roles, created on first start with default permissions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    Watcher,
+    Editor,
+    Admin,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 3] = [Preset::Watcher, Preset::Editor, Preset::Admin];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Preset::Watcher => "watcher",
+            Preset::Editor => "editor",
+            Preset::Admin => "admin",
+        }
+    }
+
+    pub fn permissions(&self) -> Vec<Permission> {
+        match self {
+            Preset::Watcher => vec![Permission::PersonsRead],
+            Preset::Editor => vec![
+                Permission::PersonsRead,
+                Permission::PersonsWriteOwn,
+                Permission::PersonsDeleteOwn,
+                Permission::MediaUpload,
+            ],
+            Preset::Admin => Permission::ALL.to_vec(),
+        }
+    }
+}
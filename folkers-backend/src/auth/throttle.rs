@@ -0,0 +1,80 @@
+//! Brute-force protection: failed login attempts counters with exponential back-off.
+
+use crate::{DATABASE, database::login_attempt::LoginAttemptRecord};
+use surrealdb::sql::Datetime;
+
+/// Failures allowed before back-off starts
+const FREE_ATTEMPTS: u32 = 3;
+/// First back-off delay (seconds), doubles with every next failure
+const BASE_DELAY_SECONDS: i64 = 2;
+/// Maximal lockout duration (seconds)
+const MAX_DELAY_SECONDS: i64 = 30 * 60;
+/// Counter is forgotten after this period without failures (seconds)
+const RESET_WINDOW_SECONDS: i64 = 60 * 60;
+
+/// Counter key for username
+pub fn username_key(username: &str) -> String {
+    format!("user:{}", username.trim().to_lowercase())
+}
+
+/// Counter key for client IP address
+pub fn ip_key(ip: &str) -> String {
+    format!("ip:{ip}")
+}
+
+/// Returns amount of seconds until the longest lock among keys ends (`None` if not locked)
+pub async fn locked_for(keys: &[String]) -> Result<Option<i64>, surrealdb::Error> {
+    let now = chrono::Utc::now();
+    let mut remaining = None;
+
+    for key in keys {
+        if let Some(attempt) = DATABASE.get_login_attempt(key).await?
+        && let Some(locked_until) = attempt.locked_until
+        && locked_until.0 > now {
+            let seconds = (locked_until.0 - now).num_seconds().max(1);
+            remaining = remaining.max(Some(seconds));
+        }
+    }
+
+    Ok(remaining)
+}
+
+/// Registers failed attempt, returns lock duration in seconds if key got locked
+pub async fn register_failure(key: &str) -> Result<Option<i64>, surrealdb::Error> {
+    let now = chrono::Utc::now();
+
+    let failures = match DATABASE.get_login_attempt(key).await? {
+        Some(attempt) if (now - attempt.last_failure.0).num_seconds() < RESET_WINDOW_SECONDS => {
+            attempt.failures + 1
+        }
+        _ => 1,
+    };
+
+    let delay = backoff_delay(failures);
+
+    DATABASE
+        .set_login_attempt(LoginAttemptRecord {
+            id: None,
+            key: key.to_owned(),
+            failures,
+            last_failure: Datetime::from(now),
+            locked_until: delay.map(|delay| Datetime::from(now + chrono::Duration::seconds(delay))),
+        })
+        .await?;
+
+    Ok(delay)
+}
+
+/// Forgets failed attempts (successful login or admin unlock)
+pub async fn reset(key: &str) -> Result<(), surrealdb::Error> {
+    DATABASE.delete_login_attempt(key).await
+}
+
+fn backoff_delay(failures: u32) -> Option<i64> {
+    if failures <= FREE_ATTEMPTS {
+        return None;
+    }
+
+    let exponent = (failures - FREE_ATTEMPTS - 1).min(20);
+    Some((BASE_DELAY_SECONDS << exponent).min(MAX_DELAY_SECONDS))
+}
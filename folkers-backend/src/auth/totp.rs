@@ -0,0 +1,79 @@
+//! TOTP (RFC 6238) two-factor authentication helpers.
+
+use base64::{Engine as _, engine::general_purpose};
+use rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use totp_rs::{Algorithm, Secret, TOTP};
+
+/// Issuer name displayed in authenticator apps
+const ISSUER: &str = "Folkers";
+/// Amount of one-time recovery codes generated on enrollment
+const RECOVERY_CODES_COUNT: usize = 10;
+/// Code lifetime (seconds)
+const STEP_SECONDS: u64 = 30;
+/// Accepted clock difference with authenticator app (steps before and after current one)
+const SKEW_STEPS: u64 = 1;
+
+/// Generates new base32 encoded TOTP secret
+pub fn generate_secret() -> String {
+    Secret::generate_secret().to_encoded().to_string()
+}
+
+fn build(secret: &str, username: &str) -> anyhow::Result<TOTP> {
+    let secret_bytes = Secret::Encoded(secret.to_owned())
+        .to_bytes()
+        .map_err(|err| anyhow::anyhow!("Invalid TOTP secret: {err:?}"))?;
+
+    // SHA1, 6 digits, 30 seconds step - defaults of most authenticator apps,
+    // skew is checked step by step in `verify_code`
+    Ok(TOTP::new(
+        Algorithm::SHA1,
+        6,
+        0,
+        STEP_SECONDS,
+        secret_bytes,
+        Some(ISSUER.to_owned()),
+        username.to_owned(),
+    )?)
+}
+
+/// Builds `otpauth://` provisioning URI (usually shown as QR code)
+pub fn provisioning_uri(secret: &str, username: &str) -> anyhow::Result<String> {
+    Ok(build(secret, username)?.get_url())
+}
+
+/// Verifies code for current time window (±1 step), returns time step of matched code. <br/>
+/// Steps up to `last_step` (already accepted) are skipped, so every code is accepted once
+pub fn verify_code(
+    secret: &str,
+    username: &str,
+    code: &str,
+    last_step: Option<u64>,
+) -> Option<u64> {
+    let totp = build(secret, username).ok()?;
+    let current = chrono::Utc::now().timestamp().max(0) as u64 / STEP_SECONDS;
+
+    (current.saturating_sub(SKEW_STEPS)..=current + SKEW_STEPS)
+        .filter(|step| last_step.is_none_or(|last_step| *step > last_step))
+        .find(|step| totp.check(code.trim(), step * STEP_SECONDS))
+}
+
+/// Generates recovery codes, returns plain codes (shown once) and their hashes (stored)
+pub fn generate_recovery_codes() -> (Vec<String>, Vec<String>) {
+    (0..RECOVERY_CODES_COUNT)
+        .map(|_| {
+            let mut bytes = [0u8; 10];
+            OsRng.fill_bytes(&mut bytes);
+
+            let code = general_purpose::URL_SAFE_NO_PAD.encode(bytes);
+            let hash = hash_recovery_code(&code);
+
+            (code, hash)
+        })
+        .unzip()
+}
+
+/// Recovery codes are stored as SHA256 hashes
+pub fn hash_recovery_code(code: &str) -> String {
+    hex::encode(Sha256::digest(code.trim().as_bytes()))
+}
@@ -5,43 +5,39 @@ pub struct User {
     pub id: String,
     pub username: String,
     pub password_hash: String,
-    pub role: UserRole,
+    /// Role name (see [Preset](super::permission::Preset) for built-in ones)
+    pub role: String,
+    /// TOTP two-factor authentication is enabled
+    pub two_factor_enabled: bool,
+    /// Password must be changed before any other action
+    pub must_change_password: bool,
+    pub password_changed_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Account of external identity provider
+    pub external_id: Option<String>,
+    /// Static admin account
+    pub protected: bool,
+    /// Account is suspended
+    pub disabled: bool,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum UserRole {
-    Watcher,
-    Editor,
-    Admin,
-}
-
-impl std::fmt::Display for UserRole {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", format!("{:?}", self).to_lowercase())
-    }
-}
-
-impl UserRole {
-    pub fn from_str(role: impl AsRef<str>) -> Self {
-        match role.as_ref().to_lowercase().as_str() {
-            "watcher" => Self::Watcher,
-            "editor" => Self::Editor,
-            "admin" => Self::Admin,
-            _ => Self::Watcher,
-        }
+impl User {
+    /// Account is neither suspended nor expired
+    pub fn is_active(&self) -> bool {
+        !self.disabled
+            && self
+                .expires_at
+                .is_none_or(|expires_at| expires_at > chrono::Utc::now())
     }
 
-    pub fn get_id(&self) -> usize {
-        match self {
-            UserRole::Watcher => 0,
-            UserRole::Editor => 1,
-            UserRole::Admin => 2,
+    /// User is limited to password change (forced by admin or password is too old)
+    pub fn password_change_required(&self) -> bool {
+        // passwords of external accounts are managed by identity provider
+        if self.external_id.is_some() {
+            return false;
         }
-    }
-}
 
-impl PartialOrd for UserRole {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.get_id().partial_cmp(&other.get_id())
+        self.must_change_password
+            || super::password_policy::POLICY.is_expired(self.password_changed_at)
     }
 }
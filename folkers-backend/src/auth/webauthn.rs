@@ -0,0 +1,114 @@
+//! WebAuthn - passwordless login with passkeys (platform authenticators and security keys).
+//!
+//! Enabled by `FOLKERS_WEBAUTHN_RP_ID` and `FOLKERS_WEBAUTHN_RP_ORIGIN`. Ceremony state (challenge)
+//! is kept server side until the finish request, so every challenge can be answered only once.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use webauthn_rs::prelude::{
+    CreationChallengeResponse, PublicKeyCredential, RegisterPublicKeyCredential,
+    RequestChallengeResponse, Url, Uuid, Webauthn, WebauthnBuilder,
+};
+
+/// Started registration or authentication expires after this time (minutes)
+pub const CEREMONY_EXPIRATION_MINUTES: i64 = 5;
+
+/// Ceremony kinds, so registration challenge can't be used to log in
+pub const REGISTRATION: &str = "registration";
+pub const AUTHENTICATION: &str = "authentication";
+
+/// Default relying party name shown by authenticators
+const DEFAULT_RP_NAME: &str = "Folkers";
+
+#[derive(Debug, Clone)]
+pub struct WebauthnConfig {
+    /// Relying party identifier, domain of frontend (e.g. `folkers.example.com`)
+    pub rp_id: String,
+    /// Frontend origin (e.g. `https://folkers.example.com`)
+    pub rp_origin: Url,
+    pub rp_name: String,
+}
+
+impl WebauthnConfig {
+    /// Reads configuration from `FOLKERS_WEBAUTHN_*` variables (`None` if passkeys are disabled)
+    pub fn from_env() -> Result<Option<Self>, anyhow::Error> {
+        let Ok(rp_id) = std::env::var("FOLKERS_WEBAUTHN_RP_ID") else {
+            return Ok(None);
+        };
+
+        let rp_origin = std::env::var("FOLKERS_WEBAUTHN_RP_ORIGIN").map_err(|_| {
+            anyhow::Error::msg("FOLKERS_WEBAUTHN_RP_ORIGIN is required for passkeys")
+        })?;
+
+        let rp_origin = Url::parse(&rp_origin).map_err(|error| {
+            anyhow::Error::msg(format!(
+                "Unable to parse FOLKERS_WEBAUTHN_RP_ORIGIN: {error}"
+            ))
+        })?;
+
+        Ok(Some(Self {
+            rp_id,
+            rp_origin,
+            rp_name: std::env::var("FOLKERS_WEBAUTHN_RP_NAME")
+                .unwrap_or_else(|_| DEFAULT_RP_NAME.to_owned()),
+        }))
+    }
+
+    pub fn build(&self) -> Result<Webauthn, anyhow::Error> {
+        WebauthnBuilder::new(&self.rp_id, &self.rp_origin)
+            .and_then(|builder| builder.rp_name(&self.rp_name).build())
+            .map_err(|error| anyhow::Error::msg(format!("Invalid WebAuthn configuration: {error}")))
+    }
+}
+
+/// Stable WebAuthn user handle derived from user ID (database identifiers aren't exposed)
+pub fn user_handle(user_id: &str) -> Uuid {
+    let digest = Sha256::digest(user_id.as_bytes());
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+
+    Uuid::from_bytes(bytes)
+}
+
+/// Identifier of started ceremony (random 128 bits)
+pub fn ceremony_id() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+/// Registration options for `navigator.credentials.create()`
+#[derive(Debug, Serialize)]
+pub struct PasskeyRegistrationChallenge {
+    pub ceremony_id: String,
+    pub options: CreationChallengeResponse,
+}
+
+/// JSON Payload to finish passkey registration
+#[derive(Debug, Deserialize)]
+pub struct PasskeyRegistrationRequest {
+    pub ceremony_id: String,
+    /// Human readable passkey name (e.g. "Laptop")
+    pub name: String,
+    /// Result of `navigator.credentials.create()`
+    pub credential: RegisterPublicKeyCredential,
+}
+
+/// JSON Payload to start passkey login
+#[derive(Debug, Deserialize)]
+pub struct PasskeyLoginRequest {
+    pub username: String,
+}
+
+/// Authentication options for `navigator.credentials.get()`
+#[derive(Debug, Serialize)]
+pub struct PasskeyLoginChallenge {
+    pub ceremony_id: String,
+    pub options: RequestChallengeResponse,
+}
+
+/// JSON Payload to finish passkey login
+#[derive(Debug, Deserialize)]
+pub struct PasskeyLoginFinishRequest {
+    pub ceremony_id: String,
+    /// Result of `navigator.credentials.get()`
+    pub credential: PublicKeyCredential,
+}
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::{Datetime, Thing};
+
+/// Personal access token for scripts and integrations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiTokenRecord {
+    pub id: Option<Thing>,
+    /// Human readable token name
+    pub name: String,
+    pub user_id: String,
+    pub username: String,
+    /// SHA256 hash of the token (raw token is shown only once)
+    pub token_hash: String,
+    /// First characters of the token to recognize it in the list
+    pub prefix: String,
+    /// Role scope, always at or below owner's role
+    pub role: String,
+    pub creation_datetime: Datetime,
+    /// Token never expires if empty
+    pub expires_at: Option<Datetime>,
+    pub last_used: Option<Datetime>,
+}
+
+/// JSON Payload to create new personal access token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateApiTokenRecord {
+    pub name: String,
+    /// Role scope (watcher, editor, admin)
+    pub role: String,
+    pub expires_at: Option<Datetime>,
+}
+
+/// Created personal access token, raw `token` is not stored anywhere
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatedApiToken {
+    pub token: String,
+    pub record: ApiTokenRecord,
+}
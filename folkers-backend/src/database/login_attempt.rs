@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::{Datetime, Thing};
+
+/// Failed login attempts counter for username or client IP
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginAttemptRecord {
+    pub id: Option<Thing>,
+    /// Counter key: `user:<username>` or `ip:<address>`
+    pub key: String,
+    /// Consecutive failures
+    pub failures: u32,
+    pub last_failure: Datetime,
+    /// Login is rejected until this moment
+    pub locked_until: Option<Datetime>,
+}
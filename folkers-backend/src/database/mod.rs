@@ -2,26 +2,59 @@
 
 use surrealdb::{
     Surreal,
-    engine::remote::ws::{Client, Ws},
-    opt::auth::Root,
+    engine::any::Any,
+    opt::{Config, auth::Root},
 };
 use serde_json::json;
 
 use crate::{database::signature::RecordSignatureRecord, signatures::RecordSignature};
 
+pub mod api_token;
 pub mod signature;
+pub mod tag;
+pub mod login_attempt;
+pub mod oidc_login;
+pub mod passkey;
 pub mod person;
+pub mod relation;
+pub mod revision;
+pub mod role;
+pub mod session;
+pub mod settings;
+pub mod token;
 pub mod user;
 
 const USER: &str = "user";
 const PERSON: &str = "person";
+const PERSON_REVISIONS: &str = "person_revision";
+const PERSON_RELATIONS: &str = "person_relation";
 const SIGNATURES: &str = "signatures";
+const TAGS: &str = "tag";
+const REFRESH_TOKENS: &str = "refresh_token";
+const REVOKED_TOKENS: &str = "revoked_token";
+const SETTINGS: &str = "settings";
+const LOGIN_ATTEMPTS: &str = "login_attempt";
+const SESSIONS: &str = "session";
+const API_TOKENS: &str = "api_token";
+const ROLES: &str = "role";
+const OIDC_LOGINS: &str = "oidc_login";
+const PASSKEYS: &str = "passkey";
+const PASSKEY_CEREMONIES: &str = "passkey_ceremony";
+const TWO_FACTOR_CHALLENGES: &str = "two_factor_challenge";
+
+/// Full-text analyzer of person's narrative fields
+const PERSON_ANALYZER: &str = "person_analyzer";
+/// Splits names into lowercase latin words (transliterates cyrillic)
+const PERSON_NAME_ANALYZER: &str = "person_name_analyzer";
+
+/// Identifier of security settings record in `settings` table
+const SECURITY_SETTINGS_ID: &str = "security";
 
 /// Database interaction client.
 /// You can adapt it and use with axum's `with_state`, but I'd recommend to make
 /// it static with LazyLock.
 pub struct DatabaseClient {
-    connection: Surreal<Client>,
+    connection: Surreal<Any>,
 }
 
 impl DatabaseClient {
@@ -32,7 +65,9 @@ impl DatabaseClient {
         }
     }
 
-    /// Setup database connection and define necessary fields
+    /// Setup database connection and define necessary fields. <br/>
+    /// Endpoint without scheme (`surrealdb:8000`) is connected via WebSocket,
+    /// `mem://` starts embedded in-memory database (tests).
     pub async fn setup(
         &self,
         endpoint: &str,
@@ -41,7 +76,16 @@ impl DatabaseClient {
         username: &str,
         password: &str,
     ) -> Result<(), surrealdb::Error> {
-        self.connection.connect::<Ws>(endpoint).await?;
+        let endpoint = if endpoint.contains("://") {
+            endpoint.to_owned()
+        } else {
+            format!("ws://{endpoint}")
+        };
+
+        // root user is only defined by embedded database, server has its own
+        self.connection
+            .connect((endpoint, Config::new().user(Root { username, password })))
+            .await?;
 
         self.connection.signin(Root { username, password }).await?;
 
@@ -60,8 +104,36 @@ DEFINE FIELD IF NOT EXISTS role ON TABLE {USER} TYPE string;
 DEFINE FIELD IF NOT EXISTS created_by ON TABLE {USER} TYPE string;
 DEFINE FIELD IF NOT EXISTS creation_datetime ON TABLE {USER} TYPE datetime;
 DEFINE FIELD IF NOT EXISTS public_key ON TABLE {USER} TYPE option<string>;
+DEFINE FIELD IF NOT EXISTS totp_secret ON TABLE {USER} TYPE option<string>;
+DEFINE FIELD IF NOT EXISTS totp_enabled ON TABLE {USER} TYPE bool DEFAULT false;
+DEFINE FIELD IF NOT EXISTS recovery_codes ON TABLE {USER} TYPE array<string> DEFAULT [];
+DEFINE FIELD IF NOT EXISTS totp_last_step ON TABLE {USER} TYPE option<int>;
+DEFINE FIELD IF NOT EXISTS must_change_password ON TABLE {USER} TYPE bool DEFAULT false;
+DEFINE FIELD IF NOT EXISTS password_changed_at ON TABLE {USER} TYPE option<datetime>;
+DEFINE FIELD IF NOT EXISTS external_id ON TABLE {USER} TYPE option<string>;
+DEFINE FIELD IF NOT EXISTS protected ON TABLE {USER} TYPE bool DEFAULT false;
+DEFINE FIELD IF NOT EXISTS disabled ON TABLE {USER} TYPE bool DEFAULT false;
+DEFINE FIELD IF NOT EXISTS disabled_reason ON TABLE {USER} TYPE option<string>;
+DEFINE FIELD IF NOT EXISTS expires_at ON TABLE {USER} TYPE option<datetime>;
+
+-- accounts created before these fields were added (defaults aren't applied on update),
+-- their password age is counted from creation
+UPDATE {USER} SET
+    totp_enabled = totp_enabled ?? false,
+    recovery_codes = recovery_codes ?? [],
+    must_change_password = must_change_password ?? false,
+    password_changed_at = password_changed_at ?? creation_datetime,
+    protected = protected ?? false,
+    disabled = disabled ?? false
+WHERE totp_enabled IS NONE
+    OR recovery_codes IS NONE
+    OR must_change_password IS NONE
+    OR password_changed_at IS NONE
+    OR protected IS NONE
+    OR disabled IS NONE;
 
 DEFINE INDEX IF NOT EXISTS unique_name ON TABLE {USER} COLUMNS username UNIQUE;
+DEFINE INDEX IF NOT EXISTS unique_external_id ON TABLE {USER} COLUMNS external_id UNIQUE;
 
 -- Persons Records Table
 
@@ -87,9 +159,74 @@ DEFINE FIELD IF NOT EXISTS avatar ON TABLE {PERSON} TYPE string;
 DEFINE FIELD IF NOT EXISTS media ON TABLE {PERSON} TYPE array<string>;
 
 DEFINE FIELD IF NOT EXISTS author ON TABLE {PERSON} TYPE string;
+-- not part of signed record content
+DEFINE FIELD IF NOT EXISTS creation_datetime ON TABLE {PERSON} TYPE datetime DEFAULT time::now();
+-- creation time of records made before it was stored is unknown
+UPDATE {PERSON} SET creation_datetime = time::now() WHERE creation_datetime IS NONE;
+-- records in trash (hidden everywhere except trash listing, purged after retention period)
+DEFINE FIELD IF NOT EXISTS deleted_at ON TABLE {PERSON} TYPE option<datetime>;
+DEFINE FIELD IF NOT EXISTS deleted_by ON TABLE {PERSON} TYPE option<string>;
+
+DEFINE INDEX IF NOT EXISTS person_deleted_at ON TABLE {PERSON} COLUMNS deleted_at;
+-- tags aren't part of signed record content either
+DEFINE FIELD IF NOT EXISTS tags ON TABLE {PERSON} TYPE array<record<{TAGS}>> DEFAULT [];
+UPDATE {PERSON} SET tags = [] WHERE tags IS NONE;
+
+DEFINE INDEX IF NOT EXISTS person_tags ON TABLE {PERSON} COLUMNS tags;
 
 DEFINE INDEX IF NOT EXISTS unique_person ON TABLE {PERSON} COLUMNS surname, name, patronymic UNIQUE;
 
+-- full-text search over narrative fields (stemming runs before `ascii`, which transliterates cyrillic)
+DEFINE ANALYZER IF NOT EXISTS {PERSON_ANALYZER} TOKENIZERS blank, class, punct FILTERS lowercase, snowball(russian), ascii;
+
+DEFINE INDEX IF NOT EXISTS person_summary_search ON TABLE {PERSON} FIELDS summary SEARCH ANALYZER {PERSON_ANALYZER} BM25 HIGHLIGHTS;
+DEFINE INDEX IF NOT EXISTS person_past_search ON TABLE {PERSON} FIELDS past SEARCH ANALYZER {PERSON_ANALYZER} BM25 HIGHLIGHTS;
+DEFINE INDEX IF NOT EXISTS person_traits_good_search ON TABLE {PERSON} FIELDS traits_good SEARCH ANALYZER {PERSON_ANALYZER} BM25 HIGHLIGHTS;
+DEFINE INDEX IF NOT EXISTS person_traits_bad_search ON TABLE {PERSON} FIELDS traits_bad SEARCH ANALYZER {PERSON_ANALYZER} BM25 HIGHLIGHTS;
+
+-- Person Records Revisions Table (history of changes, revisions are never changed)
+
+DEFINE TABLE IF NOT EXISTS {PERSON_REVISIONS} SCHEMALESS;
+
+DEFINE FIELD IF NOT EXISTS person_id ON TABLE {PERSON_REVISIONS} TYPE string READONLY;
+DEFINE FIELD IF NOT EXISTS revision ON TABLE {PERSON_REVISIONS} TYPE int READONLY;
+DEFINE FIELD OVERWRITE action ON TABLE {PERSON_REVISIONS} TYPE string READONLY
+    ASSERT $value INSIDE ['create', 'update', 'delete', 'restore', 'revert', 'tag'];
+DEFINE FIELD IF NOT EXISTS author ON TABLE {PERSON_REVISIONS} TYPE string READONLY;
+DEFINE FIELD IF NOT EXISTS creation_datetime ON TABLE {PERSON_REVISIONS} TYPE datetime READONLY;
+DEFINE FIELD IF NOT EXISTS reverted_from ON TABLE {PERSON_REVISIONS} TYPE option<int> READONLY;
+DEFINE FIELD IF NOT EXISTS snapshot ON TABLE {PERSON_REVISIONS} TYPE object READONLY;
+DEFINE FIELD IF NOT EXISTS tags ON TABLE {PERSON_REVISIONS} TYPE array<string> DEFAULT [] READONLY;
+
+-- also prevents two concurrent changes from getting the same number
+DEFINE INDEX IF NOT EXISTS unique_person_revision ON TABLE {PERSON_REVISIONS} COLUMNS person_id, revision UNIQUE;
+
+-- Person Relations Table (graph edges `person->person_relation->person`, removed with persons)
+
+DEFINE TABLE IF NOT EXISTS {PERSON_RELATIONS} TYPE RELATION IN {PERSON} OUT {PERSON} SCHEMALESS;
+
+DEFINE FIELD IF NOT EXISTS kind ON TABLE {PERSON_RELATIONS} TYPE string
+    ASSERT $value INSIDE ['relative', 'spouse', 'colleague', 'friend', 'custom'];
+DEFINE FIELD IF NOT EXISTS label ON TABLE {PERSON_RELATIONS} TYPE option<string>;
+DEFINE FIELD IF NOT EXISTS since ON TABLE {PERSON_RELATIONS} TYPE option<datetime>;
+DEFINE FIELD IF NOT EXISTS until ON TABLE {PERSON_RELATIONS} TYPE option<datetime>;
+DEFINE FIELD IF NOT EXISTS note ON TABLE {PERSON_RELATIONS} TYPE option<string>;
+DEFINE FIELD IF NOT EXISTS author ON TABLE {PERSON_RELATIONS} TYPE string;
+DEFINE FIELD IF NOT EXISTS creation_datetime ON TABLE {PERSON_RELATIONS} TYPE datetime DEFAULT time::now();
+
+DEFINE INDEX IF NOT EXISTS person_relation_in ON TABLE {PERSON_RELATIONS} COLUMNS in;
+DEFINE INDEX IF NOT EXISTS person_relation_out ON TABLE {PERSON_RELATIONS} COLUMNS out;
+
+-- Tags Table (managed vocabulary, persons reference tags in `tags` field)
+
+DEFINE TABLE IF NOT EXISTS {TAGS} SCHEMALESS;
+
+DEFINE FIELD IF NOT EXISTS name ON TABLE {TAGS} TYPE string;
+DEFINE FIELD IF NOT EXISTS color ON TABLE {TAGS} TYPE string ASSERT $value = /^#[0-9a-fA-F]{{6}}$/;
+DEFINE FIELD IF NOT EXISTS creation_datetime ON TABLE {TAGS} TYPE datetime DEFAULT time::now();
+
+DEFINE INDEX IF NOT EXISTS unique_tag_name ON TABLE {TAGS} COLUMNS name UNIQUE;
+
 -- Person Records Signatures Table
 
 DEFINE TABLE IF NOT EXISTS {SIGNATURES} SCHEMALESS
@@ -102,41 +239,275 @@ DEFINE FIELD IF NOT EXISTS base64 ON TABLE {SIGNATURES} TYPE string;
 DEFINE FIELD IF NOT EXISTS pubkey ON TABLE {SIGNATURES} TYPE string;
 DEFINE FIELD IF NOT EXISTS signed_by ON TABLE {SIGNATURES} TYPE string;
 
+-- Refresh Tokens Table
+
+DEFINE TABLE IF NOT EXISTS {REFRESH_TOKENS} SCHEMALESS;
+
+DEFINE FIELD IF NOT EXISTS token_hash ON TABLE {REFRESH_TOKENS} TYPE string;
+DEFINE FIELD IF NOT EXISTS family ON TABLE {REFRESH_TOKENS} TYPE string;
+DEFINE FIELD IF NOT EXISTS user_id ON TABLE {REFRESH_TOKENS} TYPE string;
+DEFINE FIELD IF NOT EXISTS username ON TABLE {REFRESH_TOKENS} TYPE string;
+DEFINE FIELD IF NOT EXISTS revoked ON TABLE {REFRESH_TOKENS} TYPE bool;
+DEFINE FIELD IF NOT EXISTS expires_at ON TABLE {REFRESH_TOKENS} TYPE datetime;
+DEFINE FIELD IF NOT EXISTS creation_datetime ON TABLE {REFRESH_TOKENS} TYPE datetime;
+
+DEFINE INDEX IF NOT EXISTS unique_token_hash ON TABLE {REFRESH_TOKENS} COLUMNS token_hash UNIQUE;
+DEFINE INDEX IF NOT EXISTS token_family ON TABLE {REFRESH_TOKENS} COLUMNS family;
+
+-- Revoked Access Tokens Table
+
+DEFINE TABLE IF NOT EXISTS {REVOKED_TOKENS} SCHEMALESS;
+
+DEFINE FIELD IF NOT EXISTS jti ON TABLE {REVOKED_TOKENS} TYPE string;
+DEFINE FIELD IF NOT EXISTS expires_at ON TABLE {REVOKED_TOKENS} TYPE datetime;
+
+DEFINE INDEX IF NOT EXISTS unique_jti ON TABLE {REVOKED_TOKENS} COLUMNS jti UNIQUE;
+
+-- Sessions Table
+
+DEFINE TABLE IF NOT EXISTS {SESSIONS} SCHEMALESS;
+
+DEFINE FIELD IF NOT EXISTS user_id ON TABLE {SESSIONS} TYPE string;
+DEFINE FIELD IF NOT EXISTS username ON TABLE {SESSIONS} TYPE string;
+DEFINE FIELD IF NOT EXISTS user_agent ON TABLE {SESSIONS} TYPE string;
+DEFINE FIELD IF NOT EXISTS ip ON TABLE {SESSIONS} TYPE string;
+DEFINE FIELD IF NOT EXISTS revoked ON TABLE {SESSIONS} TYPE bool;
+DEFINE FIELD IF NOT EXISTS creation_datetime ON TABLE {SESSIONS} TYPE datetime;
+DEFINE FIELD IF NOT EXISTS last_seen ON TABLE {SESSIONS} TYPE datetime;
+DEFINE FIELD IF NOT EXISTS expires_at ON TABLE {SESSIONS} TYPE datetime;
+DEFINE FIELD IF NOT EXISTS impersonated_by ON TABLE {SESSIONS} TYPE option<string>;
+
+DEFINE INDEX IF NOT EXISTS session_user ON TABLE {SESSIONS} COLUMNS user_id;
+
+-- Personal API Tokens Table
+
+DEFINE TABLE IF NOT EXISTS {API_TOKENS} SCHEMALESS;
+
+DEFINE FIELD IF NOT EXISTS name ON TABLE {API_TOKENS} TYPE string;
+DEFINE FIELD IF NOT EXISTS user_id ON TABLE {API_TOKENS} TYPE string;
+DEFINE FIELD IF NOT EXISTS username ON TABLE {API_TOKENS} TYPE string;
+DEFINE FIELD IF NOT EXISTS token_hash ON TABLE {API_TOKENS} TYPE string;
+DEFINE FIELD IF NOT EXISTS prefix ON TABLE {API_TOKENS} TYPE string;
+DEFINE FIELD IF NOT EXISTS role ON TABLE {API_TOKENS} TYPE string;
+DEFINE FIELD IF NOT EXISTS creation_datetime ON TABLE {API_TOKENS} TYPE datetime;
+DEFINE FIELD IF NOT EXISTS expires_at ON TABLE {API_TOKENS} TYPE option<datetime>;
+DEFINE FIELD IF NOT EXISTS last_used ON TABLE {API_TOKENS} TYPE option<datetime>;
+
+DEFINE INDEX IF NOT EXISTS unique_api_token_hash ON TABLE {API_TOKENS} COLUMNS token_hash UNIQUE;
+DEFINE INDEX IF NOT EXISTS api_token_user ON TABLE {API_TOKENS} COLUMNS user_id;
+
+-- Failed Login Attempts Table
+
+DEFINE TABLE IF NOT EXISTS {LOGIN_ATTEMPTS} SCHEMALESS;
+
+DEFINE FIELD IF NOT EXISTS key ON TABLE {LOGIN_ATTEMPTS} TYPE string;
+DEFINE FIELD IF NOT EXISTS failures ON TABLE {LOGIN_ATTEMPTS} TYPE int;
+DEFINE FIELD IF NOT EXISTS last_failure ON TABLE {LOGIN_ATTEMPTS} TYPE datetime;
+DEFINE FIELD IF NOT EXISTS locked_until ON TABLE {LOGIN_ATTEMPTS} TYPE option<datetime>;
+
+DEFINE INDEX IF NOT EXISTS unique_attempt_key ON TABLE {LOGIN_ATTEMPTS} COLUMNS key UNIQUE;
+
+-- Roles Table
+
+DEFINE TABLE IF NOT EXISTS {ROLES} SCHEMALESS;
+
+DEFINE FIELD IF NOT EXISTS name ON TABLE {ROLES} TYPE string;
+DEFINE FIELD IF NOT EXISTS permissions ON TABLE {ROLES} TYPE array<string>;
+DEFINE FIELD IF NOT EXISTS builtin ON TABLE {ROLES} TYPE bool DEFAULT false;
+DEFINE FIELD IF NOT EXISTS creation_datetime ON TABLE {ROLES} TYPE datetime;
+
+DEFINE INDEX IF NOT EXISTS unique_role_name ON TABLE {ROLES} COLUMNS name UNIQUE;
+
+-- Pending Two-Factor Challenges Table
+
+DEFINE TABLE IF NOT EXISTS {TWO_FACTOR_CHALLENGES} SCHEMALESS;
+
+DEFINE FIELD IF NOT EXISTS jti ON TABLE {TWO_FACTOR_CHALLENGES} TYPE string;
+DEFINE FIELD IF NOT EXISTS user_id ON TABLE {TWO_FACTOR_CHALLENGES} TYPE string;
+DEFINE FIELD IF NOT EXISTS expires_at ON TABLE {TWO_FACTOR_CHALLENGES} TYPE datetime;
+
+DEFINE INDEX IF NOT EXISTS unique_challenge_jti ON TABLE {TWO_FACTOR_CHALLENGES} COLUMNS jti UNIQUE;
+
+-- Pending Single Sign-On Logins Table
+
+DEFINE TABLE IF NOT EXISTS {OIDC_LOGINS} SCHEMALESS;
+
+DEFINE FIELD IF NOT EXISTS state ON TABLE {OIDC_LOGINS} TYPE string;
+DEFINE FIELD IF NOT EXISTS nonce ON TABLE {OIDC_LOGINS} TYPE string;
+DEFINE FIELD IF NOT EXISTS code_verifier ON TABLE {OIDC_LOGINS} TYPE string;
+DEFINE FIELD IF NOT EXISTS expires_at ON TABLE {OIDC_LOGINS} TYPE datetime;
+
+DEFINE INDEX IF NOT EXISTS unique_oidc_state ON TABLE {OIDC_LOGINS} COLUMNS state UNIQUE;
+
+-- Passkeys (WebAuthn Credentials) Table
+
+DEFINE TABLE IF NOT EXISTS {PASSKEYS} SCHEMALESS;
+
+DEFINE FIELD IF NOT EXISTS user_id ON TABLE {PASSKEYS} TYPE string;
+DEFINE FIELD IF NOT EXISTS username ON TABLE {PASSKEYS} TYPE string;
+DEFINE FIELD IF NOT EXISTS name ON TABLE {PASSKEYS} TYPE string;
+DEFINE FIELD IF NOT EXISTS credential_id ON TABLE {PASSKEYS} TYPE string;
+DEFINE FIELD IF NOT EXISTS passkey ON TABLE {PASSKEYS} TYPE string;
+DEFINE FIELD IF NOT EXISTS creation_datetime ON TABLE {PASSKEYS} TYPE datetime;
+DEFINE FIELD IF NOT EXISTS last_used ON TABLE {PASSKEYS} TYPE option<datetime>;
+
+DEFINE INDEX IF NOT EXISTS unique_credential_id ON TABLE {PASSKEYS} COLUMNS credential_id UNIQUE;
+DEFINE INDEX IF NOT EXISTS passkey_user ON TABLE {PASSKEYS} COLUMNS user_id;
+
+-- Pending WebAuthn Ceremonies Table
+
+DEFINE TABLE IF NOT EXISTS {PASSKEY_CEREMONIES} SCHEMALESS;
+
+DEFINE FIELD IF NOT EXISTS ceremony_id ON TABLE {PASSKEY_CEREMONIES} TYPE string;
+DEFINE FIELD IF NOT EXISTS kind ON TABLE {PASSKEY_CEREMONIES} TYPE string;
+DEFINE FIELD IF NOT EXISTS user_id ON TABLE {PASSKEY_CEREMONIES} TYPE string;
+DEFINE FIELD IF NOT EXISTS username ON TABLE {PASSKEY_CEREMONIES} TYPE string;
+DEFINE FIELD IF NOT EXISTS state ON TABLE {PASSKEY_CEREMONIES} TYPE string;
+DEFINE FIELD IF NOT EXISTS expires_at ON TABLE {PASSKEY_CEREMONIES} TYPE datetime;
+
+DEFINE INDEX IF NOT EXISTS unique_ceremony_id ON TABLE {PASSKEY_CEREMONIES} COLUMNS ceremony_id UNIQUE;
+
+-- Settings Table
+
+DEFINE TABLE IF NOT EXISTS {SETTINGS} SCHEMALESS;
+
 -- Functions
 
-DEFINE FUNCTION IF NOT EXISTS fn::find_person($query: string) {{
+-- `person_matches` is used by uniqueness check, OVERWRITE updates functions of existing databases
+DEFINE FUNCTION OVERWRITE fn::person_matches($person: object, $query: string) -> bool {{
     LET $q = string::trim(string::lowercase($query));
     LET $words = $q.split(' ');
-    
-    RETURN SELECT * FROM person 
-    WHERE 
-        string::lowercase(surname) CONTAINS $q
-        OR string::lowercase(name) CONTAINS $q
-        OR string::lowercase(patronymic) CONTAINS $q
-        OR string::lowercase(surname + ' ' + name + ' ' + patronymic) CONTAINS $q
-        OR string::lowercase(name + ' ' + patronymic + ' ' + surname) CONTAINS $q
-        OR string::lowercase(patronymic + ' ' + name + ' ' + surname) CONTAINS $q
-        OR string::lowercase(surname + ' ' + patronymic + ' ' + name) CONTAINS $q
+
+    LET $surname = string::lowercase($person.surname);
+    LET $name = string::lowercase($person.name);
+    LET $patronymic = string::lowercase($person.patronymic);
+
+    RETURN
+        $surname CONTAINS $q
+        OR $name CONTAINS $q
+        OR $patronymic CONTAINS $q
+        OR ($surname + ' ' + $name + ' ' + $patronymic) CONTAINS $q
+        OR ($name + ' ' + $patronymic + ' ' + $surname) CONTAINS $q
+        OR ($patronymic + ' ' + $name + ' ' + $surname) CONTAINS $q
+        OR ($surname + ' ' + $patronymic + ' ' + $name) CONTAINS $q
         OR array::len($words) >= 2 AND (
-            (string::lowercase(surname) CONTAINS array::at($words, 0) AND string::lowercase(name) CONTAINS array::at($words, 1))
-            OR (string::lowercase(surname) CONTAINS array::at($words, 1) AND string::lowercase(name) CONTAINS array::at($words, 0))
-            OR (string::lowercase(name) CONTAINS array::at($words, 0) AND string::lowercase(patronymic) CONTAINS array::at($words, 1))
-            OR (string::lowercase(name) CONTAINS array::at($words, 1) AND string::lowercase(patronymic) CONTAINS array::at($words, 0))
+            ($surname CONTAINS array::at($words, 0) AND $name CONTAINS array::at($words, 1))
+            OR ($surname CONTAINS array::at($words, 1) AND $name CONTAINS array::at($words, 0))
+            OR ($name CONTAINS array::at($words, 0) AND $patronymic CONTAINS array::at($words, 1))
+            OR ($name CONTAINS array::at($words, 1) AND $patronymic CONTAINS array::at($words, 0))
         )
         OR array::len($words) = 3 AND (
-            (string::lowercase(surname) CONTAINS array::at($words, 0) AND string::lowercase(name) CONTAINS array::at($words, 1) AND string::lowercase(patronymic) CONTAINS array::at($words, 2))
-            OR (string::lowercase(surname) CONTAINS array::at($words, 0) AND string::lowercase(name) CONTAINS array::at($words, 2) AND string::lowercase(patronymic) CONTAINS array::at($words, 1))
-            OR (string::lowercase(surname) CONTAINS array::at($words, 1) AND string::lowercase(name) CONTAINS array::at($words, 0) AND string::lowercase(patronymic) CONTAINS array::at($words, 2))
-            OR (string::lowercase(surname) CONTAINS array::at($words, 1) AND string::lowercase(name) CONTAINS array::at($words, 2) AND string::lowercase(patronymic) CONTAINS array::at($words, 0))
-            OR (string::lowercase(surname) CONTAINS array::at($words, 2) AND string::lowercase(name) CONTAINS array::at($words, 0) AND string::lowercase(patronymic) CONTAINS array::at($words, 1))
-            OR (string::lowercase(surname) CONTAINS array::at($words, 2) AND string::lowercase(name) CONTAINS array::at($words, 1) AND string::lowercase(patronymic) CONTAINS array::at($words, 0))
-        )
+            ($surname CONTAINS array::at($words, 0) AND $name CONTAINS array::at($words, 1) AND $patronymic CONTAINS array::at($words, 2))
+            OR ($surname CONTAINS array::at($words, 0) AND $name CONTAINS array::at($words, 2) AND $patronymic CONTAINS array::at($words, 1))
+            OR ($surname CONTAINS array::at($words, 1) AND $name CONTAINS array::at($words, 0) AND $patronymic CONTAINS array::at($words, 2))
+            OR ($surname CONTAINS array::at($words, 1) AND $name CONTAINS array::at($words, 2) AND $patronymic CONTAINS array::at($words, 0))
+            OR ($surname CONTAINS array::at($words, 2) AND $name CONTAINS array::at($words, 0) AND $patronymic CONTAINS array::at($words, 1))
+            OR ($surname CONTAINS array::at($words, 2) AND $name CONTAINS array::at($words, 1) AND $patronymic CONTAINS array::at($words, 0))
+        );
+}};
+
+-- Fuzzy name search
+
+DEFINE ANALYZER IF NOT EXISTS {PERSON_NAME_ANALYZER} TOKENIZERS blank, punct FILTERS lowercase, ascii;
+
+-- same key for cyrillic and latin spellings: `Фёдор`, `Федор`, `Fyodor` and `Fedor` give `fedor`
+DEFINE FUNCTION OVERWRITE fn::person_name_key($word: string) -> string {{
+    LET $word = string::replace($word, <regex>'[^a-z0-9]', '');
+    LET $word = string::replace(string::replace($word, 'yo', 'e'), 'ye', 'e');
+    LET $word = string::replace(string::replace($word, 'kh', 'h'), 'x', 'ks');
+    RETURN string::replace(string::replace(string::replace($word, 'y', 'i'), 'j', 'i'), 'w', 'v');
+}};
+
+DEFINE FUNCTION OVERWRITE fn::person_name_words($text: string) -> array<string> {{
+    LET $words = search::analyze('{PERSON_NAME_ANALYZER}', string::replace(string::lowercase($text), 'ё', 'е'));
+    RETURN array::filter(array::map($words, |$word| fn::person_name_key($word)), |$word| $word != '');
+}};
+
+-- 1 for equal words, less for prefix or substring, less for typos (1 for 4-6 letters, 2 for longer words), 0 if not similar
+DEFINE FUNCTION OVERWRITE fn::person_word_score($part: string, $word: string) -> float {{
+    IF $part = $word {{ RETURN 1.0; }};
+    IF string::starts_with($part, $word) {{ RETURN 0.9; }};
+    IF $part CONTAINS $word {{ RETURN 0.8; }};
+
+    LET $length = string::len($word);
+    LET $allowed = IF $length <= 3 {{ 0 }} ELSE IF $length <= 6 {{ 1 }} ELSE {{ 2 }};
+    -- word may be an unfinished part with typo
+    LET $distance = math::min([
+        string::distance::damerau_levenshtein($part, $word),
+        string::distance::damerau_levenshtein(string::slice($part, 0, $length), $word)
+    ]);
+
+    RETURN IF $distance <= $allowed {{ 0.7 - 0.1 * $distance }} ELSE {{ 0.0 }};
+}};
+
+-- mean score of query words, 0 if any word isn't similar to name, surname or patronymic
+DEFINE FUNCTION OVERWRITE fn::person_search_score($person: object, $query: string) -> float {{
+    LET $words = fn::person_name_words($query);
+    LET $parts = fn::person_name_words($person.surname + ' ' + $person.name + ' ' + $person.patronymic);
+
+    IF array::len($words) = 0 OR array::len($parts) = 0 {{ RETURN 0.0; }};
+
+    -- best score of each word among name parts (closures can't capture variables)
+    LET $scores = SELECT VALUE best FROM (
+        SELECT word, math::max(score) AS best FROM array::map(
+            array::combine($words, $parts),
+            |$pair| {{ word: $pair[0], score: fn::person_word_score($pair[1], $pair[0]) }}
+        ) GROUP BY word
+    );
+
+    RETURN IF math::min($scores) > 0 {{ math::mean($scores) }} ELSE {{ 0.0 }};
+}};
+
+DEFINE FUNCTION OVERWRITE fn::find_person($query: string) {{
+    RETURN SELECT * FROM {PERSON}
+    WHERE fn::person_matches($this, $query)
     ORDER BY
-        surname, name, patronymic
-    LIMIT 25;
+        surname, name, patronymic;
+}};
+
+-- Revisions
+
+-- stores snapshot as next revision of person, must be called in the same statement as change itself,
+-- so change is rolled back if revision can't be stored
+DEFINE FUNCTION OVERWRITE fn::person_revision_add($person: object, $action: string, $author: string, $reverted_from: option<int>) {{
+    LET $person_id = <string> record::id($person.id);
+    LET $last = math::max(SELECT VALUE revision FROM {PERSON_REVISIONS} WHERE person_id = $person_id) ?? 0;
+
+    RETURN CREATE ONLY {PERSON_REVISIONS} CONTENT {{
+        person_id: $person_id,
+        revision: $last + 1,
+        action: $action,
+        author: $author,
+        creation_datetime: time::now(),
+        reverted_from: $reverted_from,
+        snapshot: $person,
+        tags: array::sort($person.tags.name ?? [])
+    }};
+}};
+
+-- history of records made before revisions were stored starts with their current state
+FOR $person IN (
+    SELECT * FROM {PERSON}
+    WHERE (SELECT VALUE id FROM {PERSON_REVISIONS} WHERE person_id = <string> record::id($parent.id) LIMIT 1) = []
+) {{
+    fn::person_revision_add($person, 'create', $person.author, NONE);
 }};
 ")).await?;
 
+        // built-in roles are only created once, so admins can adjust their permissions
+        for preset in crate::auth::permission::Preset::ALL {
+            if self.get_role(preset.name()).await?.is_none() {
+                self.create_role(role::RoleRecord {
+                    id: None,
+                    name: preset.name().to_owned(),
+                    permissions: preset.permissions(),
+                    builtin: true,
+                    creation_datetime: surrealdb::Datetime::from(chrono::Utc::now()),
+                })
+                .await?;
+            }
+        }
+
         match (
             std::env::var("FOLKERS_STATIC_ADMIN_USERNAME"),
             std::env::var("FOLKERS_STATIC_ADMIN_PASSWORD"),
@@ -144,13 +515,16 @@ DEFINE FUNCTION IF NOT EXISTS fn::find_person($query: string) {{
             (Ok(admin_username), Ok(admin_password)) => {
                 let _ = self
                     .create_user(user::CreateUserRecord {
-                        username: admin_username,
+                        username: admin_username.clone(),
                         password: crate::auth::UserRepository::hash_password(&admin_password)
                             .unwrap(),
-                        role: crate::auth::user::UserRole::Admin.to_string(),
+                        role: crate::auth::permission::Preset::Admin.name().to_owned(),
                         created_by: String::from("system"),
-                    })
+                        expires_at: None,
+                    }, false)
                     .await;
+
+                self.protect_user(admin_username).await?;
             }
             _ => {
                 log::warn!("Static admin wasn't initialized (environment variables fetch error)");
@@ -166,6 +540,38 @@ DEFINE FUNCTION IF NOT EXISTS fn::find_person($query: string) {{
     pub async fn create_user(
         &self,
         user: user::CreateUserRecord,
+        must_change_password: bool,
+    ) -> Result<Option<user::UserRecord>, surrealdb::Error> {
+        self.connection
+            .create(USER)
+            .content(user::UserRecord {
+                id: None,
+                username: user.username,
+                password: user.password,
+                role: user.role,
+                created_by: user.created_by,
+                creation_datetime: surrealdb::Datetime::from(chrono::Utc::now()),
+                public_key: None,
+                totp_secret: None,
+                totp_enabled: false,
+                recovery_codes: Vec::new(),
+                totp_last_step: None,
+                must_change_password,
+                password_changed_at: Some(surrealdb::sql::Datetime::default()),
+                external_id: None,
+                protected: false,
+                disabled: false,
+                disabled_reason: None,
+                expires_at: user.expires_at,
+            })
+            .await
+    }
+
+    /// Create user authenticated by external identity provider (has no usable password)
+    pub async fn create_external_user(
+        &self,
+        user: user::CreateUserRecord,
+        external_id: impl AsRef<str>,
     ) -> Result<Option<user::UserRecord>, surrealdb::Error> {
         self.connection
             .create(USER)
@@ -176,11 +582,35 @@ DEFINE FUNCTION IF NOT EXISTS fn::find_person($query: string) {{
                 role: user.role,
                 created_by: user.created_by,
                 creation_datetime: surrealdb::Datetime::from(chrono::Utc::now()),
-                public_key: None
+                public_key: None,
+                totp_secret: None,
+                totp_enabled: false,
+                recovery_codes: Vec::new(),
+                totp_last_step: None,
+                must_change_password: false,
+                password_changed_at: None,
+                external_id: Some(external_id.as_ref().to_owned()),
+                protected: false,
+                disabled: false,
+                disabled_reason: None,
+                expires_at: user.expires_at,
             })
             .await
     }
 
+    /// Mark user as the only protected account (static admin may be renamed in environment)
+    pub async fn protect_user(&self, username: impl AsRef<str>) -> Result<(), surrealdb::Error> {
+        self.connection
+            .query(format!(
+                "UPDATE {USER} SET protected = (username = $username) WHERE protected != (username = $username)"
+            ))
+            .bind(("username", username.as_ref().to_string()))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
     /// Get user by SurrealDB Identifier
     pub async fn get_user(&self, id: impl AsRef<str>) -> Option<user::UserRecord> {
         let user_record: Option<user::UserRecord> =
@@ -204,6 +634,37 @@ DEFINE FUNCTION IF NOT EXISTS fn::find_person($query: string) {{
         Ok(result)
     }
 
+    /// Get user linked with external identity provider account
+    pub async fn get_user_by_external_id(
+        &self,
+        external_id: impl AsRef<str>,
+    ) -> Result<Option<user::UserRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!("SELECT * FROM {USER} WHERE external_id = $external_id"))
+            .bind(("external_id", external_id.as_ref().to_string()))
+            .await?;
+
+        let result: Option<user::UserRecord> = query.take(0usize)?;
+        Ok(result)
+    }
+
+    /// Link user with external identity provider account and set role granted by it
+    pub async fn update_user_external(
+        &self,
+        id: impl AsRef<str>,
+        external_id: impl AsRef<str>,
+        role: impl AsRef<str>,
+    ) -> Result<Option<user::UserRecord>, surrealdb::Error> {
+        self.connection
+            .update((USER, id.as_ref()))
+            .merge(json!({
+                "external_id": external_id.as_ref(),
+                "role": role.as_ref(),
+            }))
+            .await
+    }
+
     /// Update user data by SurrealDB ID
     pub async fn update_user(
         &self,
@@ -217,6 +678,38 @@ DEFINE FUNCTION IF NOT EXISTS fn::find_person($query: string) {{
             .await
     }
 
+    /// Set new user password (not a rehash) and reset its age
+    pub async fn change_user_password(
+        &self,
+        id: impl AsRef<str>,
+        password_hash: String,
+        must_change_password: bool,
+    ) -> Result<(), surrealdb::Error> {
+        self.connection
+            .query(format!(
+                "UPDATE type::thing('{USER}', $id) SET password = $password, must_change_password = $must_change_password, password_changed_at = time::now()"
+            ))
+            .bind(("id", id.as_ref().to_string()))
+            .bind(("password", password_hash))
+            .bind(("must_change_password", must_change_password))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// Update user password hash by SurrealDB ID
+    pub async fn update_user_password(
+        &self,
+        id: impl AsRef<str>,
+        password_hash: String,
+    ) -> Result<Option<user::UserRecord>, surrealdb::Error> {
+        self.connection
+            .update((USER, id.as_ref()))
+            .merge(json!({ "password": password_hash }))
+            .await
+    }
+
     pub async fn update_user_pubkey(
         &self,
         id: impl AsRef<str>,
@@ -228,15 +721,65 @@ DEFINE FUNCTION IF NOT EXISTS fn::find_person($query: string) {{
             .await
     }
 
-    /// Delete user by SurrealDB ID
-    pub async fn delete_user(
+    /// Update user two-factor authentication data by SurrealDB ID
+    pub async fn update_user_totp(
         &self,
         id: impl AsRef<str>,
+        totp_secret: Option<String>,
+        totp_enabled: bool,
+        recovery_codes: Vec<String>,
     ) -> Result<Option<user::UserRecord>, surrealdb::Error> {
-        let id = id.as_ref();
-        let deleted_user = self.connection.delete((USER, id)).await?;
+        self.connection
+            .update((USER, id.as_ref()))
+            .merge(json!({
+                "totp_secret": totp_secret,
+                "totp_enabled": totp_enabled,
+                "recovery_codes": recovery_codes,
+            }))
+            .await
+    }
+
+    /// Store time step of accepted TOTP code, `false` if this or later step was already used
+    pub async fn use_totp_step(
+        &self,
+        id: impl AsRef<str>,
+        step: u64,
+    ) -> Result<bool, surrealdb::Error> {
+        // condition is checked by the update itself, so concurrent requests can't both pass
+        let mut query = self
+            .connection
+            .query(format!(
+                "UPDATE type::thing('{USER}', $id) SET totp_last_step = $step
+                 WHERE totp_last_step IS NONE OR totp_last_step < $step"
+            ))
+            .bind(("id", id.as_ref().to_string()))
+            .bind(("step", step))
+            .await?;
+
+        let result: Option<user::UserRecord> = query.take(0usize)?;
+        Ok(result.is_some())
+    }
+
+    /// Suspend or reactivate user (users are never deleted, so their records keep authors)
+    pub async fn update_user_status(
+        &self,
+        id: impl AsRef<str>,
+        disabled: bool,
+        disabled_reason: Option<String>,
+        expires_at: Option<surrealdb::sql::Datetime>,
+    ) -> Result<Option<user::UserRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "UPDATE type::thing('{USER}', $id) SET disabled = $disabled, disabled_reason = $disabled_reason, expires_at = $expires_at"
+            ))
+            .bind(("id", id.as_ref().to_string()))
+            .bind(("disabled", disabled))
+            .bind(("disabled_reason", disabled_reason))
+            .bind(("expires_at", expires_at))
+            .await?;
 
-        Ok(deleted_user)
+        query.take(0usize)
     }
 
     /// List all users
@@ -252,36 +795,60 @@ DEFINE FUNCTION IF NOT EXISTS fn::find_person($query: string) {{
         person: person::CreatePersonRecord,
         author: impl AsRef<str>,
     ) -> Result<Option<person::PersonRecord>, surrealdb::Error> {
-        self.connection
-            .create(PERSON)
-            .content(person::PersonRecord {
-                id: None,
+        let author = author.as_ref().to_owned();
+
+        let mut query = self
+            .connection
+            .query(format!(
+                "RETURN {{
+                    LET $person = CREATE ONLY {PERSON} CONTENT $content;
+                    fn::person_revision_add($person, $action, $author, NONE);
+                    RETURN $person;
+                }}"
+            ))
+            .bind((
+                "content",
+                person::PersonRecord {
+                    id: None,
 
-                name: person.name,
-                surname: person.surname,
-                patronymic: person.patronymic,
+                    name: person.name,
+                    surname: person.surname,
+                    patronymic: person.patronymic,
 
-                birthday: person.birthday,
-                city: person.city,
-                intented_address: person.intented_address,
+                    birthday: person.birthday,
+                    city: person.city,
+                    intented_address: person.intented_address,
 
-                summary: person.summary,
-                past: person.past,
-                traits_good: person.traits_good,
-                traits_bad: person.traits_bad,
+                    summary: person.summary,
+                    past: person.past,
+                    traits_good: person.traits_good,
+                    traits_bad: person.traits_bad,
 
-                avatar: person.avatar,
-                media: person.media,
+                    avatar: person.avatar,
+                    media: person.media,
 
-                author: author.as_ref().to_owned(),
-            })
-            .await
+                    author: author.clone(),
+                },
+            ))
+            .bind(("action", revision::RevisionAction::Create.name()))
+            .bind(("author", author))
+            .await?;
+
+        query.take(0usize)
     }
 
-    /// Get Person record by SurrealDB Identifier
+    /// Get Person record by SurrealDB Identifier (records in trash are not returned)
     pub async fn get_person(&self, id: impl AsRef<str>) -> Option<person::PersonRecord> {
-        let user_record: Option<person::PersonRecord> =
-            self.connection.select((PERSON, id.as_ref())).await.ok()?;
+        let mut query = self
+            .connection
+            .query(format!(
+                "SELECT * FROM type::thing('{PERSON}', $id) WHERE deleted_at IS NONE"
+            ))
+            .bind(("id", id.as_ref().to_string()))
+            .await
+            .ok()?;
+
+        let user_record: Option<person::PersonRecord> = query.take(0usize).ok()?;
 
         user_record
     }
@@ -301,66 +868,1474 @@ DEFINE FUNCTION IF NOT EXISTS fn::find_person($query: string) {{
         query.take(0usize)
     }
 
-    /// Update person by SurrealDB Identifier
-    pub async fn update_person(
+    /// Search persons page and total number of matches (transliteration and typos are tolerated)
+    pub async fn search_persons(
         &self,
-        id: impl AsRef<str>,
-        person: person::CreatePersonRecord,
-    ) -> Result<Option<person::PersonRecord>, surrealdb::Error> {
-        self.connection
-            .update((PERSON, id.as_ref()))
-            .merge(person)
-            .await
+        search: &person::SearchPersonRecord,
+    ) -> Result<person::PersonPage, surrealdb::Error> {
+        let (page, limit) = search.page_bounds();
+        let tags = search.tag_names();
+
+        let mut condition = String::from("fn::person_search_score($this, $query) > 0");
+
+        if !tags.is_empty() {
+            condition = format!("{condition} AND {}", search.tags_mode.condition());
+        }
+
+        let mut query = self
+            .connection
+            .query(Self::persons_page_query(
+                "fn::person_search_score($this, $query) AS relevance",
+                &condition,
+                &search.order_clause(),
+            ))
+            .bind(("query", search.search_query.clone()))
+            .bind(("tags", tags))
+            .bind(("limit", limit))
+            .bind(("start", (page - 1) * limit))
+            .await?;
+
+        let items: Vec<person::PersonSummaryRecord> = query.take(0usize)?;
+        let total: Option<usize> = query.take((1usize, "count"))?;
+
+        Ok(person::PersonPage {
+            items,
+            total: total.unwrap_or_default(),
+            page,
+            limit,
+        })
     }
 
-    /// Delete person by SurrealDB Identifier
-    pub async fn delete_person(
+    /// Filtered persons page and total number of matches
+    pub async fn filter_persons(
         &self,
-        id: impl AsRef<str>,
-    ) -> Result<Option<person::PersonRecord>, surrealdb::Error> {
-        let id = id.as_ref();
-        let deleted_record = self.connection.delete((PERSON, id)).await?;
+        filter: &person::FilterPersonRecord,
+        birthday_from: Option<surrealdb::Datetime>,
+        birthday_to: Option<surrealdb::Datetime>,
+    ) -> Result<person::PersonPage, surrealdb::Error> {
+        let (page, limit) = filter.page_bounds();
+        let mut conditions = vec!["true"];
 
-        Ok(deleted_record)
-    }
+        if filter.city.is_some() {
+            conditions.push("string::lowercase(city) = string::lowercase($city)");
+        }
 
-    /// Get Persons Records list
-    pub async fn list_persons(&self) -> Result<Vec<person::PersonRecord>, surrealdb::Error> {
-        self.connection.select(PERSON).await
-    }
+        if filter.author.is_some() {
+            conditions.push("author = $author");
+        }
 
-    // INFO: Signatures Section
+        if birthday_from.is_some() {
+            conditions.push("birthday >= $birthday_from");
+        }
 
-    pub async fn add_signature(&self, signature: RecordSignature, signed_by: impl AsRef<str>) -> Result<Option<RecordSignatureRecord>, surrealdb::Error> {
-        self.connection
-            .create(SIGNATURES)
-            .content(RecordSignatureRecord {
-                id: None,
-                record_id: signature.record_id,
-                base64: signature.base64,
-                pubkey: signature.pubkey,
-                signed_by: signed_by.as_ref().to_owned()
-            })
-            .await
-    }
+        if birthday_to.is_some() {
+            conditions.push("birthday < $birthday_to");
+        }
 
-    pub async fn delete_signature(&self, record_id: impl AsRef<str>) -> Result<Option<RecordSignatureRecord>, surrealdb::Error> {
-        let mut query = self.connection
-            .query(format!("DELETE FROM {SIGNATURES} WHERE record_id = $record_id"))
-            .bind(("record_id", record_id.as_ref().to_string()))
+        if filter.has_avatar.is_some() {
+            conditions.push("(avatar != NONE AND avatar != '') = $has_avatar");
+        }
+
+        let signed_condition = format!("({}) = $signed", Self::person_signed_expression());
+
+        if filter.signed.is_some() {
+            conditions.push(&signed_condition);
+        }
+
+        let tags = filter.tag_names();
+
+        if !tags.is_empty() {
+            conditions.push(filter.tags_mode.condition());
+        }
+
+        let mut query = self
+            .connection
+            .query(Self::persons_page_query(
+                "",
+                &conditions.join(" AND "),
+                &filter.order_clause(),
+            ))
+            .bind(("city", filter.city.clone()))
+            .bind(("author", filter.author.clone()))
+            .bind(("birthday_from", birthday_from))
+            .bind(("birthday_to", birthday_to))
+            .bind(("has_avatar", filter.has_avatar))
+            .bind(("signed", filter.signed))
+            .bind(("tags", tags))
+            .bind(("limit", limit))
+            .bind(("start", (page - 1) * limit))
             .await?;
 
-        let result: Option<RecordSignatureRecord> = query.take(0usize)?;
-        Ok(result)
+        let items: Vec<person::PersonSummaryRecord> = query.take(0usize)?;
+        let total: Option<usize> = query.take((1usize, "count"))?;
+
+        Ok(person::PersonPage {
+            items,
+            total: total.unwrap_or_default(),
+            page,
+            limit,
+        })
     }
 
-    pub async fn get_signature(&self, record_id: impl AsRef<str>) -> Result<Option<RecordSignatureRecord>, surrealdb::Error> {
-        let mut query = self.connection
-            .query(format!("SELECT * FROM {SIGNATURES} WHERE record_id = $record_id"))
-            .bind(("record_id", record_id.as_ref().to_string()))
+    /// Full-text search page, ranked by relevance
+    pub async fn text_search_persons(
+        &self,
+        search: &person::TextSearchPersonRecord,
+    ) -> Result<person::PersonPage<person::PersonSearchHit>, surrealdb::Error> {
+        let (page, limit) = search.page_bounds();
+        let tags = search.tag_names();
+
+        let mut condition = String::from(
+            "deleted_at IS NONE
+            AND (summary @0@ $query OR past @1@ $query OR traits_good @2@ $query OR traits_bad @3@ $query)",
+        );
+
+        if !tags.is_empty() {
+            condition = format!("{condition} AND {}", search.tags_mode.condition());
+        }
+
+        let mut query = self
+            .connection
+            .query(format!(
+                "SELECT
+                     id AS person.id, name AS person.name, surname AS person.surname,
+                     patronymic AS person.patronymic, birthday AS person.birthday, city AS person.city,
+                     avatar AS person.avatar, author AS person.author,
+                     creation_datetime AS person.creation_datetime, {} AS person.signed,
+                     tags.{{ id, name, color }} AS person.tags,
+                     search::score(0) + search::score(1) + search::score(2) + search::score(3) AS score,
+                     search::highlight($start_marker, $end_marker, 0) AS highlights.summary,
+                     search::highlight($start_marker, $end_marker, 1) AS highlights.past,
+                     search::highlight($start_marker, $end_marker, 2) AS highlights.traits_good,
+                     search::highlight($start_marker, $end_marker, 3) AS highlights.traits_bad
+                 FROM {PERSON} WHERE {condition}
+                 ORDER BY score DESC, person.id LIMIT $limit START $start;
+                 SELECT count() FROM {PERSON} WHERE {condition} GROUP ALL;",
+                Self::person_signed_expression()
+            ))
+            .bind(("query", search.search_query.clone()))
+            .bind(("tags", tags))
+            .bind(("start_marker", person::HIGHLIGHT_START))
+            .bind(("end_marker", person::HIGHLIGHT_END))
+            .bind(("limit", limit))
+            .bind(("start", (page - 1) * limit))
             .await?;
 
-        let result: Option<RecordSignatureRecord> = query.take(0usize)?;
-        Ok(result)
+        let rows: Vec<person::PersonSearchRow> = query.take(0usize)?;
+        let total: Option<usize> = query.take((1usize, "count"))?;
+
+        Ok(person::PersonPage {
+            items: rows.into_iter().map(person::PersonSearchRow::into_hit).collect(),
+            total: total.unwrap_or_default(),
+            page,
+            limit,
+        })
+    }
+
+    /// Whether person (current document) has signature
+    fn person_signed_expression() -> String {
+        format!(
+            "(SELECT VALUE id FROM {SIGNATURES} WHERE record_id = <string> record::id($parent.id) LIMIT 1) != []"
+        )
+    }
+
+    /// Page of [person::PersonSummaryRecord] and matches count. <br/>
+    /// Projection, condition and ordering must be built from constants (values are bound as parameters),
+    /// projection adds fields used by ordering.
+    fn persons_page_query(projection: &str, condition: &str, order: &str) -> String {
+        let projection = if projection.is_empty() {
+            String::new()
+        } else {
+            format!(", {projection}")
+        };
+
+        format!(
+            "SELECT id, name, surname, patronymic, birthday, city, avatar, author, creation_datetime,
+                 {} AS signed, tags.{{ id, name, color }} AS tags{projection}
+             FROM {PERSON} WHERE deleted_at IS NONE AND ({condition})
+             ORDER BY {order}, id LIMIT $limit START $start;
+             SELECT count() FROM {PERSON} WHERE deleted_at IS NONE AND ({condition}) GROUP ALL;",
+            Self::person_signed_expression()
+        )
+    }
+
+    /// Update person by SurrealDB Identifier, `author` is username of user who made the change
+    pub async fn update_person(
+        &self,
+        id: impl AsRef<str>,
+        person: person::CreatePersonRecord,
+        author: impl AsRef<str>,
+    ) -> Result<Option<person::PersonRecord>, surrealdb::Error> {
+        self.save_person_revision(id, person, author, revision::RevisionAction::Update, None)
+            .await
+    }
+
+    /// Restore content of person's revision, restoring is stored as new revision
+    pub async fn revert_person(
+        &self,
+        id: impl AsRef<str>,
+        revision: &revision::PersonRevisionRecord,
+        author: impl AsRef<str>,
+    ) -> Result<Option<person::PersonRecord>, surrealdb::Error> {
+        let Some(snapshot) = revision.snapshot.clone() else {
+            return Ok(None);
+        };
+
+        self.save_person_revision(
+            id,
+            snapshot.into(),
+            author,
+            revision::RevisionAction::Revert,
+            Some(revision.revision),
+        )
+        .await
+    }
+
+    async fn save_person_revision(
+        &self,
+        id: impl AsRef<str>,
+        person: person::CreatePersonRecord,
+        author: impl AsRef<str>,
+        action: revision::RevisionAction,
+        reverted_from: Option<u64>,
+    ) -> Result<Option<person::PersonRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "RETURN {{
+                    LET $person = (
+                        UPDATE type::thing('{PERSON}', $id) MERGE $content WHERE deleted_at IS NONE
+                    )[0];
+                    IF $person {{
+                        fn::person_revision_add($person, $action, $author, $reverted_from);
+                    }};
+                    RETURN $person;
+                }}"
+            ))
+            .bind(("id", id.as_ref().to_string()))
+            .bind(("content", person))
+            .bind(("action", action.name()))
+            .bind(("author", author.as_ref().to_string()))
+            .bind(("reverted_from", reverted_from))
+            .await?;
+
+        query.take(0usize)
+    }
+
+    /// Move person to trash, last state is kept as revision
+    pub async fn trash_person(
+        &self,
+        id: impl AsRef<str>,
+        author: impl AsRef<str>,
+    ) -> Result<Option<person::PersonRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "RETURN {{
+                    LET $person = (
+                        UPDATE type::thing('{PERSON}', $id)
+                        SET deleted_at = time::now(), deleted_by = $author
+                        WHERE deleted_at IS NONE
+                        RETURN BEFORE
+                    )[0];
+                    IF $person {{
+                        fn::person_revision_add($person, $action, $author, NONE);
+                    }};
+                    RETURN $person;
+                }}"
+            ))
+            .bind(("id", id.as_ref().to_string()))
+            .bind(("action", revision::RevisionAction::Delete.name()))
+            .bind(("author", author.as_ref().to_string()))
+            .await?;
+
+        query.take(0usize)
+    }
+
+    /// Persons in trash, recently deleted first
+    pub async fn list_trashed_persons(
+        &self,
+    ) -> Result<Vec<person::TrashedPersonRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "SELECT id, name, surname, patronymic, author, deleted_at, deleted_by
+                 FROM {PERSON} WHERE deleted_at IS NOT NONE ORDER BY deleted_at DESC"
+            ))
+            .await?;
+
+        query.take(0usize)
+    }
+
+    /// Take person out of trash, restoring is stored as new revision
+    pub async fn restore_person(
+        &self,
+        id: impl AsRef<str>,
+        author: impl AsRef<str>,
+    ) -> Result<Option<person::PersonRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "RETURN {{
+                    LET $person = (
+                        UPDATE type::thing('{PERSON}', $id)
+                        SET deleted_at = NONE, deleted_by = NONE
+                        WHERE deleted_at IS NOT NONE
+                    )[0];
+                    IF $person {{
+                        fn::person_revision_add($person, $action, $author, NONE);
+                    }};
+                    RETURN $person;
+                }}"
+            ))
+            .bind(("id", id.as_ref().to_string()))
+            .bind(("action", revision::RevisionAction::Restore.name()))
+            .bind(("author", author.as_ref().to_string()))
+            .await?;
+
+        query.take(0usize)
+    }
+
+    /// Permanently delete person from trash with its signature and revisions
+    pub async fn purge_person(
+        &self,
+        id: impl AsRef<str>,
+    ) -> Result<Option<person::PersonRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "RETURN {{
+                    LET $person = (
+                        DELETE type::thing('{PERSON}', $id) WHERE deleted_at IS NOT NONE RETURN BEFORE
+                    )[0];
+                    IF $person {{
+                        DELETE {SIGNATURES} WHERE record_id = $id;
+                        DELETE {PERSON_REVISIONS} WHERE person_id = $id;
+                    }};
+                    RETURN $person;
+                }}"
+            ))
+            .bind(("id", id.as_ref().to_string()))
+            .await?;
+
+        query.take(0usize)
+    }
+
+    /// Permanently delete persons which are in trash longer than `retention`, returns number of purged records
+    pub async fn purge_trashed_persons(
+        &self,
+        retention: chrono::Duration,
+    ) -> Result<usize, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "RETURN {{
+                    LET $ids = array::map(
+                        (DELETE {PERSON} WHERE deleted_at < $deleted_before RETURN BEFORE),
+                        |$person| <string> record::id($person.id)
+                    );
+                    -- `INSIDE` isn't matched by composite revisions index
+                    DELETE {SIGNATURES} WHERE $ids CONTAINS record_id;
+                    DELETE {PERSON_REVISIONS} WHERE $ids CONTAINS person_id;
+                    RETURN array::len($ids);
+                }}"
+            ))
+            .bind((
+                "deleted_before",
+                surrealdb::Datetime::from(chrono::Utc::now() - retention),
+            ))
+            .await?;
+
+        let purged: Option<usize> = query.take(0usize)?;
+        Ok(purged.unwrap_or_default())
+    }
+
+    /// Person's revisions without snapshots, newest first
+    pub async fn list_person_revisions(
+        &self,
+        person_id: impl AsRef<str>,
+    ) -> Result<Vec<revision::PersonRevisionRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "SELECT id, person_id, revision, action, author, creation_datetime, reverted_from, tags
+                 FROM {PERSON_REVISIONS} WHERE person_id = $person_id ORDER BY revision DESC"
+            ))
+            .bind(("person_id", person_id.as_ref().to_string()))
+            .await?;
+
+        query.take(0usize)
+    }
+
+    pub async fn get_person_revision(
+        &self,
+        person_id: impl AsRef<str>,
+        revision: u64,
+    ) -> Result<Option<revision::PersonRevisionRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "SELECT * FROM {PERSON_REVISIONS} WHERE person_id = $person_id AND revision = $revision LIMIT 1"
+            ))
+            .bind(("person_id", person_id.as_ref().to_string()))
+            .bind(("revision", revision))
+            .await?;
+
+        let result: Option<revision::PersonRevisionRecord> = query.take(0usize)?;
+        Ok(result)
+    }
+
+    // INFO: Person Relations Section
+
+    /// Relate person with another one, `None` if persons are already related the same way
+    pub async fn add_person_relation(
+        &self,
+        id: impl AsRef<str>,
+        relation: &relation::CreateRelationRecord,
+        author: impl AsRef<str>,
+    ) -> Result<Option<relation::PersonRelationRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "RETURN {{
+                    LET $from = type::thing('{PERSON}', $from_id);
+                    LET $to = type::thing('{PERSON}', $to_id);
+                    -- relations are mutual, so direction doesn't matter
+                    LET $existing = SELECT VALUE id FROM {PERSON_RELATIONS}
+                        WHERE ((in = $from AND out = $to) OR (in = $to AND out = $from))
+                            AND kind = $kind AND label = $label
+                        LIMIT 1;
+                    IF $existing != [] {{
+                        RETURN NONE;
+                    }};
+                    RETURN (RELATE $from->{PERSON_RELATIONS}->$to CONTENT {{
+                        kind: $kind,
+                        label: $label,
+                        since: $since,
+                        until: $until,
+                        note: $note,
+                        author: $author
+                    }})[0];
+                }}"
+            ))
+            .bind(("from_id", id.as_ref().to_string()))
+            .bind(("to_id", relation.person_id.clone()))
+            .bind(("kind", relation.kind.name()))
+            .bind(("label", relation.label()))
+            .bind(("since", relation.since.clone()))
+            .bind(("until", relation.until.clone()))
+            .bind(("note", relation.note.clone()))
+            .bind(("author", author.as_ref().to_string()))
+            .await?;
+
+        query.take(0usize)
+    }
+
+    /// Person's relations in both directions (relations with persons in trash are hidden)
+    pub async fn list_person_connections(
+        &self,
+        id: impl AsRef<str>,
+    ) -> Result<Vec<relation::PersonConnectionRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "LET $person = type::thing('{PERSON}', $id);
+                 SELECT
+                     id,
+                     IF in = $person {{ 'outgoing' }} ELSE {{ 'incoming' }} AS direction,
+                     (IF in = $person {{ out }} ELSE {{ in }}).{{ id, name, surname, patronymic, avatar }} AS person,
+                     kind, label, since, until, note, author, creation_datetime
+                 FROM {PERSON_RELATIONS}
+                 WHERE (in = $person OR out = $person) AND in.deleted_at IS NONE AND out.deleted_at IS NONE
+                 ORDER BY kind, creation_datetime;"
+            ))
+            .bind(("id", id.as_ref().to_string()))
+            .await?;
+
+        query.take(1usize)
+    }
+
+    /// Remove relation, `None` if there is no such relation of the person
+    pub async fn remove_person_relation(
+        &self,
+        id: impl AsRef<str>,
+        relation_id: impl AsRef<str>,
+    ) -> Result<Option<relation::PersonRelationRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "LET $person = type::thing('{PERSON}', $id);
+                 (DELETE type::thing('{PERSON_RELATIONS}', $relation_id)
+                     WHERE in = $person OR out = $person
+                     RETURN BEFORE)[0];"
+            ))
+            .bind(("id", id.as_ref().to_string()))
+            .bind(("relation_id", relation_id.as_ref().to_string()))
+            .await?;
+
+        query.take(1usize)
+    }
+
+    /// Persons within `depth` hops from person and relations between them, persons in trash are
+    /// skipped (graph is walked by database, only reachable part is loaded)
+    pub async fn person_network(
+        &self,
+        id: impl AsRef<str>,
+        depth: u32,
+    ) -> Result<relation::PersonNetwork, surrealdb::Error> {
+        // persons reached within 1, 2, ... `depth` hops (each one includes previous)
+        let levels = (1..=depth)
+            .map(|hops| {
+                format!(
+                    "$start.{{1..{hops}+collect+inclusive}}({})",
+                    Self::relation_step()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let mut query = self
+            .connection
+            .query(format!(
+                "LET $start = type::thing('{PERSON}', $id);
+                 LET $levels = [{levels}];
+                 LET $persons = array::last($levels) ?? [];
+                 RETURN $levels;
+                 SELECT id, name, surname, patronymic, avatar FROM $persons;
+                 {}",
+                Self::relation_edges_query()
+            ))
+            .bind(("id", id.as_ref().to_string()))
+            .await?;
+
+        let levels: Vec<Vec<surrealdb::sql::Thing>> = query.take(3usize)?;
+        let persons: Vec<relation::RelatedPersonRecord> = query.take(4usize)?;
+        let mut edges: Vec<relation::RelationEdge> = query.take(5usize)?;
+
+        let distance = |person_id: &surrealdb::sql::Thing| {
+            levels
+                .iter()
+                .position(|level| level.contains(person_id))
+                .map(|hops| hops as u32 + 1)
+        };
+
+        let mut nodes = persons
+            .into_iter()
+            .filter_map(|person| {
+                let person_id = person.id.as_ref()?;
+                let distance = if person_id.id.to_raw() == id.as_ref() {
+                    0
+                } else {
+                    distance(person_id)?
+                };
+
+                Some(relation::NetworkNode { person, distance })
+            })
+            .collect::<Vec<_>>();
+
+        nodes.sort_by(|a, b| {
+            (a.distance, &a.person.surname, &a.person.name)
+                .cmp(&(b.distance, &b.person.surname, &b.person.name))
+        });
+
+        edges.sort_by_key(|edge| edge.id.to_string());
+
+        Ok(relation::PersonNetwork { nodes, edges })
+    }
+
+    /// Shortest chain of relations between two persons (found by database),
+    /// `None` if persons aren't connected within [relation::MAX_PATH_LENGTH] relations
+    pub async fn person_path(
+        &self,
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+    ) -> Result<Option<relation::PersonPath>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "LET $start = type::thing('{PERSON}', $from);
+                 LET $end = type::thing('{PERSON}', $to);
+                 LET $persons = $start.{{..{}+shortest=$end+inclusive}}({}) ?? [];
+                 RETURN $persons;
+                 SELECT id, name, surname, patronymic, avatar FROM $persons;
+                 {}",
+                relation::MAX_PATH_LENGTH,
+                Self::relation_step(),
+                Self::relation_edges_query()
+            ))
+            .bind(("from", from.as_ref().to_string()))
+            .bind(("to", to.as_ref().to_string()))
+            .await?;
+
+        let ids: Vec<surrealdb::sql::Thing> = query.take(3usize)?;
+        let mut persons: Vec<relation::RelatedPersonRecord> = query.take(4usize)?;
+        let edges: Vec<relation::RelationEdge> = query.take(5usize)?;
+
+        let ids = ids.iter().map(|id| id.id.to_raw()).collect::<Vec<_>>();
+
+        if ids.len() < 2 || ids.last() != Some(&to.as_ref().to_string()) {
+            return Ok(None);
+        }
+
+        // any relation between neighbouring persons of chain
+        let relations = ids
+            .windows(2)
+            .map(|pair| {
+                edges
+                    .iter()
+                    .filter(|edge| edge.other(&pair[0]) == Some(pair[1].as_str()))
+                    .min_by_key(|edge| edge.id.to_string())
+                    .cloned()
+            })
+            .collect::<Option<Vec<_>>>();
+
+        // record is missing if person was moved to trash during search
+        let Some(relations) = relations.filter(|_| persons.len() == ids.len()) else {
+            return Ok(None);
+        };
+
+        persons.sort_by_key(|person| {
+            let person_id = person.id.as_ref().map(|id| id.id.to_raw());
+            ids.iter().position(|id| Some(id) == person_id.as_ref())
+        });
+
+        Ok(Some(relation::PersonPath { persons, relations }))
+    }
+
+    /// One hop of graph walk: relation in any direction to person which isn't in trash
+    fn relation_step() -> String {
+        format!("<->{PERSON_RELATIONS}<->({PERSON} WHERE deleted_at IS NONE)")
+    }
+
+    /// Relations between `$persons` (which must be bound and not in trash)
+    fn relation_edges_query() -> String {
+        format!(
+            "SELECT id, <string> record::id(in) AS from, <string> record::id(out) AS to, kind, label
+             FROM array::distinct(array::flatten($persons<->{PERSON_RELATIONS}))
+             WHERE in IN $persons AND out IN $persons;"
+        )
+    }
+
+    // INFO: Tags Section
+
+    /// Tags with number of tagged persons, ordered by name
+    pub async fn list_tags(&self) -> Result<Vec<tag::TagUsageRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "SELECT
+                     id, name, color,
+                     (SELECT count() FROM {PERSON} WHERE tags CONTAINS $parent.id AND deleted_at IS NONE GROUP ALL)[0].count ?? 0 AS count
+                 FROM {TAGS} ORDER BY name"
+            ))
+            .await?;
+
+        query.take(0usize)
+    }
+
+    pub async fn get_tag(&self, id: impl AsRef<str>) -> Option<tag::TagRecord> {
+        self.connection.select((TAGS, id.as_ref())).await.ok()?
+    }
+
+    pub async fn get_tag_by_name(
+        &self,
+        name: impl AsRef<str>,
+    ) -> Result<Option<tag::TagRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!("SELECT * FROM {TAGS} WHERE name = $name LIMIT 1"))
+            .bind(("name", name.as_ref().to_string()))
+            .await?;
+
+        query.take(0usize)
+    }
+
+    pub async fn create_tag(
+        &self,
+        tag: &tag::CreateTagRecord,
+    ) -> Result<Option<tag::TagRecord>, surrealdb::Error> {
+        self.connection
+            .create(TAGS)
+            .content(json!({
+                "name": tag.name(),
+                "color": tag.color,
+            }))
+            .await
+    }
+
+    /// Rename or recolor tag, tagged persons reference tag by identifier so they are updated too
+    pub async fn update_tag(
+        &self,
+        id: impl AsRef<str>,
+        tag: &tag::CreateTagRecord,
+    ) -> Result<Option<tag::TagRecord>, surrealdb::Error> {
+        self.connection
+            .update((TAGS, id.as_ref()))
+            .merge(json!({
+                "name": tag.name(),
+                "color": tag.color,
+            }))
+            .await
+    }
+
+    /// Delete tag and remove it from all persons
+    pub async fn delete_tag(
+        &self,
+        id: impl AsRef<str>,
+    ) -> Result<Option<tag::TagRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "RETURN {{
+                    LET $tag = type::thing('{TAGS}', $id);
+                    UPDATE {PERSON} SET tags = array::complement(tags, [$tag]) WHERE tags CONTAINS $tag;
+                    RETURN (DELETE $tag RETURN BEFORE)[0];
+                }}"
+            ))
+            .bind(("id", id.as_ref().to_string()))
+            .await?;
+
+        query.take(0usize)
+    }
+
+    /// Replace tag with another one on all persons and delete it,
+    /// `None` if any of tags doesn't exist
+    pub async fn merge_tags(
+        &self,
+        source_id: impl AsRef<str>,
+        target_id: impl AsRef<str>,
+    ) -> Result<Option<tag::TagRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "RETURN {{
+                    LET $source = type::thing('{TAGS}', $source_id);
+                    LET $target = type::thing('{TAGS}', $target_id);
+                    IF $source.id IS NONE OR $target.id IS NONE OR $source = $target {{
+                        RETURN NONE;
+                    }};
+                    UPDATE {PERSON} SET tags = array::union(array::complement(tags, [$source]), [$target])
+                        WHERE tags CONTAINS $source;
+                    DELETE $source;
+                    RETURN (SELECT * FROM $target)[0];
+                }}"
+            ))
+            .bind(("source_id", source_id.as_ref().to_string()))
+            .bind(("target_id", target_id.as_ref().to_string()))
+            .await?;
+
+        query.take(0usize)
+    }
+
+    /// Person's tags ordered by name
+    pub async fn get_person_tags(
+        &self,
+        id: impl AsRef<str>,
+    ) -> Result<Vec<tag::TagRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "SELECT id, name, color FROM type::thing('{PERSON}', $id).tags ORDER BY name"
+            ))
+            .bind(("id", id.as_ref().to_string()))
+            .await?;
+
+        query.take(0usize)
+    }
+
+    /// Replace person's tags, change is stored as revision (`author` is username of user who made
+    /// the change)
+    pub async fn set_person_tags(
+        &self,
+        id: impl AsRef<str>,
+        tag_ids: Vec<String>,
+        author: impl AsRef<str>,
+    ) -> Result<tag::PersonTagsOutcome, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "RETURN {{
+                    LET $tags = array::distinct(array::map($tag_ids, |$id| type::thing('{TAGS}', $id)));
+                    IF array::len(SELECT VALUE id FROM $tags) != array::len($tags) {{
+                        RETURN {{ status: 'unknown_tags' }};
+                    }};
+
+                    LET $current = (
+                        SELECT VALUE tags FROM type::thing('{PERSON}', $id) WHERE deleted_at IS NONE
+                    )[0];
+                    IF $current IS NONE {{
+                        RETURN {{ status: 'person_not_found' }};
+                    }};
+
+                    IF array::sort($current) != array::sort($tags) {{
+                        LET $person = (
+                            UPDATE type::thing('{PERSON}', $id) SET tags = $tags WHERE deleted_at IS NONE
+                        )[0];
+                        fn::person_revision_add($person, $action, $author, NONE);
+                    }};
+
+                    RETURN {{ status: 'tagged', tags: (SELECT id, name, color FROM $tags ORDER BY name) }};
+                }}"
+            ))
+            .bind(("id", id.as_ref().to_string()))
+            .bind(("tag_ids", tag_ids))
+            .bind(("action", revision::RevisionAction::Tag.name()))
+            .bind(("author", author.as_ref().to_string()))
+            .await?;
+
+        let outcome: Option<tag::PersonTagsOutcome> = query.take(0usize)?;
+        Ok(outcome.unwrap_or(tag::PersonTagsOutcome::PersonNotFound))
+    }
+
+    // INFO: Signatures Section
+
+    pub async fn add_signature(&self, signature: RecordSignature, signed_by: impl AsRef<str>) -> Result<Option<RecordSignatureRecord>, surrealdb::Error> {
+        self.connection
+            .create(SIGNATURES)
+            .content(RecordSignatureRecord {
+                id: None,
+                record_id: signature.record_id,
+                base64: signature.base64,
+                pubkey: signature.pubkey,
+                signed_by: signed_by.as_ref().to_owned()
+            })
+            .await
+    }
+
+    pub async fn delete_signature(&self, record_id: impl AsRef<str>) -> Result<Option<RecordSignatureRecord>, surrealdb::Error> {
+        let mut query = self.connection
+            .query(format!("DELETE FROM {SIGNATURES} WHERE record_id = $record_id"))
+            .bind(("record_id", record_id.as_ref().to_string()))
+            .await?;
+
+        let result: Option<RecordSignatureRecord> = query.take(0usize)?;
+        Ok(result)
+    }
+
+    pub async fn get_signature(&self, record_id: impl AsRef<str>) -> Result<Option<RecordSignatureRecord>, surrealdb::Error> {
+        let mut query = self.connection
+            .query(format!("SELECT * FROM {SIGNATURES} WHERE record_id = $record_id"))
+            .bind(("record_id", record_id.as_ref().to_string()))
+            .await?;
+
+        let result: Option<RecordSignatureRecord> = query.take(0usize)?;
+        Ok(result)
+    }
+
+    // INFO: Tokens Section
+
+    /// Store new refresh token
+    pub async fn add_refresh_token(
+        &self,
+        token: token::RefreshTokenRecord,
+    ) -> Result<Option<token::RefreshTokenRecord>, surrealdb::Error> {
+        self.connection.create(REFRESH_TOKENS).content(token).await
+    }
+
+    /// Get refresh token by its hash
+    pub async fn get_refresh_token(
+        &self,
+        token_hash: impl AsRef<str>,
+    ) -> Result<Option<token::RefreshTokenRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "SELECT * FROM {REFRESH_TOKENS} WHERE token_hash = $token_hash"
+            ))
+            .bind(("token_hash", token_hash.as_ref().to_string()))
+            .await?;
+
+        let result: Option<token::RefreshTokenRecord> = query.take(0usize)?;
+        Ok(result)
+    }
+
+    /// Mark refresh token as used. <br/>
+    /// Returns `None` if token was already revoked (so it can't be rotated twice).
+    pub async fn consume_refresh_token(
+        &self,
+        token_hash: impl AsRef<str>,
+    ) -> Result<Option<token::RefreshTokenRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "UPDATE {REFRESH_TOKENS} SET revoked = true WHERE token_hash = $token_hash AND revoked = false RETURN BEFORE"
+            ))
+            .bind(("token_hash", token_hash.as_ref().to_string()))
+            .await?;
+
+        let result: Option<token::RefreshTokenRecord> = query.take(0usize)?;
+        Ok(result)
+    }
+
+    /// Revoke every refresh token in the chain
+    pub async fn revoke_refresh_family(
+        &self,
+        family: impl AsRef<str>,
+    ) -> Result<(), surrealdb::Error> {
+        self.connection
+            .query(format!(
+                "UPDATE {REFRESH_TOKENS} SET revoked = true WHERE family = $family"
+            ))
+            .bind(("family", family.as_ref().to_string()))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// Put access token identifier to revocation list
+    pub async fn revoke_access_token(
+        &self,
+        jti: impl AsRef<str>,
+        expires_at: surrealdb::Datetime,
+    ) -> Result<(), surrealdb::Error> {
+        let _: Option<token::RevokedTokenRecord> = self
+            .connection
+            .create(REVOKED_TOKENS)
+            .content(token::RevokedTokenRecord {
+                id: None,
+                jti: jti.as_ref().to_owned(),
+                expires_at,
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Check if access token identifier is on revocation list
+    pub async fn is_access_token_revoked(
+        &self,
+        jti: impl AsRef<str>,
+    ) -> Result<bool, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "SELECT * FROM {REVOKED_TOKENS} WHERE jti = $jti LIMIT 1"
+            ))
+            .bind(("jti", jti.as_ref().to_string()))
+            .await?;
+
+        let result: Option<token::RevokedTokenRecord> = query.take(0usize)?;
+        Ok(result.is_some())
+    }
+
+    /// Remove expired refresh tokens, sessions, revocation list entries and pending logins
+    pub async fn purge_expired_tokens(&self) -> Result<(), surrealdb::Error> {
+        self.connection
+            .query(format!(
+                "DELETE FROM {REFRESH_TOKENS} WHERE expires_at < time::now();
+                 DELETE FROM {REVOKED_TOKENS} WHERE expires_at < time::now();
+                 DELETE FROM {SESSIONS} WHERE expires_at < time::now();
+                 DELETE FROM {OIDC_LOGINS} WHERE expires_at < time::now();
+                 DELETE FROM {PASSKEY_CEREMONIES} WHERE expires_at < time::now();
+                 DELETE FROM {TWO_FACTOR_CHALLENGES} WHERE expires_at < time::now();"
+            ))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    // INFO: Settings Section
+
+    /// Get security settings (defaults if never saved)
+    pub async fn get_security_settings(
+        &self,
+    ) -> Result<settings::SecuritySettings, surrealdb::Error> {
+        let settings: Option<settings::SecuritySettings> = self
+            .connection
+            .select((SETTINGS, SECURITY_SETTINGS_ID))
+            .await?;
+
+        Ok(settings.unwrap_or_default())
+    }
+
+    /// Replace security settings
+    pub async fn update_security_settings(
+        &self,
+        settings: settings::SecuritySettings,
+    ) -> Result<Option<settings::SecuritySettings>, surrealdb::Error> {
+        self.connection
+            .upsert((SETTINGS, SECURITY_SETTINGS_ID))
+            .content(settings)
+            .await
+    }
+
+    // INFO: Roles Section
+
+    /// Create new role (record identifier is the role name)
+    pub async fn create_role(
+        &self,
+        role: role::RoleRecord,
+    ) -> Result<Option<role::RoleRecord>, surrealdb::Error> {
+        self.connection
+            .create((ROLES, role.name.clone()))
+            .content(role)
+            .await
+    }
+
+    /// Get role by its name
+    pub async fn get_role(
+        &self,
+        name: impl AsRef<str>,
+    ) -> Result<Option<role::RoleRecord>, surrealdb::Error> {
+        self.connection.select((ROLES, name.as_ref())).await
+    }
+
+    /// Get all roles
+    pub async fn get_roles(&self) -> Result<Vec<role::RoleRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "SELECT * FROM {ROLES} ORDER BY builtin DESC, creation_datetime"
+            ))
+            .await?;
+
+        let result: Vec<role::RoleRecord> = query.take(0usize)?;
+        Ok(result)
+    }
+
+    /// Replace role permissions
+    pub async fn update_role_permissions(
+        &self,
+        name: impl AsRef<str>,
+        permissions: Vec<crate::auth::permission::Permission>,
+    ) -> Result<Option<role::RoleRecord>, surrealdb::Error> {
+        self.connection
+            .update((ROLES, name.as_ref()))
+            .merge(json!({ "permissions": permissions }))
+            .await
+    }
+
+    /// Delete role
+    pub async fn delete_role(
+        &self,
+        name: impl AsRef<str>,
+    ) -> Result<Option<role::RoleRecord>, surrealdb::Error> {
+        self.connection.delete((ROLES, name.as_ref())).await
+    }
+
+    /// Count users which have role assigned
+    pub async fn count_role_users(&self, name: impl AsRef<str>) -> Result<usize, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "SELECT count() FROM {USER} WHERE role = $role GROUP ALL"
+            ))
+            .bind(("role", name.as_ref().to_string()))
+            .await?;
+
+        let count: Option<usize> = query.take((0, "count"))?;
+        Ok(count.unwrap_or_default())
+    }
+
+    // INFO: Login Attempts Section
+
+    /// Get failed login attempts counter by key
+    pub async fn get_login_attempt(
+        &self,
+        key: impl AsRef<str>,
+    ) -> Result<Option<login_attempt::LoginAttemptRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!("SELECT * FROM {LOGIN_ATTEMPTS} WHERE key = $key"))
+            .bind(("key", key.as_ref().to_string()))
+            .await?;
+
+        let result: Option<login_attempt::LoginAttemptRecord> = query.take(0usize)?;
+        Ok(result)
+    }
+
+    /// Create or replace failed login attempts counter
+    pub async fn set_login_attempt(
+        &self,
+        attempt: login_attempt::LoginAttemptRecord,
+    ) -> Result<(), surrealdb::Error> {
+        self.connection
+            .query(format!(
+                "UPSERT {LOGIN_ATTEMPTS} MERGE {{ key: $key, failures: $failures, last_failure: $last_failure, locked_until: $locked_until }} WHERE key = $key"
+            ))
+            .bind(("key", attempt.key))
+            .bind(("failures", attempt.failures))
+            .bind(("last_failure", attempt.last_failure))
+            .bind(("locked_until", attempt.locked_until))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// Reset failed login attempts counter
+    pub async fn delete_login_attempt(&self, key: impl AsRef<str>) -> Result<(), surrealdb::Error> {
+        self.connection
+            .query(format!("DELETE FROM {LOGIN_ATTEMPTS} WHERE key = $key"))
+            .bind(("key", key.as_ref().to_string()))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    // INFO: Sessions Section
+
+    /// Create new session
+    pub async fn create_session(
+        &self,
+        session: session::SessionRecord,
+    ) -> Result<Option<session::SessionRecord>, surrealdb::Error> {
+        self.connection.create(SESSIONS).content(session).await
+    }
+
+    /// Get session by SurrealDB Identifier
+    pub async fn get_session(
+        &self,
+        id: impl AsRef<str>,
+    ) -> Result<Option<session::SessionRecord>, surrealdb::Error> {
+        self.connection.select((SESSIONS, id.as_ref())).await
+    }
+
+    /// List active (not revoked and not expired) sessions of user
+    pub async fn list_user_sessions(
+        &self,
+        user_id: impl AsRef<str>,
+    ) -> Result<Vec<session::SessionRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "SELECT * FROM {SESSIONS} WHERE user_id = $user_id AND revoked = false AND expires_at > time::now() ORDER BY last_seen DESC"
+            ))
+            .bind(("user_id", user_id.as_ref().to_string()))
+            .await?;
+
+        query.take(0usize)
+    }
+
+    /// Update session last activity timestamp
+    pub async fn touch_session(&self, id: impl AsRef<str>) -> Result<(), surrealdb::Error> {
+        self.connection
+            .query(format!(
+                "UPDATE type::thing('{SESSIONS}', $id) SET last_seen = time::now()"
+            ))
+            .bind(("id", id.as_ref().to_string()))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// Prolong session (on refresh)
+    pub async fn extend_session(
+        &self,
+        id: impl AsRef<str>,
+        expires_at: surrealdb::Datetime,
+    ) -> Result<(), surrealdb::Error> {
+        self.connection
+            .query(format!(
+                "UPDATE type::thing('{SESSIONS}', $id) SET last_seen = time::now(), expires_at = $expires_at"
+            ))
+            .bind(("id", id.as_ref().to_string()))
+            .bind(("expires_at", expires_at))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// Revoke all sessions of user together with their refresh tokens
+    pub async fn revoke_user_sessions(&self, user_id: impl AsRef<str>) -> Result<(), surrealdb::Error> {
+        self.connection
+            .query(format!(
+                "UPDATE {SESSIONS} SET revoked = true WHERE user_id = $user_id AND revoked = false;
+                UPDATE {REFRESH_TOKENS} SET revoked = true WHERE user_id = $user_id AND revoked = false;"
+            ))
+            .bind(("user_id", user_id.as_ref().to_string()))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// Revoke session together with its refresh tokens chain
+    pub async fn revoke_session(&self, id: impl AsRef<str>) -> Result<(), surrealdb::Error> {
+        let id = id.as_ref();
+
+        let _: Option<session::SessionRecord> = self
+            .connection
+            .update((SESSIONS, id))
+            .merge(json!({ "revoked": true }))
+            .await?;
+
+        self.revoke_refresh_family(id).await
+    }
+
+    // INFO: Personal API Tokens Section
+
+    /// Store new personal access token
+    pub async fn add_api_token(
+        &self,
+        token: api_token::ApiTokenRecord,
+    ) -> Result<Option<api_token::ApiTokenRecord>, surrealdb::Error> {
+        self.connection.create(API_TOKENS).content(token).await
+    }
+
+    /// Get personal access token by its hash
+    pub async fn get_api_token_by_hash(
+        &self,
+        token_hash: impl AsRef<str>,
+    ) -> Result<Option<api_token::ApiTokenRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "SELECT * FROM {API_TOKENS} WHERE token_hash = $token_hash"
+            ))
+            .bind(("token_hash", token_hash.as_ref().to_string()))
+            .await?;
+
+        let result: Option<api_token::ApiTokenRecord> = query.take(0usize)?;
+        Ok(result)
+    }
+
+    /// Get personal access token by SurrealDB Identifier
+    pub async fn get_api_token(
+        &self,
+        id: impl AsRef<str>,
+    ) -> Result<Option<api_token::ApiTokenRecord>, surrealdb::Error> {
+        self.connection.select((API_TOKENS, id.as_ref())).await
+    }
+
+    /// List personal access tokens of user
+    pub async fn list_user_api_tokens(
+        &self,
+        user_id: impl AsRef<str>,
+    ) -> Result<Vec<api_token::ApiTokenRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "SELECT * FROM {API_TOKENS} WHERE user_id = $user_id ORDER BY creation_datetime DESC"
+            ))
+            .bind(("user_id", user_id.as_ref().to_string()))
+            .await?;
+
+        query.take(0usize)
+    }
+
+    /// Update personal access token last usage timestamp
+    pub async fn touch_api_token(&self, id: impl AsRef<str>) -> Result<(), surrealdb::Error> {
+        self.connection
+            .query(format!(
+                "UPDATE type::thing('{API_TOKENS}', $id) SET last_used = time::now()"
+            ))
+            .bind(("id", id.as_ref().to_string()))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// Delete (revoke) personal access token by SurrealDB Identifier
+    pub async fn delete_api_token(
+        &self,
+        id: impl AsRef<str>,
+    ) -> Result<Option<api_token::ApiTokenRecord>, surrealdb::Error> {
+        self.connection.delete((API_TOKENS, id.as_ref())).await
+    }
+
+    /// Delete (revoke) every personal access token of user, returns deleted tokens
+    pub async fn delete_user_api_tokens(
+        &self,
+        user_id: impl AsRef<str>,
+    ) -> Result<Vec<api_token::ApiTokenRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "DELETE FROM {API_TOKENS} WHERE user_id = $user_id RETURN BEFORE"
+            ))
+            .bind(("user_id", user_id.as_ref().to_string()))
+            .await?;
+
+        query.take(0usize)
+    }
+
+    // INFO: Two-Factor Challenges Section
+
+    /// Store issued two-factor challenge
+    pub async fn add_two_factor_challenge(
+        &self,
+        challenge: token::TwoFactorChallengeRecord,
+    ) -> Result<(), surrealdb::Error> {
+        let _: Option<token::TwoFactorChallengeRecord> = self
+            .connection
+            .create(TWO_FACTOR_CHALLENGES)
+            .content(challenge)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Remove issued challenge and return it (only once, if not expired)
+    pub async fn take_two_factor_challenge(
+        &self,
+        jti: impl AsRef<str>,
+    ) -> Result<Option<token::TwoFactorChallengeRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "DELETE FROM {TWO_FACTOR_CHALLENGES} WHERE jti = $jti RETURN BEFORE"
+            ))
+            .bind(("jti", jti.as_ref().to_string()))
+            .await?;
+
+        let result: Option<token::TwoFactorChallengeRecord> = query.take(0usize)?;
+        Ok(result.filter(|challenge| challenge.expires_at.0 > chrono::Utc::now()))
+    }
+
+    // INFO: Single Sign-On Section
+
+    /// Store started single sign-on login
+    pub async fn add_oidc_login(
+        &self,
+        login: oidc_login::OidcLoginRecord,
+    ) -> Result<(), surrealdb::Error> {
+        let _: Option<oidc_login::OidcLoginRecord> =
+            self.connection.create(OIDC_LOGINS).content(login).await?;
+
+        Ok(())
+    }
+
+    /// Remove started login by its state and return it (only once, if not expired)
+    pub async fn take_oidc_login(
+        &self,
+        state: impl AsRef<str>,
+    ) -> Result<Option<oidc_login::OidcLoginRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "DELETE FROM {OIDC_LOGINS} WHERE state = $state RETURN BEFORE"
+            ))
+            .bind(("state", state.as_ref().to_string()))
+            .await?;
+
+        let result: Option<oidc_login::OidcLoginRecord> = query.take(0usize)?;
+        Ok(result.filter(|login| login.expires_at.0 > chrono::Utc::now()))
+    }
+
+    // INFO: Passkeys Section
+
+    /// Store registered passkey
+    pub async fn add_passkey(
+        &self,
+        passkey: passkey::PasskeyRecord,
+    ) -> Result<Option<passkey::PasskeyRecord>, surrealdb::Error> {
+        self.connection.create(PASSKEYS).content(passkey).await
+    }
+
+    /// Get passkey by SurrealDB Identifier
+    pub async fn get_passkey(
+        &self,
+        id: impl AsRef<str>,
+    ) -> Result<Option<passkey::PasskeyRecord>, surrealdb::Error> {
+        self.connection.select((PASSKEYS, id.as_ref())).await
+    }
+
+    /// Get passkey by its base64url encoded credential identifier
+    pub async fn get_passkey_by_credential_id(
+        &self,
+        credential_id: impl AsRef<str>,
+    ) -> Result<Option<passkey::PasskeyRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "SELECT * FROM {PASSKEYS} WHERE credential_id = $credential_id"
+            ))
+            .bind(("credential_id", credential_id.as_ref().to_string()))
+            .await?;
+
+        let result: Option<passkey::PasskeyRecord> = query.take(0usize)?;
+        Ok(result)
+    }
+
+    /// List passkeys of user
+    pub async fn list_user_passkeys(
+        &self,
+        user_id: impl AsRef<str>,
+    ) -> Result<Vec<passkey::PasskeyRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "SELECT * FROM {PASSKEYS} WHERE user_id = $user_id ORDER BY creation_datetime DESC"
+            ))
+            .bind(("user_id", user_id.as_ref().to_string()))
+            .await?;
+
+        query.take(0usize)
+    }
+
+    /// Store updated passkey (signature counter) after login
+    pub async fn use_passkey(
+        &self,
+        id: impl AsRef<str>,
+        passkey: impl AsRef<str>,
+    ) -> Result<(), surrealdb::Error> {
+        self.connection
+            .query(format!(
+                "UPDATE type::thing('{PASSKEYS}', $id) SET passkey = $passkey, last_used = time::now()"
+            ))
+            .bind(("id", id.as_ref().to_string()))
+            .bind(("passkey", passkey.as_ref().to_string()))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// Rename passkey
+    pub async fn rename_passkey(
+        &self,
+        id: impl AsRef<str>,
+        name: impl AsRef<str>,
+    ) -> Result<Option<passkey::PasskeyRecord>, surrealdb::Error> {
+        self.connection
+            .update((PASSKEYS, id.as_ref()))
+            .merge(json!({ "name": name.as_ref() }))
+            .await
+    }
+
+    /// Delete (revoke) passkey by SurrealDB Identifier
+    pub async fn delete_passkey(
+        &self,
+        id: impl AsRef<str>,
+    ) -> Result<Option<passkey::PasskeyRecord>, surrealdb::Error> {
+        self.connection.delete((PASSKEYS, id.as_ref())).await
+    }
+
+    /// Store started WebAuthn ceremony
+    pub async fn add_passkey_ceremony(
+        &self,
+        ceremony: passkey::PasskeyCeremonyRecord,
+    ) -> Result<(), surrealdb::Error> {
+        let _: Option<passkey::PasskeyCeremonyRecord> =
+            self.connection.create(PASSKEY_CEREMONIES).content(ceremony).await?;
+
+        Ok(())
+    }
+
+    /// Remove started ceremony and return it (only once, if not expired and of expected kind)
+    pub async fn take_passkey_ceremony(
+        &self,
+        ceremony_id: impl AsRef<str>,
+        kind: impl AsRef<str>,
+    ) -> Result<Option<passkey::PasskeyCeremonyRecord>, surrealdb::Error> {
+        let mut query = self
+            .connection
+            .query(format!(
+                "DELETE FROM {PASSKEY_CEREMONIES} WHERE ceremony_id = $ceremony_id RETURN BEFORE"
+            ))
+            .bind(("ceremony_id", ceremony_id.as_ref().to_string()))
+            .await?;
+
+        let result: Option<passkey::PasskeyCeremonyRecord> = query.take(0usize)?;
+        Ok(result.filter(|ceremony| {
+            ceremony.kind == kind.as_ref() && ceremony.expires_at.0 > chrono::Utc::now()
+        }))
     }
 }
@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::{Datetime, Thing};
+
+/// Started single sign-on login, consumed by callback
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OidcLoginRecord {
+    pub id: Option<Thing>,
+    /// `state` parameter, binds callback to this login
+    pub state: String,
+    /// Expected `nonce` claim of ID token
+    pub nonce: String,
+    /// PKCE code verifier
+    pub code_verifier: String,
+    pub expires_at: Datetime,
+}
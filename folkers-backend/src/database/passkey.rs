@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::{Datetime, Thing};
+
+/// WebAuthn credential (passkey) registered by user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasskeyRecord {
+    pub id: Option<Thing>,
+    pub user_id: String,
+    pub username: String,
+    /// Human readable passkey name
+    pub name: String,
+    /// Base64url encoded credential identifier
+    pub credential_id: String,
+    /// Serialized passkey (public key and signature counter)
+    pub passkey: String,
+    pub creation_datetime: Datetime,
+    pub last_used: Option<Datetime>,
+}
+
+impl PasskeyRecord {
+    /// Hides credential internals before sending record to client
+    pub fn redacted(mut self) -> Self {
+        self.passkey.clear();
+        self
+    }
+}
+
+/// JSON Payload to rename passkey
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenamePasskeyRecord {
+    pub name: String,
+}
+
+/// Started WebAuthn registration or authentication, consumed by its finish request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasskeyCeremonyRecord {
+    pub id: Option<Thing>,
+    /// Random identifier returned to client
+    pub ceremony_id: String,
+    /// `registration` or `authentication`
+    pub kind: String,
+    pub user_id: String,
+    pub username: String,
+    /// Serialized server side ceremony state (challenge)
+    pub state: String,
+    pub expires_at: Datetime,
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use surrealdb::{Datetime, sql::Thing};
 
+use super::tag::{TagRecord, TagsMode, tag_names};
+
 /// Record about Person's full information.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PersonRecord {
@@ -58,12 +60,408 @@ pub struct CreatePersonRecord {
     pub media: Vec<String>,
 }
 
-/// Search JSON payload. <br/>
+/// Record's content without identifier and author (used to restore revision)
+impl From<PersonRecord> for CreatePersonRecord {
+    fn from(record: PersonRecord) -> Self {
+        Self {
+            name: record.name,
+            surname: record.surname,
+            patronymic: record.patronymic,
+
+            birthday: record.birthday,
+            city: record.city,
+            intented_address: record.intented_address,
+
+            summary: record.summary,
+            past: record.past,
+            traits_good: record.traits_good,
+            traits_bad: record.traits_bad,
+
+            avatar: record.avatar,
+            media: record.media,
+        }
+    }
+}
+
+/// Default number of records on page
+pub const DEFAULT_PAGE_LIMIT: usize = 25;
+/// Maximal number of records on page
+pub const MAX_PAGE_LIMIT: usize = 100;
+
+/// Lightweight projection of Person's record for list views
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonSummaryRecord {
+    pub id: Option<Thing>,
+
+    pub name: String,
+    pub surname: String,
+    pub patronymic: String,
+
+    pub birthday: Datetime,
+    pub city: String,
+    pub avatar: Option<String>,
+
+    pub author: String,
+    /// Not part of [PersonRecord], so record signatures aren't affected
+    pub creation_datetime: Datetime,
+    /// Record has signature
+    pub signed: bool,
+    /// Not part of [PersonRecord], so record signatures aren't affected
+    pub tags: Vec<TagRecord>,
+}
+
+/// Person's record in trash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedPersonRecord {
+    pub id: Option<Thing>,
+
+    pub name: String,
+    pub surname: String,
+    pub patronymic: String,
+
+    pub author: String,
+    pub deleted_at: Datetime,
+    /// Username of user who deleted record
+    pub deleted_by: String,
+}
+
+/// Page of Person's records
+#[derive(Debug, Clone, Serialize)]
+pub struct PersonPage<T = PersonSummaryRecord> {
+    pub items: Vec<T>,
+    /// Number of all matching records
+    pub total: usize,
+    /// Page number (starting with 1)
+    pub page: usize,
+    pub limit: usize,
+}
+
+/// Page number and size from query parameters
+fn page_bounds(page: Option<usize>, limit: Option<usize>) -> (usize, usize) {
+    (
+        page.unwrap_or(1).max(1),
+        limit
+            .unwrap_or(DEFAULT_PAGE_LIMIT)
+            .clamp(1, MAX_PAGE_LIMIT),
+    )
+}
+
+/// SurrealQL `ORDER BY` fields, full name is used to break ties
+fn order_clause(sort: PersonSortField, order: SortOrder) -> String {
+    let order = order.keyword();
+
+    match sort {
+        PersonSortField::Surname => format!("surname {order}, name {order}, patronymic {order}"),
+        PersonSortField::Name => format!("name {order}, surname {order}, patronymic {order}"),
+        field => format!("{} {order}, surname, name, patronymic", field.field()),
+    }
+}
+
+/// Search query parameters. <br/>
 /// Possible options for query:
 /// `name surname patronymic`, `name patronymic`, `name surname`, `surname patronymic` and etc.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[allow(unused)]
 pub struct SearchPersonRecord {
-    /// Optional string that can contains `name`, `surname`, `patronymic`
+    /// String that can contains `name`, `surname`, `patronymic` in any order
+    #[serde(rename = "q")]
     pub search_query: String,
+    /// Comma-separated tag names
+    pub tags: Option<String>,
+    /// Match persons with any (default) or all of tags
+    #[serde(default)]
+    pub tags_mode: TagsMode,
+    /// Records are sorted by relevance if field isn't set
+    pub sort: Option<PersonSortField>,
+    #[serde(default)]
+    pub order: SortOrder,
+    /// Page number (default: 1)
+    pub page: Option<usize>,
+    /// Page size (default: 25, max: 100)
+    pub limit: Option<usize>,
+}
+
+impl SearchPersonRecord {
+    pub fn page_bounds(&self) -> (usize, usize) {
+        page_bounds(self.page, self.limit)
+    }
+
+    pub fn tag_names(&self) -> Vec<String> {
+        tag_names(self.tags.as_deref())
+    }
+
+    /// Exact matches go first, then near-misses (`relevance` must be selected)
+    pub fn order_clause(&self) -> String {
+        match self.sort {
+            Some(sort) => order_clause(sort, self.order),
+            None => format!("relevance DESC, {}", order_clause(PersonSortField::default(), self.order)),
+        }
+    }
+}
+
+/// List query parameters: filters, sorting and pagination
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FilterPersonRecord {
+    /// City (case insensitive)
+    pub city: Option<String>,
+    /// Author's username
+    pub author: Option<String>,
+    /// Born on this day or later (`YYYY-MM-DD`)
+    pub birthday_from: Option<String>,
+    /// Born on this day or earlier (`YYYY-MM-DD`)
+    pub birthday_to: Option<String>,
+    pub has_avatar: Option<bool>,
+    pub signed: Option<bool>,
+    /// Comma-separated tag names
+    pub tags: Option<String>,
+    /// Match persons with any (default) or all of tags
+    #[serde(default)]
+    pub tags_mode: TagsMode,
+    #[serde(default)]
+    pub sort: PersonSortField,
+    #[serde(default)]
+    pub order: SortOrder,
+    /// Page number (default: 1)
+    pub page: Option<usize>,
+    /// Page size (default: 25, max: 100)
+    pub limit: Option<usize>,
+}
+
+impl FilterPersonRecord {
+    pub fn page_bounds(&self) -> (usize, usize) {
+        page_bounds(self.page, self.limit)
+    }
+
+    pub fn tag_names(&self) -> Vec<String> {
+        tag_names(self.tags.as_deref())
+    }
+
+    pub fn order_clause(&self) -> String {
+        order_clause(self.sort, self.order)
+    }
+
+    /// Birthday range as `[from, to)` (end of range is the next day), `Err` if date is malformed
+    pub fn birthday_range(&self) -> Result<(Option<Datetime>, Option<Datetime>), chrono::ParseError> {
+        let parse = |date: &str| chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d");
+
+        let from = self
+            .birthday_from
+            .as_deref()
+            .map(parse)
+            .transpose()?
+            .map(|date| Datetime::from(date.and_time(chrono::NaiveTime::MIN).and_utc()));
+
+        let to = self
+            .birthday_to
+            .as_deref()
+            .map(parse)
+            .transpose()?
+            .map(|date| {
+                Datetime::from(
+                    (date + chrono::Days::new(1))
+                        .and_time(chrono::NaiveTime::MIN)
+                        .and_utc(),
+                )
+            });
+
+        Ok((from, to))
+    }
+}
+
+/// Full-text search query parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextSearchPersonRecord {
+    /// Words to find in summary, past or traits (word forms are matched too)
+    #[serde(rename = "q")]
+    pub search_query: String,
+    /// Comma-separated tag names
+    pub tags: Option<String>,
+    /// Match persons with any (default) or all of tags
+    #[serde(default)]
+    pub tags_mode: TagsMode,
+    /// Page number (default: 1)
+    pub page: Option<usize>,
+    /// Page size (default: 25, max: 100)
+    pub limit: Option<usize>,
+}
+
+impl TextSearchPersonRecord {
+    pub fn page_bounds(&self) -> (usize, usize) {
+        page_bounds(self.page, self.limit)
+    }
+
+    pub fn tag_names(&self) -> Vec<String> {
+        tag_names(self.tags.as_deref())
+    }
+}
+
+/// Markers which `search::highlight` puts around matched words,
+/// control characters don't occur in records text
+pub const HIGHLIGHT_START: &str = "\u{2}";
+pub const HIGHLIGHT_END: &str = "\u{3}";
+
+/// Number of characters shown around first match in snippet
+const SNIPPET_CONTEXT: usize = 60;
+
+/// Full-text search hit
+#[derive(Debug, Clone, Serialize)]
+pub struct PersonSearchHit {
+    #[serde(flatten)]
+    pub person: PersonSummaryRecord,
+    /// Relevance (BM25), higher is better
+    pub score: f64,
+    /// Snippets of matched fields
+    pub highlights: Vec<PersonHighlight>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PersonHighlight {
+    /// `summary`, `past`, `traits_good` or `traits_bad`
+    pub field: &'static str,
+    /// Fragment of field's text: HTML escaped, matched words are wrapped in `<mark>`
+    pub snippet: String,
+}
+
+/// Row of full-text search query
+#[derive(Debug, Clone, Deserialize)]
+pub struct PersonSearchRow {
+    pub person: PersonSummaryRecord,
+    pub score: f64,
+    pub highlights: NarrativeHighlights,
+}
+
+/// Narrative fields with highlighted matches (field is returned as is if it didn't match)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct NarrativeHighlights {
+    pub summary: Option<String>,
+    pub past: Option<String>,
+    pub traits_good: Option<String>,
+    pub traits_bad: Option<String>,
+}
+
+impl PersonSearchRow {
+    pub fn into_hit(self) -> PersonSearchHit {
+        let fields = [
+            ("summary", self.highlights.summary),
+            ("past", self.highlights.past),
+            ("traits_good", self.highlights.traits_good),
+            ("traits_bad", self.highlights.traits_bad),
+        ];
+
+        let highlights = fields
+            .into_iter()
+            .filter_map(|(field, text)| {
+                Some(PersonHighlight {
+                    field,
+                    snippet: highlight_snippet(&text?)?,
+                })
+            })
+            .collect();
+
+        PersonSearchHit {
+            person: self.person,
+            score: self.score,
+            highlights,
+        }
+    }
+}
+
+/// Cuts fragment around first match out of highlighted text (`None` if nothing matched)
+fn highlight_snippet(highlighted: &str) -> Option<String> {
+    let highlighted = highlighted.split_whitespace().collect::<Vec<_>>().join(" ");
+    let (before, rest) = highlighted.split_at(highlighted.find(HIGHLIGHT_START)?);
+
+    let mut snippet = String::new();
+    let before_length = before.chars().count();
+
+    if before_length > SNIPPET_CONTEXT {
+        snippet.push('…');
+    }
+
+    push_escaped(
+        &mut snippet,
+        &before
+            .chars()
+            .skip(before_length.saturating_sub(SNIPPET_CONTEXT))
+            .collect::<String>(),
+    );
+
+    let mut budget = SNIPPET_CONTEXT * 2;
+
+    for part in rest.split(HIGHLIGHT_START).skip(1) {
+        let (matched, tail) = part.split_once(HIGHLIGHT_END).unwrap_or((part, ""));
+
+        snippet.push_str("<mark>");
+        push_escaped(&mut snippet, matched);
+        snippet.push_str("</mark>");
+
+        let tail_length = tail.chars().count();
+
+        if tail_length > budget {
+            push_escaped(&mut snippet, &tail.chars().take(budget).collect::<String>());
+            snippet.push('…');
+            break;
+        }
+
+        push_escaped(&mut snippet, tail);
+        budget -= tail_length;
+    }
+
+    Some(snippet)
+}
+
+fn push_escaped(target: &mut String, text: &str) {
+    for character in text.chars() {
+        match character {
+            '&' => target.push_str("&amp;"),
+            '<' => target.push_str("&lt;"),
+            '>' => target.push_str("&gt;"),
+            '"' => target.push_str("&quot;"),
+            '\'' => target.push_str("&#39;"),
+            character => target.push(character),
+        }
+    }
+}
+
+/// Field to sort records by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PersonSortField {
+    #[default]
+    Surname,
+    Name,
+    Patronymic,
+    Birthday,
+    City,
+    CreationDatetime,
+}
+
+impl PersonSortField {
+    pub fn field(&self) -> &'static str {
+        match self {
+            Self::Surname => "surname",
+            Self::Name => "name",
+            Self::Patronymic => "patronymic",
+            Self::Birthday => "birthday",
+            Self::City => "city",
+            Self::CreationDatetime => "creation_datetime",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            Self::Asc => "ASC",
+            Self::Desc => "DESC",
+        }
+    }
 }
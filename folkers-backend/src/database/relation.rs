@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+use surrealdb::{Datetime, sql::Thing};
+
+/// Default number of hops in person's network
+pub const DEFAULT_NETWORK_DEPTH: u32 = 1;
+/// Maximal number of hops in person's network
+pub const MAX_NETWORK_DEPTH: u32 = 3;
+/// Maximal number of relations in path between two persons
+pub const MAX_PATH_LENGTH: u32 = 6;
+
+/// Type of relationship between two persons
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelationKind {
+    Relative,
+    Spouse,
+    Colleague,
+    Friend,
+    /// Described by relation's label
+    Custom,
+}
+
+impl RelationKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Relative => "relative",
+            Self::Spouse => "spouse",
+            Self::Colleague => "colleague",
+            Self::Friend => "friend",
+            Self::Custom => "custom",
+        }
+    }
+}
+
+/// JSON Payload to relate person with another one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateRelationRecord {
+    /// Identifier of related person
+    pub person_id: String,
+    pub kind: RelationKind,
+    /// Name of `custom` relation (ignored for other kinds)
+    pub label: Option<String>,
+
+    /// Start of relationship (if known)
+    pub since: Option<Datetime>,
+    /// End of relationship (if known)
+    pub until: Option<Datetime>,
+    pub note: Option<String>,
+}
+
+impl CreateRelationRecord {
+    /// Label without surrounding whitespaces, `None` for non-custom relations
+    pub fn label(&self) -> Option<String> {
+        match self.kind {
+            RelationKind::Custom => self
+                .label
+                .as_deref()
+                .map(str::trim)
+                .filter(|label| !label.is_empty())
+                .map(str::to_owned),
+            _ => None,
+        }
+    }
+
+    /// Custom relation must be labeled, relationship can't end before it started
+    pub fn is_valid(&self) -> bool {
+        if self.kind == RelationKind::Custom && self.label().is_none() {
+            return false;
+        }
+
+        match (&self.since, &self.until) {
+            (Some(since), Some(until)) => since <= until,
+            _ => true,
+        }
+    }
+}
+
+/// Graph edge between two persons (`in` -> `out`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonRelationRecord {
+    pub id: Option<Thing>,
+    #[serde(rename = "in")]
+    pub from: Thing,
+    #[serde(rename = "out")]
+    pub to: Thing,
+
+    pub kind: RelationKind,
+    pub label: Option<String>,
+    pub since: Option<Datetime>,
+    pub until: Option<Datetime>,
+    pub note: Option<String>,
+
+    /// Username of user who related persons
+    pub author: String,
+    pub creation_datetime: Datetime,
+}
+
+/// Related person's identity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelatedPersonRecord {
+    pub id: Option<Thing>,
+
+    pub name: String,
+    pub surname: String,
+    pub patronymic: String,
+    pub avatar: Option<String>,
+}
+
+/// Direction of relation from person's point of view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelationDirection {
+    /// Relation was added from person's record
+    Outgoing,
+    /// Relation was added from related person's record
+    Incoming,
+}
+
+/// Person's relation with another person
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonConnectionRecord {
+    /// Relation's SurrealDB Identifier
+    pub id: Thing,
+    pub direction: RelationDirection,
+    pub person: RelatedPersonRecord,
+
+    pub kind: RelationKind,
+    pub label: Option<String>,
+    pub since: Option<Datetime>,
+    pub until: Option<Datetime>,
+    pub note: Option<String>,
+
+    pub author: String,
+    pub creation_datetime: Datetime,
+}
+
+/// Relation as graph edge, persons are referenced with record identifiers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelationEdge {
+    pub id: Thing,
+    pub from: String,
+    pub to: String,
+
+    pub kind: RelationKind,
+    pub label: Option<String>,
+}
+
+impl RelationEdge {
+    /// Person on the other end of edge, `None` if edge doesn't touch `person_id`
+    pub fn other(&self, person_id: &str) -> Option<&str> {
+        if self.from == person_id {
+            Some(&self.to)
+        } else if self.to == person_id {
+            Some(&self.from)
+        } else {
+            None
+        }
+    }
+}
+
+/// Query parameters of person's network
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetworkQuery {
+    /// Number of hops (default: 1, max: 3)
+    pub depth: Option<u32>,
+}
+
+impl NetworkQuery {
+    pub fn depth(&self) -> u32 {
+        self.depth
+            .unwrap_or(DEFAULT_NETWORK_DEPTH)
+            .clamp(1, MAX_NETWORK_DEPTH)
+    }
+}
+
+/// Person reachable in network
+#[derive(Debug, Clone, Serialize)]
+pub struct NetworkNode {
+    #[serde(flatten)]
+    pub person: RelatedPersonRecord,
+    /// Number of hops from network's center
+    pub distance: u32,
+}
+
+/// Persons within few hops and relations between them
+#[derive(Debug, Clone, Serialize)]
+pub struct PersonNetwork {
+    pub nodes: Vec<NetworkNode>,
+    pub edges: Vec<RelationEdge>,
+}
+
+/// Shortest chain of relations between two persons
+#[derive(Debug, Clone, Serialize)]
+pub struct PersonPath {
+    /// Persons from start to end of path
+    pub persons: Vec<RelatedPersonRecord>,
+    /// `relations[i]` connects `persons[i]` and `persons[i + 1]`
+    pub relations: Vec<RelationEdge>,
+}
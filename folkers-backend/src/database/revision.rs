@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::{Datetime, Thing};
+
+use super::person::PersonRecord;
+
+/// Change which produced revision
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RevisionAction {
+    Create,
+    Update,
+    Delete,
+    Restore,
+    Revert,
+    /// Person's tags were replaced
+    Tag,
+}
+
+impl RevisionAction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Update => "update",
+            Self::Delete => "delete",
+            Self::Restore => "restore",
+            Self::Revert => "revert",
+            Self::Tag => "tag",
+        }
+    }
+}
+
+/// Immutable snapshot of Person's record: state after create, update, tagging, restore from
+/// trash or revert, or last state before moving to trash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonRevisionRecord {
+    pub id: Option<Thing>,
+    pub person_id: String,
+    /// Sequential number of revision (starting with 1)
+    pub revision: u64,
+    pub action: RevisionAction,
+    /// Username of user who made the change
+    pub author: String,
+    pub creation_datetime: Datetime,
+    /// Restored revision number (for `revert` action)
+    pub reverted_from: Option<u64>,
+    /// Full record, omitted in revisions list
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<PersonRecord>,
+    /// Names of person's tags (tags aren't part of record, revert doesn't change them)
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Query parameters of revisions diff
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevisionDiffQuery {
+    /// Older revision number
+    pub from: u64,
+    /// Newer revision number
+    pub to: u64,
+}
+
+/// Field which differs between two revisions
+#[derive(Debug, Clone, Serialize)]
+pub struct PersonFieldChange {
+    pub field: String,
+    pub from: serde_json::Value,
+    pub to: serde_json::Value,
+}
+
+/// Field-level diff between two snapshots (record identifier is not compared)
+pub fn diff_persons(
+    from: &PersonRecord,
+    to: &PersonRecord,
+) -> Result<Vec<PersonFieldChange>, serde_json::Error> {
+    let serde_json::Value::Object(from) = serde_json::to_value(from)? else {
+        return Ok(Vec::new());
+    };
+
+    let serde_json::Value::Object(mut to) = serde_json::to_value(to)? else {
+        return Ok(Vec::new());
+    };
+
+    Ok(from
+        .into_iter()
+        .filter(|(field, _)| field != "id")
+        .filter_map(|(field, old_value)| {
+            let new_value = to.remove(&field).unwrap_or_default();
+
+            (old_value != new_value).then_some(PersonFieldChange {
+                field,
+                from: old_value,
+                to: new_value,
+            })
+        })
+        .collect())
+}
+
+/// Change of tags between two revisions, `None` if tags are the same
+pub fn diff_tags(from: &[String], to: &[String]) -> Option<PersonFieldChange> {
+    (from != to).then(|| PersonFieldChange {
+        field: String::from("tags"),
+        from: serde_json::json!(from),
+        to: serde_json::json!(to),
+    })
+}
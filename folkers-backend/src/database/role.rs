@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+use surrealdb::{Datetime, sql::Thing};
+
+use crate::auth::permission::Permission;
+
+/// Named set of permissions assigned to users
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleRecord {
+    pub id: Option<Thing>,
+    pub name: String,
+    pub permissions: Vec<Permission>,
+    /// Built-in role (watcher, editor, admin), can't be deleted
+    #[serde(default)]
+    pub builtin: bool,
+    pub creation_datetime: Datetime,
+}
+
+/// JSON Payload to create new role
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateRoleRecord {
+    pub name: String,
+    pub permissions: Vec<Permission>,
+}
+
+/// JSON Payload to replace role permissions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatchRoleRecord {
+    pub permissions: Vec<Permission>,
+}
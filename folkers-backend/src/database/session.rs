@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::{Datetime, Thing};
+
+/// Login session, created on every successful login. <br/>
+/// Session identifier is also used as refresh tokens chain (`family`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub id: Option<Thing>,
+    pub user_id: String,
+    pub username: String,
+    /// Client `User-Agent` header
+    pub user_agent: String,
+    /// Client IP address
+    pub ip: String,
+    pub revoked: bool,
+    pub creation_datetime: Datetime,
+    /// Last authorized request (updated at most once a minute)
+    pub last_seen: Datetime,
+    /// Session ends with its refresh token, prolonged on every refresh
+    pub expires_at: Datetime,
+    /// Username of admin who impersonates user in this session
+    #[serde(default)]
+    pub impersonated_by: Option<String>,
+}
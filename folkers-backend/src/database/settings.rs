@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+/// Instance-wide security settings managed by admins
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecuritySettings {
+    /// Roles which must complete TOTP two-factor authentication on login
+    #[serde(default)]
+    pub require_2fa_roles: Vec<String>,
+}
+
+impl SecuritySettings {
+    /// Role names are case-insensitive (older accounts may keep capitalized preset names)
+    pub fn requires_2fa(&self, role: &str) -> bool {
+        self.require_2fa_roles
+            .iter()
+            .any(|required| required.eq_ignore_ascii_case(role))
+    }
+}
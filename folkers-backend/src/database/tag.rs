@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+
+/// Maximal length of tag's name
+const MAX_TAG_NAME_LENGTH: usize = 64;
+
+/// Tag from managed vocabulary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagRecord {
+    pub id: Option<Thing>,
+    pub name: String,
+    /// Hex color (`#rrggbb`)
+    pub color: String,
+}
+
+/// Tag with number of tagged persons (records in trash aren't counted)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagUsageRecord {
+    pub id: Option<Thing>,
+    pub name: String,
+    pub color: String,
+    pub count: usize,
+}
+
+/// JSON Payload to create, rename or recolor tag
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateTagRecord {
+    pub name: String,
+    pub color: String,
+}
+
+impl CreateTagRecord {
+    /// Name without surrounding whitespaces
+    pub fn name(&self) -> String {
+        self.name.trim().to_owned()
+    }
+
+    /// Name is not empty and has no commas (they separate tags in filters), color is `#rrggbb`
+    pub fn is_valid(&self) -> bool {
+        let name = self.name();
+
+        let valid_name =
+            !name.is_empty() && name.chars().count() <= MAX_TAG_NAME_LENGTH && !name.contains(',');
+
+        let valid_color = self.color.len() == 7
+            && self.color.starts_with('#')
+            && self.color[1..].chars().all(|c| c.is_ascii_hexdigit());
+
+        valid_name && valid_color
+    }
+}
+
+/// JSON Payload to merge tag into another one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeTagRecord {
+    /// Identifier of tag which remains after merge
+    pub into: String,
+}
+
+/// JSON Payload to set person's tags
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonTagsRecord {
+    /// Identifiers of tags (replace current ones)
+    pub tags: Vec<String>,
+}
+
+/// Result of replacing person's tags
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum PersonTagsOutcome {
+    /// Tags are replaced (change is stored as person's revision)
+    Tagged { tags: Vec<TagRecord> },
+    /// Some of tags don't exist, nothing is changed
+    UnknownTags,
+    /// Person doesn't exist or is in trash
+    PersonNotFound,
+}
+
+/// How persons are matched by tags filter
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagsMode {
+    /// Person has at least one of tags
+    #[default]
+    Any,
+    /// Person has every tag
+    All,
+}
+
+impl TagsMode {
+    /// SurrealQL condition over person's tag names (`$tags` must be bound)
+    pub fn condition(&self) -> &'static str {
+        match self {
+            Self::Any => "tags.name CONTAINSANY $tags",
+            Self::All => "tags.name CONTAINSALL $tags",
+        }
+    }
+}
+
+/// Tag names from comma-separated filter value
+pub fn tag_names(tags: Option<&str>) -> Vec<String> {
+    tags.unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
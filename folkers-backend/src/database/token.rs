@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use surrealdb::{Datetime, sql::Thing};
+
+/// Rotating refresh token record.
+/// Tokens issued from the same login share one `family`, so the whole chain
+/// can be revoked at once (logout or reuse detection).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshTokenRecord {
+    pub id: Option<Thing>,
+    /// SHA256 hash of the opaque token (raw token is never stored)
+    pub token_hash: String,
+    /// Identifier of the refresh chain
+    pub family: String,
+    pub user_id: String,
+    pub username: String,
+    pub revoked: bool,
+    pub expires_at: Datetime,
+    pub creation_datetime: Datetime,
+}
+
+/// Revoked access token (`jti` claim), kept until the token expires
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevokedTokenRecord {
+    pub id: Option<Thing>,
+    pub jti: String,
+    pub expires_at: Datetime,
+}
+
+/// Issued two-factor challenge (`jti` claim), consumed by its answer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwoFactorChallengeRecord {
+    pub id: Option<Thing>,
+    pub jti: String,
+    pub user_id: String,
+    pub expires_at: surrealdb::sql::Datetime,
+}
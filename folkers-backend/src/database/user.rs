@@ -9,7 +9,58 @@ pub struct UserRecord {
     pub role: String,
     pub created_by: String,
     pub creation_datetime: Datetime,
-    pub public_key: Option<String>
+    pub public_key: Option<String>,
+
+    /// Base32 encoded TOTP secret (set on enrollment)
+    #[serde(default)]
+    pub totp_secret: Option<String>,
+    /// TOTP secret was confirmed and is required on login
+    #[serde(default)]
+    pub totp_enabled: bool,
+    /// SHA256 hashes of unused one-time recovery codes
+    #[serde(default)]
+    pub recovery_codes: Vec<String>,
+    /// Time step of last accepted TOTP code, earlier codes are rejected (no replay)
+    #[serde(default)]
+    pub totp_last_step: Option<u64>,
+
+    /// Only password change is allowed until it is done (set for admin-created passwords)
+    #[serde(default)]
+    pub must_change_password: bool,
+    /// Last password change (used for maximum password age)
+    #[serde(default)]
+    pub password_changed_at: Option<surrealdb::sql::Datetime>,
+    /// Account of external identity provider (`oidc:<sub>`), such users have no local password
+    #[serde(default)]
+    pub external_id: Option<String>,
+    /// Static admin account, can't be edited or deleted and manages every user
+    #[serde(default)]
+    pub protected: bool,
+
+    /// Suspended account can't log in, its records and signatures are kept
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default)]
+    pub disabled_reason: Option<String>,
+    /// Account is suspended automatically after this moment (never if empty)
+    #[serde(default)]
+    pub expires_at: Option<surrealdb::sql::Datetime>,
+}
+
+impl UserRecord {
+    /// Hides two-factor secrets before sending record to client
+    pub fn redacted(mut self) -> Self {
+        self.totp_secret = None;
+        self.recovery_codes.clear();
+        self
+    }
+}
+
+/// JSON Payload to change own password
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangePasswordRecord {
+    pub current_password: String,
+    pub new_password: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,4 +69,27 @@ pub struct CreateUserRecord {
     pub password: String,
     pub role: String,
     pub created_by: String,
+    /// Account expiration (kept unchanged on patch if empty)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<surrealdb::sql::Datetime>,
+}
+
+/// JSON Payload to suspend user
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SuspendUserRecord {
+    pub reason: Option<String>,
+}
+
+/// Query parameters of login unlock
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UnlockUserQuery {
+    /// Client IP address to unlock too (users behind the same address share its lock)
+    pub ip: Option<String>,
+}
+
+/// JSON Payload to reactivate suspended or expired user
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReactivateUserRecord {
+    /// New account expiration (never if empty)
+    pub expires_at: Option<surrealdb::sql::Datetime>,
 }
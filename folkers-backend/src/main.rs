@@ -8,20 +8,65 @@
 //! - **Web Framework:** Axum
 //! - **Password Hasher:** Argon2
 //! - **Media Hasher:** Sha256
-//! - **Authorization:** JSON Web Tokens (Bearer)
+//! - **Authorization:** JSON Web Tokens (Bearer, EdDSA)
 //!
 //! ## Environment Variables
 //! List of required environment variables (duplicated in README.md):
 //! ```env
-//! FOLKERS_JWT_SECRET=secret string for jwt tokens
-//! FOLKERS_BASE64_SALT=base64 encoded salt for hash
+//! FOLKERS_JWT_KEYS_DIR=path to directory with Ed25519 PEM keys for jwt tokens (optional, default: ./jwt_keys, key is generated if empty)
+//! FOLKERS_JWT_ACTIVE_KEY_ID=id (file name) of the key used for signing (optional, default: newest key)
+//! FOLKERS_JWT_ISSUER=jwt `iss` claim (optional, default: folkers)
+//! FOLKERS_JWT_AUDIENCE=jwt `aud` claim (optional, default: folkers-api)
+//! FOLKERS_JWT_EXPIRATION_MINUTES=access token lifetime (optional, default: 15)
+//! FOLKERS_JWT_REFRESH_EXPIRATION_DAYS=refresh token lifetime (optional, default: 14)
+//! FOLKERS_BASE64_SALT=legacy base64 encoded global salt (optional, hashes made with it are upgraded on login)
+//! FOLKERS_ARGON2_MEMORY_COST=argon2 memory cost in KiB (optional, default: 19456)
+//! FOLKERS_ARGON2_TIME_COST=argon2 iterations (optional, default: 2)
+//! FOLKERS_ARGON2_PARALLELISM=argon2 lanes (optional, default: 1)
+//! FOLKERS_PASSWORD_MIN_LENGTH=minimal password length (optional, default: 8)
+//! FOLKERS_PASSWORD_MAX_AGE_DAYS=password must be changed after this number of days (optional, disabled by default)
+//! FOLKERS_PASSWORD_WORDLIST=path to file with breached passwords, one per line (optional)
+//! FOLKERS_AUTH_BACKENDS=login backends in order: `local`, `ldap` (optional, default: local)
+//! FOLKERS_LDAP_URL=directory URL, `ldap://` or `ldaps://` (required for ldap backend)
+//! FOLKERS_LDAP_STARTTLS=upgrade `ldap://` connection with StartTLS (optional, default: false)
+//! FOLKERS_LDAP_BIND_DN=service account used for searches (optional, anonymous if empty)
+//! FOLKERS_LDAP_BIND_PASSWORD=service account password (optional)
+//! FOLKERS_LDAP_BASE_DN=users search base (required for ldap backend)
+//! FOLKERS_LDAP_USERNAME_ATTRIBUTE=attribute with username (optional, default: uid, `sAMAccountName` for Active Directory)
+//! FOLKERS_LDAP_USER_FILTER=additional users filter, e.g. `(objectClass=person)` (optional)
+//! FOLKERS_LDAP_GROUP_BASE_DN=groups search base (optional, default: FOLKERS_LDAP_BASE_DN)
+//! FOLKERS_LDAP_GROUP_FILTER=groups filter with `{dn}` and `{username}` placeholders (optional, default: member, uniqueMember or memberUid)
+//! FOLKERS_LDAP_ROLE_MAPPING=group (`cn`) to role pairs, first match wins: `group=role,group=role` (optional)
+//! FOLKERS_LDAP_DEFAULT_ROLE=role of users without mapped groups (optional, login is denied if empty)
+//! FOLKERS_LDAP_AUTO_PROVISION=create users on first login (optional, default: true)
+//! FOLKERS_LDAP_LINK_EXISTING=link local user (except static admin) with the same username on first login (optional, default: false)
+//! FOLKERS_OIDC_ISSUER=OpenID Connect identity provider issuer URL, enables single sign-on (optional)
+//! FOLKERS_OIDC_CLIENT_ID=client id registered at identity provider (required for single sign-on)
+//! FOLKERS_OIDC_CLIENT_SECRET=client secret (optional, public clients use PKCE only)
+//! FOLKERS_OIDC_REDIRECT_URI=frontend callback page (required for single sign-on)
+//! FOLKERS_OIDC_SCOPES=requested scopes (optional, default: openid profile email)
+//! FOLKERS_OIDC_AUTHORIZATION_ENDPOINT=authorization endpoint for browsers if it differs from discovered one (optional)
+//! FOLKERS_OIDC_USERNAME_CLAIM=claim used as username (optional, default: preferred_username)
+//! FOLKERS_OIDC_GROUPS_CLAIM=claim with user groups (optional, default: groups)
+//! FOLKERS_OIDC_ROLE_MAPPING=group to role pairs, first match wins: `group=role,group=role` (optional)
+//! FOLKERS_OIDC_DEFAULT_ROLE=role of users without mapped groups (optional, login is denied if empty)
+//! FOLKERS_OIDC_AUTO_PROVISION=create users on first login (optional, default: true)
+//! FOLKERS_OIDC_LINK_EXISTING=link local user (except static admin) with the same username on first login (optional, default: false)
+//! FOLKERS_WEBAUTHN_RP_ID=relying party id, domain of frontend, enables passkey login (optional)
+//! FOLKERS_WEBAUTHN_RP_ORIGIN=frontend origin, e.g. `https://folkers.example.com` (required for passkey login)
+//! FOLKERS_WEBAUTHN_RP_NAME=name shown by authenticators (optional, default: Folkers)
+//! FOLKERS_SESSION_COOKIES=keep tokens in `HttpOnly` cookies instead of response body (optional, default: false)
+//! FOLKERS_COOKIE_SECURE=`Secure` cookie attribute, disable only for plain HTTP development (optional, default: true)
+//! FOLKERS_COOKIE_SAME_SITE=`SameSite` cookie attribute: `Strict` or `Lax` (optional, default: Strict)
 //! FOLKERS_UPLOAD_DIR=path to directory with uploaded media
+//! FOLKERS_TRASH_RETENTION_DAYS=deleted persons records are purged after this number of days, `0` keeps them forever (optional, default: 30)
+//! FOLKERS_TRUST_PROXY_HEADERS=take client IP from X-Real-IP or last X-Forwarded-For entry (optional, default: false)
 //!
 //! FOLKERS_DB_USERNAME=database username
 //! FOLKERS_DB_PASSWORD=database password
 //! FOLKERS_DB_NAMESPACE=database namespace (surrealdb)
 //! FOLKERS_DB_DATABASE=database base name (surrealdb)
-//! FOLKERS_DB_ENDPOINT=database endpoint
+//! FOLKERS_DB_ENDPOINT=database endpoint (`host:port` for WebSocket, or URL with scheme)
 //!
 //! FOLKERS_STATIC_ADMIN_USERNAME=admin that will be created every start
 //! FOLKERS_STATIC_ADMIN_PASSWORD=static admin password
@@ -30,7 +75,32 @@
 //! ## API
 //! **❗ Each endpoint, which requires authorization will return `401 UNAUTHORIZED` if: <br/>**
 //! **- JWT Token is not provided / wrong <br/>**
-//! **- JWT Token is expired**
+//! **- JWT Token is expired <br/>**
+//! **- JWT Token is revoked (logout) <br/>**
+//! **- Session is terminated or expired <br/>**
+//! **- User was deleted (role changes are applied immediately) <br/>**
+//! **- Personal access token is unknown / expired / revoked**
+//!
+//! **❗ If password must be changed (set by admin or expired) every endpoint except `/me`, `/me/password`
+//! and `/logout` returns `403 FORBIDDEN` with `X-Password-Change-Required: true` header.**
+//!
+//! JWT signing keys are stored as `<kid>.pem` files in `FOLKERS_JWT_KEYS_DIR`. To rotate key put a new
+//! one there (or generate it with `openssl genpkey -algorithm ed25519`) and restart, tokens signed with
+//! previous keys stay valid while their files exist. Retired key can be kept as public `<kid>.pub.pem`.
+//!
+//! **Authorization** header accepts both JWT and personal access token (`fkr_...`) as `Bearer`.
+//! Personal access token acts with its scope role, limited by owner's current permissions.
+//!
+//! **Cookie session mode** (`FOLKERS_SESSION_COOKIES=true`) is meant for browser frontend served from the
+//! same origin as API. Every [AuthResponse](auth::AuthResponse) sets `folkers_session` (access token) and
+//! `folkers_refresh` (refresh token) `HttpOnly` cookies instead of returning tokens in body, and readable
+//! `folkers_csrf` cookie. Access token cookie is accepted when `Authorization` header is missing, every
+//! `POST`/`PATCH`/`DELETE` request authorized with it (and cookie `/refresh`) must repeat `folkers_csrf`
+//! value in `X-CSRF-Token` header, otherwise `403 FORBIDDEN` is returned.
+//!
+//! Endpoints are guarded by [permissions](auth::permission::Permission) granted through roles.
+//! Roles are stored in database, built-in `watcher`, `editor` and `admin` are created on first start
+//! (see [Preset](auth::permission::Preset)) and can be adjusted, custom ones are managed with `/roles`.
 //!
 //! ----
 //! - ### GET `/` <br/>
@@ -39,17 +109,234 @@
 //! - ### GET `/health` <br/>
 //! > **Returns:** `200 OK`
 //! ----
+//! - ### GET `/.well-known/jwks.json` <br/>
+//! > **Returns:** [JwkSet](jsonwebtoken::jwk::JwkSet) with public keys (EdDSA) to verify access tokens
+//! ----
 //! - ### POST `/login` <br/>
+//! > Credentials are checked by backends from `FOLKERS_AUTH_BACKENDS` in order <br/>
 //! > **Payload:** [LoginRequest](auth::LoginRequest) <br/>
 //! > **Errors:** <br/>
-//! > - `401 UNAUTHORIZED` User doesn't exists, Verification failed <br/>
+//! > - `401 UNAUTHORIZED` User doesn't exists or verification failed (same response for both) <br/>
+//! > - `403 FORBIDDEN` Account is suspended or expired, Directory user has no mapped role, Auto provisioning is disabled <br/>
+//! > - `409 CONFLICT` Username is taken by local or protected user (see `FOLKERS_LDAP_LINK_EXISTING`) <br/>
+//! > - `429 TOO MANY REQUESTS` Too many failed attempts for username or IP (see `Retry-After` header) <br/>
 //! > - `500 INTERNAL SERVER ERROR` JWT generation error <br/>
 //! >
-//! > **Returns:** [AuthResponse](auth::AuthResponse) (JWT Token Structure)
+//! > **Returns:** [AuthResponse](auth::AuthResponse) (JWT Token Structure, see `password_change_required`) <br/>
+//! > **Returns:** `202 ACCEPTED` [TwoFactorChallenge](auth::TwoFactorChallenge) if second factor is required
+//! ----
+//! - ### POST `/login/2fa` <br/>
+//! > Challenge is answered once (wrong code can be retyped), every TOTP code is accepted once <br/>
+//! > **Payload:** [TwoFactorRequest](auth::TwoFactorRequest) <br/>
+//! > **Errors:** <br/>
+//! > - `401 UNAUTHORIZED` Challenge token is wrong/expired/already answered, Challenge doesn't match
+//! >   two-factor state of account, Code verification failed or code was already used <br/>
+//! > - `403 FORBIDDEN` Account is suspended or expired <br/>
+//! > - `429 TOO MANY REQUESTS` Too many failed attempts for username or IP (see `Retry-After` header) <br/>
+//! > - `500 INTERNAL SERVER ERROR` JWT generation error, Database error <br/>
+//! >
+//! > **Returns:** [AuthResponse](auth::AuthResponse) (for `totp_enrollment` challenge also enables 2FA)
+//! ----
+//! - ### POST `/login/2fa/enroll` <br/>
+//! > **Payload:** [ChallengeRequest](auth::ChallengeRequest) (only `totp_enrollment` challenge) <br/>
+//! > **Errors:** <br/>
+//! > - `400 BAD REQUEST` Challenge doesn't require enrollment <br/>
+//! > - `401 UNAUTHORIZED` Challenge token is wrong/expired <br/>
+//! > - `409 CONFLICT` Two-factor authentication is already enabled <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** [TotpEnrollment](auth::TotpEnrollment)
+//! ----
+//! - ### POST `/refresh` <br/>
+//! > **Payload:** [RefreshRequest](auth::RefreshRequest) (`refresh_token` can be empty in cookie session mode) <br/>
+//! > **Errors:** <br/>
+//! > - `401 UNAUTHORIZED` Refresh token is wrong, expired or already used (whole chain gets revoked), Session is terminated or expired, Account is suspended or expired <br/>
+//! > - `403 FORBIDDEN` Refresh token cookie is sent without valid CSRF token <br/>
+//! > - `500 INTERNAL SERVER ERROR` JWT generation error, Database error <br/>
+//! >
+//! > **Returns:** [AuthResponse](auth::AuthResponse) (new access token and rotated refresh token)
+//! ----
+//! - ### GET `/oidc/authorize` <br/>
+//! > **Errors:** <br/>
+//! > - `404 NOT FOUND` Single sign-on is disabled <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! > - `502 BAD GATEWAY` Identity provider is unreachable <br/>
+//! >
+//! > **Returns:** [OidcAuthorization](auth::oidc::OidcAuthorization) (browser must be redirected to `authorization_url`)
+//! ----
+//! - ### POST `/oidc/callback` <br/>
+//! > **Payload:** [OidcCallbackRequest](auth::oidc::OidcCallbackRequest) (query parameters of redirect URI) <br/>
+//! > **Errors:** <br/>
+//! > - `401 UNAUTHORIZED` Unknown/expired state, Code exchange or ID token verification failed <br/>
+//! > - `403 FORBIDDEN` No role is mapped for user's groups, Auto-provisioning is disabled, Account is suspended or expired <br/>
+//! > - `404 NOT FOUND` Single sign-on is disabled <br/>
+//! > - `409 CONFLICT` Username is taken by local or protected user (see `FOLKERS_OIDC_LINK_EXISTING`) <br/>
+//! > - `500 INTERNAL SERVER ERROR` JWT generation error, Database error <br/>
+//! > - `502 BAD GATEWAY` Identity provider is unreachable <br/>
+//! >
+//! > **Returns:** [AuthResponse](auth::AuthResponse), role is synchronized with identity provider groups on every login
+//! ----
+//! - ### POST `/login/passkey` <br/>
+//! > **Payload:** [PasskeyLoginRequest](auth::webauthn::PasskeyLoginRequest) <br/>
+//! > **Errors:** <br/>
+//! > - `401 UNAUTHORIZED` Unknown user or user has no passkeys <br/>
+//! > - `404 NOT FOUND` Passkey login is disabled <br/>
+//! > - `429 TOO MANY REQUESTS` Too many failed attempts (`Retry-After` header) <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** [PasskeyLoginChallenge](auth::webauthn::PasskeyLoginChallenge) (`options` for `navigator.credentials.get()`)
+//! ----
+//! - ### POST `/login/passkey/finish` <br/>
+//! > **Payload:** [PasskeyLoginFinishRequest](auth::webauthn::PasskeyLoginFinishRequest) <br/>
+//! > **Errors:** <br/>
+//! > - `401 UNAUTHORIZED` Unknown/expired ceremony, Passkey verification failed <br/>
+//! > - `403 FORBIDDEN` Account is suspended or expired <br/>
+//! > - `404 NOT FOUND` Passkey login is disabled <br/>
+//! > - `429 TOO MANY REQUESTS` Too many failed attempts (`Retry-After` header) <br/>
+//! > - `500 INTERNAL SERVER ERROR` JWT generation error, Database error <br/>
+//! >
+//! > **Returns:** [AuthResponse](auth::AuthResponse), passkey replaces both password and second factor
+//! ----
+//! - ### POST `/logout` <br/>
+//! > **Authorization:** Required <br/>
+//! > **Errors:** <br/>
+//! > - `400 BAD REQUEST` Authorized with personal access token <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** `200 OK`, current access token and session (with its refresh tokens chain) are revoked, session cookies are removed
+//! ----
+//! - ### POST `/me/password` <br/>
+//! > **Authorization:** Required <br/>
+//! > **Payload:** [ChangePasswordRecord](database::user::ChangePasswordRecord) <br/>
+//! > **Errors:** <br/>
+//! > - `400 BAD REQUEST` New password violates [password policy](auth::password_policy::PasswordPolicy) or equals current one <br/>
+//! > - `403 FORBIDDEN` Current password verification failed <br/>
+//! > - `500 INTERNAL SERVER ERROR` Hashing error, Database error <br/>
+//! >
+//! > **Returns:** `200 OK`, all other sessions are terminated and personal access tokens are revoked
+//! ----
+//! - ### GET `/me/permissions` <br/>
+//! > **Authorization:** Required <br/>
+//! > **Returns:** List of granted [Permission](auth::permission::Permission)
+//! ----
+//! - ### GET `/me/sessions` <br/>
+//! > **Authorization:** Required <br/>
+//! > **Errors:** <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** List of active [SessionRecord](database::session::SessionRecord)
+//! ----
+//! - ### DELETE `/me/sessions/{id}` <br/>
+//! > **Authorization:** Required <br/>
+//! > **Errors:** <br/>
+//! > - `404 NOT FOUND` Session not found <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** Terminated [SessionRecord](database::session::SessionRecord)
+//! ----
+//! - ### POST `/me/2fa/enroll` <br/>
+//! > **Authorization:** Required <br/>
+//! > **Payload:** [PasswordConfirmation](auth::PasswordConfirmation) <br/>
+//! > **Errors:** <br/>
+//! > - `403 FORBIDDEN` Wrong password <br/>
+//! > - `409 CONFLICT` Two-factor authentication is already enabled <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** [TotpEnrollment](auth::TotpEnrollment) (must be confirmed)
+//! ----
+//! - ### POST `/me/2fa/confirm` <br/>
+//! > **Authorization:** Required <br/>
+//! > **Payload:** [TotpCodeRequest](auth::TotpCodeRequest) <br/>
+//! > **Errors:** <br/>
+//! > - `400 BAD REQUEST` Enrollment wasn't started <br/>
+//! > - `403 FORBIDDEN` Wrong code <br/>
+//! > - `409 CONFLICT` Two-factor authentication is already enabled <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** `200 OK`
+//! ----
+//! - ### DELETE `/me/2fa` <br/>
+//! > **Authorization:** Required <br/>
+//! > **Payload:** [TotpCodeRequest](auth::TotpCodeRequest) (code or recovery code) <br/>
+//! > **Errors:** <br/>
+//! > - `403 FORBIDDEN` Wrong code, Two-factor authentication is required for role <br/>
+//! > - `404 NOT FOUND` Two-factor authentication is not enabled <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** `200 OK`
+//! ----
+//! - ### GET `/me/tokens` <br/>
+//! > **Authorization:** Required <br/>
+//! > **Errors:** <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** List of [ApiTokenRecord](database::api_token::ApiTokenRecord) (without raw tokens)
+//! ----
+//! - ### POST `/me/tokens` <br/>
+//! > **Authorization:** Required <br/>
+//! > **Payload:** [CreateApiTokenRecord](database::api_token::CreateApiTokenRecord) <br/>
+//! > **Errors:** <br/>
+//! > - `400 BAD REQUEST` Empty name, Unknown role, Expiration is in the past <br/>
+//! > - `403 FORBIDDEN` Role has permissions which you don't have <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** [CreatedApiToken](database::api_token::CreatedApiToken) (raw token is shown only once, tokens are revoked on password change)
+//! ----
+//! - ### DELETE `/me/tokens/{id}` <br/>
+//! > **Authorization:** Required <br/>
+//! > **Errors:** <br/>
+//! > - `404 NOT FOUND` Token not found <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** Revoked [ApiTokenRecord](database::api_token::ApiTokenRecord)
+//! ----
+//! - ### GET `/me/passkeys` <br/>
+//! > **Authorization:** Required <br/>
+//! > **Errors:** <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** Array of [PasskeyRecord](database::passkey::PasskeyRecord) (without credential data)
+//! ----
+//! - ### POST `/me/passkeys/register` <br/>
+//! > **Authorization:** Required <br/>
+//! > **Errors:** <br/>
+//! > - `403 FORBIDDEN` Account is managed by external identity provider, Impersonated session <br/>
+//! > - `404 NOT FOUND` Passkey login is disabled <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** [PasskeyRegistrationChallenge](auth::webauthn::PasskeyRegistrationChallenge) (`options` for `navigator.credentials.create()`)
+//! ----
+//! - ### POST `/me/passkeys` <br/>
+//! > **Authorization:** Required <br/>
+//! > **Payload:** [PasskeyRegistrationRequest](auth::webauthn::PasskeyRegistrationRequest) <br/>
+//! > **Errors:** <br/>
+//! > - `400 BAD REQUEST` Empty name, Unknown/expired ceremony, Attestation verification failed <br/>
+//! > - `404 NOT FOUND` Passkey login is disabled <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** Registered [PasskeyRecord](database::passkey::PasskeyRecord)
+//! ----
+//! - ### PATCH `/me/passkeys/{id}` <br/>
+//! > **Authorization:** Required <br/>
+//! > **Payload:** [RenamePasskeyRecord](database::passkey::RenamePasskeyRecord) <br/>
+//! > **Errors:** <br/>
+//! > - `400 BAD REQUEST` Empty name <br/>
+//! > - `404 NOT FOUND` Passkey not found <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** Renamed [PasskeyRecord](database::passkey::PasskeyRecord)
+//! ----
+//! - ### DELETE `/me/passkeys/{id}` <br/>
+//! > **Authorization:** Required <br/>
+//! > **Errors:** <br/>
+//! > - `404 NOT FOUND` Passkey not found <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** Revoked [PasskeyRecord](database::passkey::PasskeyRecord)
 //! ----
 //! - ### POST `/upload` <br/>
 //! > **Payload:** Image File <br/>
-//! > **Authorization:** Required, Role: [Editor](auth::user::UserRole::Editor)^ <br/>
+//! > **Authorization:** Required, Permission: `media.upload` <br/>
 //! > **Errors:** <br/>
 //! > - `403 FORBIDDEN` Not enough permissions <br/>
 //! > - `500 INTERNAL SERVER ERROR` Server IO error <br/>
@@ -57,7 +344,7 @@
 //! > **Returns:** [String], image hash (required for getter)
 //! ----
 //! - ### GET `/media/{hash}` <br/>
-//! > **Authorization:** Required, Role: [Watcher](auth::user::UserRole::Watcher)^ <br/>
+//! > **Authorization:** Required, Permission: `persons.read` <br/>
 //! > **Errors:** <br/>
 //! > - `403 FORBIDDEN` Not enough permissions <br/>
 //! > - `404 NOT FOUND` Media not found <br/>
@@ -66,25 +353,53 @@
 //! > **Returns:** Image
 //! ----
 //! - ### GET `/persons` <br/>
-//! > **Authorization:** Required, Role: [Watcher](auth::user::UserRole::Watcher)^ <br/>
+//! > Summaries of persons (without media and long text fields), filtered and paginated <br/>
+//! > **Authorization:** Required, Permission: `persons.read` <br/>
+//! > **Query:** [FilterPersonRecord](database::person::FilterPersonRecord) (`city`, `author`, `birthday_from`/`birthday_to`: `YYYY-MM-DD` (inclusive), `has_avatar`, `signed`, `tags`: comma-separated tag names, `tags_mode`: `any`/`all`, `sort`: `surname`/`name`/`patronymic`/`birthday`/`city`/`creation_datetime`, `order`: `asc`/`desc`, `page` (from 1), `limit` (default 25, max 100)) <br/>
+//! > **Errors:** <br/>
+//! > - `400 BAD REQUEST` Malformed date, Unknown sort field, order or tags mode <br/>
+//! > - `403 FORBIDDEN` Not enough permissions <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** [PersonPage](database::person::PersonPage) (page of [PersonSummaryRecord](database::person::PersonSummaryRecord) and total number of matches)
+//! ----
+//! - ### GET `/persons/search?q=` <br/>
+//! > Words of query match name, surname and patronymic in any order. Cyrillic and latin spellings
+//! > are equal (`Иванов` = `Ivanov`, `ё` = `е`), small typos are tolerated <br/>
+//! > **Authorization:** Required, Permission: `persons.read` <br/>
+//! > **Query:** [SearchPersonRecord](database::person::SearchPersonRecord) (`q`, `tags`, `tags_mode`, `sort`: `surname`/`name`/`patronymic`/`birthday`/`city`/`creation_datetime` (by default exact matches go first, then near-misses), `order`: `asc`/`desc`, `page` (from 1), `limit` (default 25, max 100)) <br/>
 //! > **Errors:** <br/>
+//! > - `400 BAD REQUEST` Empty query, Unknown sort field, order or tags mode <br/>
 //! > - `403 FORBIDDEN` Not enough permissions <br/>
 //! > - `500 INTERNAL SERVER ERROR` Database error <br/>
 //! >
-//! > **Returns:** List of [PersonRecord](database::person::PersonRecord)
+//! > **Returns:** [PersonPage](database::person::PersonPage) (page of [PersonSummaryRecord](database::person::PersonSummaryRecord) and total number of matches)
+//! ----
+//! - ### GET `/persons/search/text?q=` <br/>
+//! > Full-text search in summary, past and traits: word forms are matched (russian stemming),
+//! > all words of query must occur in the same field <br/>
+//! > **Authorization:** Required, Permission: `persons.read` <br/>
+//! > **Query:** [TextSearchPersonRecord](database::person::TextSearchPersonRecord) (`q`, `tags`, `tags_mode`, `page` (from 1), `limit` (default 25, max 100)) <br/>
+//! > **Errors:** <br/>
+//! > - `400 BAD REQUEST` Empty query, Unknown tags mode <br/>
+//! > - `403 FORBIDDEN` Not enough permissions <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** [PersonPage](database::person::PersonPage) of [PersonSearchHit](database::person::PersonSearchHit)
+//! > (summary, relevance score and HTML escaped snippets of matched fields with `<mark>` around matches), most relevant first
 //! ----
 //! - ### POST `/persons/create` <br/>
-//! > **Authorization:** Required, Role: [Editor](auth::user::UserRole::Editor)^ <br/>
+//! > **Authorization:** Required, Permission: `persons.write.own` <br/>
 //! > **Payload:** [CreatePersonRecord](database::person::CreatePersonRecord) <br/>
 //! > **Errors:** <br/>
 //! > - `403 FORBIDDEN` Not enough permissions <br/>
-//! > - `409 CONFLICT` Unique record already exists <br/>
+//! > - `409 CONFLICT` Unique record already exists (records in trash are counted too) <br/>
 //! > - `500 INTERNAL SERVER ERROR` Database error <br/>
 //! >
 //! > **Returns:** [PersonRecord](database::person::PersonRecord)
 //! ----
 //! - ### GET `/persons/{id}` <br/>
-//! > **Authorization:** Required, Role: [Watcher](auth::user::UserRole::Watcher)^ <br/>
+//! > **Authorization:** Required, Permission: `persons.read` <br/>
 //! > **Errors:** <br/>
 //! > - `403 FORBIDDEN` Not enough permissions <br/>
 //! > - `404 NOT FOUND` Record not found <br/>
@@ -92,7 +407,7 @@
 //! > **Returns:** [PersonRecord](database::person::PersonRecord)
 //! ----
 //! - ### PATCH `/persons/{id}` <br/>
-//! > **Authorization:** Required, Role: [Editor](auth::user::UserRole::Editor)^ <br/>
+//! > **Authorization:** Required, Permission: `persons.write.own` (own records) or `persons.write.any` <br/>
 //! > **Payload:** [CreatePersonRecord](database::person::CreatePersonRecord) <br/>
 //! > **Errors:** <br/>
 //! > - `403 FORBIDDEN` Not enough permissions, Not author of record <br/>
@@ -102,7 +417,8 @@
 //! > **Returns:** [PersonRecord](database::person::PersonRecord)
 //! ----
 //! - ### DELETE `/persons/{id}` <br/>
-//! > **Authorization:** Required, Role: [Editor](auth::user::UserRole::Editor)^ <br/>
+//! > Moves record to trash (see `/persons/trash`) <br/>
+//! > **Authorization:** Required, Permission: `persons.delete.own` (own records) or `persons.delete.any` <br/>
 //! > **Errors:** <br/>
 //! > - `403 FORBIDDEN` Not enough permissions, Not author of record <br/>
 //! > - `404 NOT FOUND` Record not found <br/>
@@ -110,8 +426,155 @@
 //! >
 //! > **Returns:** [PersonRecord](database::person::PersonRecord)
 //! ----
+//! - ### GET `/persons/{id}/revisions` <br/>
+//! > Every create, update, revert and delete of record is stored as immutable revision
+//! > (with author, time and full snapshot), history is kept after record is deleted.
+//! > History of record in trash is available only with `persons.delete.any` <br/>
+//! > **Authorization:** Required, Permission: `persons.read` <br/>
+//! > **Errors:** <br/>
+//! > - `403 FORBIDDEN` Not enough permissions <br/>
+//! > - `404 NOT FOUND` Record has no revisions, Record is in trash <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** List of [PersonRevisionRecord](database::revision::PersonRevisionRecord) without snapshots, newest first
+//! ----
+//! - ### GET `/persons/{id}/revisions/{revision}` <br/>
+//! > **Authorization:** Required, Permission: `persons.read` <br/>
+//! > **Errors:** <br/>
+//! > - `403 FORBIDDEN` Not enough permissions <br/>
+//! > - `404 NOT FOUND` Revision not found, Record is in trash <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** [PersonRevisionRecord](database::revision::PersonRevisionRecord) with snapshot
+//! ----
+//! - ### GET `/persons/{id}/revisions/diff?from=&to=` <br/>
+//! > **Authorization:** Required, Permission: `persons.read` <br/>
+//! > **Query:** [RevisionDiffQuery](database::revision::RevisionDiffQuery) (`from`, `to`: revision numbers) <br/>
+//! > **Errors:** <br/>
+//! > - `400 BAD REQUEST` Missing or malformed revision number <br/>
+//! > - `403 FORBIDDEN` Not enough permissions <br/>
+//! > - `404 NOT FOUND` Revision not found, Record is in trash <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** List of changed fields [PersonFieldChange](database::revision::PersonFieldChange) (`field`, `from`, `to` values)
+//! ----
+//! - ### POST `/persons/{id}/revisions/{revision}/revert` <br/>
+//! > Restores record's content from revision, restoring is stored as new revision (tags are kept) <br/>
+//! > **Authorization:** Required, Permission: `persons.write.own` (own records) or `persons.write.any` <br/>
+//! > **Errors:** <br/>
+//! > - `403 FORBIDDEN` Not enough permissions, Not author of record <br/>
+//! > - `404 NOT FOUND` Record or revision not found <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** [PersonRecord](database::person::PersonRecord)
+//! ----
+//! - ### GET `/persons/{id}/relations` <br/>
+//! > Relations are mutual, so person's relations include ones added from other records.
+//! > Relations with records in trash are hidden <br/>
+//! > **Authorization:** Required, Permission: `persons.read` <br/>
+//! > **Errors:** <br/>
+//! > - `403 FORBIDDEN` Not enough permissions <br/>
+//! > - `404 NOT FOUND` Record not found <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** List of [PersonConnectionRecord](database::relation::PersonConnectionRecord) (relation with related person)
+//! ----
+//! - ### POST `/persons/{id}/relations` <br/>
+//! > Relates person with another one (`relative`, `spouse`, `colleague`, `friend` or `custom` with label),
+//! > relation may be dated with `since` and `until` <br/>
+//! > **Authorization:** Required, Permission: `persons.write.own` (own records) or `persons.write.any` <br/>
+//! > **Payload:** [CreateRelationRecord](database::relation::CreateRelationRecord) <br/>
+//! > **Errors:** <br/>
+//! > - `400 BAD REQUEST` Relation with itself, Custom relation without label, `until` is before `since` <br/>
+//! > - `403 FORBIDDEN` Not enough permissions, Not author of record <br/>
+//! > - `404 NOT FOUND` Record or related record not found <br/>
+//! > - `409 CONFLICT` Persons are already related the same way <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** [PersonRelationRecord](database::relation::PersonRelationRecord)
+//! ----
+//! - ### DELETE `/persons/{id}/relations/{relation_id}` <br/>
+//! > **Authorization:** Required, Permission: `persons.write.own` (own records) or `persons.write.any` <br/>
+//! > **Errors:** <br/>
+//! > - `403 FORBIDDEN` Not enough permissions, Not author of record <br/>
+//! > - `404 NOT FOUND` Record or its relation not found <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** [PersonRelationRecord](database::relation::PersonRelationRecord)
+//! ----
+//! - ### GET `/persons/{id}/network?depth=` <br/>
+//! > Persons reachable within `depth` relations and relations between them <br/>
+//! > **Authorization:** Required, Permission: `persons.read` <br/>
+//! > **Query:** [NetworkQuery](database::relation::NetworkQuery) (`depth`: default 1, max 3) <br/>
+//! > **Errors:** <br/>
+//! > - `403 FORBIDDEN` Not enough permissions <br/>
+//! > - `404 NOT FOUND` Record not found <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** [PersonNetwork](database::relation::PersonNetwork) (`nodes` with `distance` from person, `edges`)
+//! ----
+//! - ### GET `/persons/{id}/path/{target_id}` <br/>
+//! > Shortest chain of relations between two persons (up to 6 relations) <br/>
+//! > **Authorization:** Required, Permission: `persons.read` <br/>
+//! > **Errors:** <br/>
+//! > - `403 FORBIDDEN` Not enough permissions <br/>
+//! > - `404 NOT FOUND` Record not found, Persons aren't connected <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** [PersonPath](database::relation::PersonPath) (`persons` and `relations` between them)
+//! ----
+//! - ### GET `/persons/{id}/tags` <br/>
+//! > **Authorization:** Required, Permission: `persons.read` <br/>
+//! > **Errors:** <br/>
+//! > - `403 FORBIDDEN` Not enough permissions <br/>
+//! > - `404 NOT FOUND` Record not found <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** List of [TagRecord](database::tag::TagRecord), ordered by name
+//! ----
+//! - ### POST `/persons/{id}/tags` <br/>
+//! > Replaces person's tags (tags aren't part of signed record content), change is stored as revision <br/>
+//! > **Authorization:** Required, Permission: `persons.write.own` (own records) or `persons.write.any` <br/>
+//! > **Payload:** [PersonTagsRecord](database::tag::PersonTagsRecord) (tags identifiers) <br/>
+//! > **Errors:** <br/>
+//! > - `400 BAD REQUEST` Unknown tag <br/>
+//! > - `403 FORBIDDEN` Not enough permissions, Not author of record <br/>
+//! > - `404 NOT FOUND` Record not found or in trash <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** List of person's [TagRecord](database::tag::TagRecord)
+//! ----
+//! - ### GET `/persons/trash` <br/>
+//! > Records in trash are hidden from other endpoints and purged after `FOLKERS_TRASH_RETENTION_DAYS` <br/>
+//! > **Authorization:** Required, Permission: `persons.delete.any` <br/>
+//! > **Errors:** <br/>
+//! > - `403 FORBIDDEN` Not enough permissions <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** List of [TrashedPersonRecord](database::person::TrashedPersonRecord), recently deleted first
+//! ----
+//! - ### POST `/persons/trash/{id}/restore` <br/>
+//! > Takes record out of trash, restoring is stored as new revision <br/>
+//! > **Authorization:** Required, Permission: `persons.delete.any` <br/>
+//! > **Errors:** <br/>
+//! > - `403 FORBIDDEN` Not enough permissions <br/>
+//! > - `404 NOT FOUND` Record not found in trash <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** [PersonRecord](database::person::PersonRecord)
+//! ----
+//! - ### DELETE `/persons/trash/{id}` <br/>
+//! > Permanently deletes record from trash with its signature, revisions and relations <br/>
+//! > **Authorization:** Required, Permission: `persons.delete.any` <br/>
+//! > **Errors:** <br/>
+//! > - `403 FORBIDDEN` Not enough permissions <br/>
+//! > - `404 NOT FOUND` Record not found in trash <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** [PersonRecord](database::person::PersonRecord)
+//! ----
 //! - ### GET `/persons/{id}/verify` <br/>
-//! > **Authorization:** Required, Role: [Watcher](auth::user::UserRole::Watcher)^ <br/>
+//! > **Authorization:** Required, Permission: `persons.read` <br/>
 //! > **Errors:** <br/>
 //! > - `403 FORBIDDEN` Record verification error <br/>
 //! > - `404 NOT FOUND` Record not found <br/>
@@ -120,7 +583,7 @@
 //! > **Returns:** [RecordSignatureRecord](database::signature::RecordSignatureRecord)
 //! ----
 //! - ### GET `/users` <br/>
-//! > **Authorization:** Required, Role: [Admin](auth::user::UserRole::Admin)^ <br/>
+//! > **Authorization:** Required, Permission: `users.manage` <br/>
 //! > **Errors:** <br/>
 //! > - `403 FORBIDDEN` Not enough permissions <br/>
 //! > - `404 NOT FOUND` Record not found <br/>
@@ -129,16 +592,17 @@
 //! > **Returns:** List of [UserRecord](database::user::UserRecord)
 //! ----
 //! - ### POST `/users/create` <br/>
-//! > **Authorization:** Required, Role: [Admin](auth::user::UserRole::Admin)^ <br/>
+//! > **Authorization:** Required, Permission: `users.manage` <br/>
 //! > **Payload:** [CreateUserRecord](database::user::CreateUserRecord) <br/>
 //! > **Errors:** <br/>
-//! > - `403 FORBIDDEN` Not enough permissions <br/>
+//! > - `400 BAD REQUEST` Role doesn't exist, Password violates policy <br/>
+//! > - `403 FORBIDDEN` Not enough permissions, Role has permissions current user doesn't have <br/>
 //! > - `500 INTERNAL SERVER ERROR` Hashing error, Database error <br/>
 //! >
-//! > **Returns:** [UserRecord](database::user::UserRecord)
+//! > **Returns:** [UserRecord](database::user::UserRecord) (user must change password on first login)
 //! ----
 //! - ### GET `/users/{username}` <br/>
-//! > **Authorization:** Required, Role: [Admin](auth::user::UserRole::Admin)^ <br/>
+//! > **Authorization:** Required, Permission: `users.manage` (or own username) <br/>
 //! > **Errors:** <br/>
 //! > - `403 FORBIDDEN` Not enough permissions <br/>
 //! > - `404 NOT FOUND` User not found <br/>
@@ -147,33 +611,217 @@
 //! > **Returns:** [UserRecord](database::user::UserRecord)
 //! ----
 //! - ### PATCH `/users/{username}` <br/>
-//! > **Authorization:** Required, Role: [Admin](auth::user::UserRole::Admin)^ <br/>
+//! > **Authorization:** Required, Permission: `users.manage` <br/>
 //! > **Payload:** [CreateUserRecord](database::user::CreateUserRecord) <br/>
 //! > **Errors:** <br/>
-//! > - `403 FORBIDDEN` Not enough permissions <br/>
+//! > - `400 BAD REQUEST` Role doesn't exist, Password violates policy, Username is changed (records and signatures refer to it) <br/>
+//! > - `403 FORBIDDEN` Not enough permissions, User is protected or created by another user, New role has permissions current user doesn't have <br/>
 //! > - `404 NOT FOUND` User not found <br/>
-//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! > - `500 INTERNAL SERVER ERROR` Hashing error, Database error <br/>
 //! >
-//! > **Returns:** [UserRecord](database::user::UserRecord)
+//! > **Returns:** Updated [UserRecord](database::user::UserRecord) (new password must be changed by user)
 //! ----
 //! - ### DELETE `/users/{username}` <br/>
-//! > **Authorization:** Required, Role: [Admin](auth::user::UserRole::Admin)^ <br/>
+//! > User is suspended (not deleted), so authored records and signatures keep resolving to the user <br/>
+//! > **Authorization:** Required, Permission: `users.manage` <br/>
 //! > **Errors:** <br/>
-//! > - `403 FORBIDDEN` Not enough permissions <br/>
+//! > - `403 FORBIDDEN` Not enough permissions, User is protected or created by another user, Deleting own account <br/>
 //! > - `404 NOT FOUND` User not found <br/>
 //! > - `500 INTERNAL SERVER ERROR` Database error <br/>
 //! >
 //! > **Returns:** [UserRecord](database::user::UserRecord)
 //! ----
+//! - ### POST `/users/{username}/suspend` <br/>
+//! > **Authorization:** Required, Permission: `users.manage` <br/>
+//! > **Payload:** [SuspendUserRecord](database::user::SuspendUserRecord) <br/>
+//! > **Errors:** <br/>
+//! > - `403 FORBIDDEN` Not enough permissions, User is protected or created by another user, Suspending own account <br/>
+//! > - `404 NOT FOUND` User not found <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** [UserRecord](database::user::UserRecord), all user's sessions are terminated
+//! ----
+//! - ### POST `/users/{username}/reactivate` <br/>
+//! > **Authorization:** Required, Permission: `users.manage` <br/>
+//! > **Payload:** [ReactivateUserRecord](database::user::ReactivateUserRecord) <br/>
+//! > **Errors:** <br/>
+//! > - `400 BAD REQUEST` Expiration is in the past <br/>
+//! > - `403 FORBIDDEN` Not enough permissions, User is protected or created by another user <br/>
+//! > - `404 NOT FOUND` User not found <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** [UserRecord](database::user::UserRecord) (expiration is replaced with the new one)
+//! ----
+//! - ### POST `/users/{username}/impersonate` <br/>
+//! > Starts session of user on behalf of current admin (`act` claim), every request is logged with both usernames. <br/>
+//! > Signing, users/roles/settings management, password, 2FA and personal tokens changes are unavailable under impersonation. <br/>
+//! > **Authorization:** Required, Permission: `users.manage` (personal access tokens are not accepted) <br/>
+//! > **Errors:** <br/>
+//! > - `400 BAD REQUEST` Impersonating self, User is suspended or expired <br/>
+//! > - `403 FORBIDDEN` Not enough permissions, User is protected or created by another user, User's role has permissions current user doesn't have <br/>
+//! > - `404 NOT FOUND` User not found <br/>
+//! > - `500 INTERNAL SERVER ERROR` JWT generation error, Database error <br/>
+//! >
+//! > **Returns:** [ImpersonationResponse](auth::ImpersonationResponse) (15 minutes token, can't be refreshed)
+//! ----
+//! - ### GET `/users/{username}/sessions` <br/>
+//! > **Authorization:** Required, Permission: `users.manage` <br/>
+//! > **Errors:** <br/>
+//! > - `403 FORBIDDEN` Not enough permissions, User is protected or created by another user <br/>
+//! > - `404 NOT FOUND` User not found <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** List of active [SessionRecord](database::session::SessionRecord)
+//! ----
+//! - ### DELETE `/users/{username}/sessions/{id}` <br/>
+//! > **Authorization:** Required, Permission: `users.manage` <br/>
+//! > **Errors:** <br/>
+//! > - `403 FORBIDDEN` Not enough permissions, User is protected or created by another user <br/>
+//! > - `404 NOT FOUND` User not found, Session not found <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** Terminated [SessionRecord](database::session::SessionRecord)
+//! ----
+//! - ### DELETE `/users/{username}/2fa` <br/>
+//! > **Authorization:** Required, Permission: `users.manage` <br/>
+//! > **Errors:** <br/>
+//! > - `403 FORBIDDEN` Not enough permissions, User is protected or created by another user <br/>
+//! > - `404 NOT FOUND` User not found <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** `200 OK`, user's two-factor authentication is reset
+//! ----
+//! - ### DELETE `/users/{username}/lockout?ip=` <br/>
+//! > **Authorization:** Required, Permission: `users.manage` <br/>
+//! > **Query:** [UnlockUserQuery](database::user::UnlockUserQuery) (`ip`: client IP address, optional) <br/>
+//! > **Errors:** <br/>
+//! > - `403 FORBIDDEN` Not enough permissions, User is protected or created by another user <br/>
+//! > - `404 NOT FOUND` User not found <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** `200 OK`, failed login attempts of username (and IP address if given) are reset
+//! ----
+//! - ### GET `/roles` <br/>
+//! > **Authorization:** Required, Permission: `roles.manage` <br/>
+//! > **Errors:** <br/>
+//! > - `403 FORBIDDEN` Not enough permissions <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** List of [RoleRecord](database::role::RoleRecord)
+//! ----
+//! - ### POST `/roles` <br/>
+//! > **Authorization:** Required, Permission: `roles.manage` <br/>
+//! > **Payload:** [CreateRoleRecord](database::role::CreateRoleRecord) <br/>
+//! > **Errors:** <br/>
+//! > - `400 BAD REQUEST` Name is empty or has characters other than `a-z`, `0-9`, `_`, `-` <br/>
+//! > - `403 FORBIDDEN` Not enough permissions, Granted permissions aren't held by user <br/>
+//! > - `409 CONFLICT` Role already exists <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** [RoleRecord](database::role::RoleRecord)
+//! ----
+//! - ### PATCH `/roles/{name}` <br/>
+//! > **Authorization:** Required, Permission: `roles.manage` <br/>
+//! > **Payload:** [PatchRoleRecord](database::role::PatchRoleRecord) <br/>
+//! > **Errors:** <br/>
+//! > - `403 FORBIDDEN` Not enough permissions, Granted permissions aren't held by user <br/>
+//! > - `404 NOT FOUND` Role not found <br/>
+//! > - `409 CONFLICT` Built-in `admin` role can't be changed <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** Updated [RoleRecord](database::role::RoleRecord) (applied immediately)
+//! ----
+//! - ### DELETE `/roles/{name}` <br/>
+//! > **Authorization:** Required, Permission: `roles.manage` <br/>
+//! > **Errors:** <br/>
+//! > - `403 FORBIDDEN` Not enough permissions <br/>
+//! > - `404 NOT FOUND` Role not found <br/>
+//! > - `409 CONFLICT` Role is built-in or assigned to users <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** Deleted [RoleRecord](database::role::RoleRecord)
+//! ----
+//! - ### GET `/tags` <br/>
+//! > **Authorization:** Required, Permission: `persons.read` <br/>
+//! > **Errors:** <br/>
+//! > - `403 FORBIDDEN` Not enough permissions <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** List of [TagUsageRecord](database::tag::TagUsageRecord) (tag with number of tagged records), ordered by name
+//! ----
+//! - ### POST `/tags` <br/>
+//! > **Authorization:** Required, Permission: `settings.manage` <br/>
+//! > **Payload:** [CreateTagRecord](database::tag::CreateTagRecord) (`name`, `color`: `#rrggbb`) <br/>
+//! > **Errors:** <br/>
+//! > - `400 BAD REQUEST` Empty name, Name with commas or longer than 64 characters, Malformed color <br/>
+//! > - `403 FORBIDDEN` Not enough permissions <br/>
+//! > - `409 CONFLICT` Tag already exists <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** [TagRecord](database::tag::TagRecord)
+//! ----
+//! - ### PATCH `/tags/{id}` <br/>
+//! > Renames or recolors tag, tagged records are updated too <br/>
+//! > **Authorization:** Required, Permission: `settings.manage` <br/>
+//! > **Payload:** [CreateTagRecord](database::tag::CreateTagRecord) <br/>
+//! > **Errors:** <br/>
+//! > - `400 BAD REQUEST` Empty name, Name with commas or longer than 64 characters, Malformed color <br/>
+//! > - `403 FORBIDDEN` Not enough permissions <br/>
+//! > - `404 NOT FOUND` Tag not found <br/>
+//! > - `409 CONFLICT` Name is used by another tag <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** Updated [TagRecord](database::tag::TagRecord)
+//! ----
+//! - ### DELETE `/tags/{id}` <br/>
+//! > Deletes tag and removes it from all records <br/>
+//! > **Authorization:** Required, Permission: `settings.manage` <br/>
+//! > **Errors:** <br/>
+//! > - `403 FORBIDDEN` Not enough permissions <br/>
+//! > - `404 NOT FOUND` Tag not found <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** Deleted [TagRecord](database::tag::TagRecord)
+//! ----
+//! - ### POST `/tags/{id}/merge` <br/>
+//! > Replaces tag with another one on all records and deletes it (atomically) <br/>
+//! > **Authorization:** Required, Permission: `settings.manage` <br/>
+//! > **Payload:** [MergeTagRecord](database::tag::MergeTagRecord) (`into`: identifier of remaining tag) <br/>
+//! > **Errors:** <br/>
+//! > - `400 BAD REQUEST` Tag is merged into itself <br/>
+//! > - `403 FORBIDDEN` Not enough permissions <br/>
+//! > - `404 NOT FOUND` Tag not found <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** Remaining [TagRecord](database::tag::TagRecord)
+//! ----
+//! - ### GET `/settings/security` <br/>
+//! > **Authorization:** Required, Permission: `settings.manage` <br/>
+//! > **Errors:** <br/>
+//! > - `403 FORBIDDEN` Not enough permissions <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** [SecuritySettings](database::settings::SecuritySettings)
+//! ----
+//! - ### PATCH `/settings/security` <br/>
+//! > **Authorization:** Required, Permission: `settings.manage` <br/>
+//! > **Payload:** [SecuritySettings](database::settings::SecuritySettings) <br/>
+//! > **Errors:** <br/>
+//! > - `400 BAD REQUEST` Role doesn't exist <br/>
+//! > - `403 FORBIDDEN` Not enough permissions <br/>
+//! > - `500 INTERNAL SERVER ERROR` Database error <br/>
+//! >
+//! > **Returns:** [SecuritySettings](database::settings::SecuritySettings)
+//! ----
 //! - ### POST `/signature-keygen` <br/>
-//! > **Authorization:** Required, Role: [Admin](auth::user::UserRole::Admin)^ <br/>
+//! > **Authorization:** Required, Permission: `signatures.sign` <br/>
 //! > **Errors:** <br/>
 //! > - `403 FORBIDDEN` Not enough permissions <br/>
 //! >
 //! > **Returns:** [String] which contains private key
 //! ----
 //! - ### POST `/persons/{id}/sign` <br/>
-//! > **Authorization:** Required, Role: [Admin](auth::user::UserRole::Admin)^ <br/>
+//! > **Authorization:** Required, Permission: `signatures.sign` <br/>
 //! > **Payload:** [SignRecordPayload](database::signature::SignRecordPayload) <br/>
 //! > **Errors:** <br/>
 //! > - `400 BAD REQUEST` Signature error
@@ -185,7 +833,7 @@
 //! > **Returns:** [RecordSignatureRecord](database::signature::RecordSignatureRecord)
 //! ----
 //! - ### DELETE `/persons/{id}/unsign` <br/>
-//! > **Authorization:** Required, Role: [Admin](auth::user::UserRole::Admin)^ <br/>
+//! > **Authorization:** Required, Permission: `signatures.sign` <br/>
 //! > **Errors:** <br/>
 //! > - `403 FORBIDDEN` Not enough permissions <br/>
 //! > - `404 NOT FOUND` Record not found <br/>
@@ -194,7 +842,7 @@
 //! > **Returns:** [RecordSignatureRecord](database::signature::RecordSignatureRecord)
 
 use axum::{extract::DefaultBodyLimit, http::Method, routing, Router};
-use std::sync::LazyLock;
+use std::{net::SocketAddr, sync::LazyLock};
 use tower_http::cors::{self, CorsLayer};
 
 mod auth;
@@ -202,8 +850,12 @@ mod database;
 mod signatures;
 mod middleware;
 mod routers;
+mod trash;
 mod uploads;
 
+#[cfg(test)]
+mod tests;
+
 const ENDPOINT: &str = "0.0.0.0:3001";
 
 pub static DATABASE: LazyLock<database::DatabaseClient> =
@@ -215,22 +867,37 @@ async fn main() -> anyhow::Result<()> {
     let _ = dotenvy::dotenv();
     env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
 
-    let base64_salt = std::env::var("FOLKERS_BASE64_SALT").ok().or_else(|| {
-        log::error!("💣 Critical Security Error. Salt is not found in environment variables.");
-        log::error!("Please provide your BASE64 encoded salt in environment variable named `FOLKERS_BASE64_SALT`");
+    // legacy global salt is only used to recognize old hashes and upgrade them
+    if let Ok(base64_salt) = std::env::var("FOLKERS_BASE64_SALT") {
+        let _: Result<argon2::password_hash::SaltString, String> =
+            argon2::password_hash::SaltString::from_b64(&base64_salt).map_err(|err| {
+                log::error!("💣 Critical Security Error. Encoded salt string failed verification!");
+                log::error!("Error: {}", err);
+
+                std::process::exit(1);
+            });
+    }
+
+    let _ = auth::UserRepository::argon2_params().map_err(|err| {
+        log::error!("💣 Critical Security Error. Argon2 parameters failed verification!");
+        log::error!("Error: {}", err);
 
         std::process::exit(1);
-    }).unwrap();
+    });
 
-    let _: Result<argon2::password_hash::SaltString, String> =
-        argon2::password_hash::SaltString::from_b64(&base64_salt).map_err(|err| {
-            log::error!("💣 Critical Security Error. Encoded salt string failed verification!");
-            log::error!("Error: {}", err);
+    let password_policy = auth::password_policy::PasswordPolicy::from_env().unwrap_or_else(|err| {
+        log::error!("💣 Critical Security Error. Password policy failed verification!");
+        log::error!("Error: {}", err);
 
-            std::process::exit(1);
-        });
+        std::process::exit(1);
+    });
 
     log::info!("🚀 Folkers Backend Server");
+    log::info!(
+        "- Password policy: minimal length {}, {} breached password(s)",
+        password_policy.min_length,
+        password_policy.breached_count()
+    );
     log::info!("⚙️ Starting initialization...");
 
     // Database setup
@@ -258,6 +925,16 @@ async fn main() -> anyhow::Result<()> {
     log::info!("- Setting up uploads...");
     uploads::init_uploads().await?;
 
+    // Trash Setup
+
+    match trash::retention_from_env()? {
+        Some(retention) => {
+            log::info!("- Trash retention: {} day(s)", retention.num_days());
+            trash::spawn_purge_task(retention);
+        }
+        None => log::info!("- Automatic trash purge is disabled"),
+    }
+
     // HTTP Cors
     let cors = CorsLayer::new()
         .allow_origin(cors::Any)
@@ -270,27 +947,186 @@ async fn main() -> anyhow::Result<()> {
 
     let jwt_config = auth::jwt::JwtConfig::new()?;
     let jwt_service = auth::jwt::JwtService::new(jwt_config);
-    let user_repo = auth::UserRepository::new();
+    let auth_backends = auth::backend::from_env()?;
+
+    log::info!(
+        "- Authentication backends: {}",
+        auth_backends
+            .iter()
+            .map(|backend| backend.name())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    let user_repo = auth::UserRepository::new(auth_backends);
+
+    let oidc = match auth::oidc::OidcConfig::from_env()? {
+        Some(config) => {
+            log::info!("- Single sign-on is enabled, issuer: `{}`", config.issuer);
+            Some(auth::oidc::OidcProvider::new(config)?)
+        }
+        None => None,
+    };
+
+    let webauthn = match auth::webauthn::WebauthnConfig::from_env()? {
+        Some(config) => {
+            log::info!("- Passkey login is enabled, relying party: `{}`", config.rp_id);
+            Some(std::sync::Arc::new(config.build()?))
+        }
+        None => None,
+    };
+
+    let cookies = auth::cookie::CookieConfig::from_env()?;
+
+    if cookies.is_some() {
+        log::info!("- Cookie session mode is enabled");
+    }
 
     let app_state = routers::AppState {
         user_repo,
         jwt_service,
+        oidc,
+        webauthn,
+        cookies,
     };
 
     // Main Application Router
 
     log::info!("- Setting up routers...");
 
+    let app = router(app_state).layer(cors);
+
+    log::info!("- Binding TCP Listener...");
+
+    let listener = tokio::net::TcpListener::bind(ENDPOINT).await?;
+
+    log::info!("🔗 Listening on http://{ENDPOINT}...");
+
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Main application router (every API endpoint)
+fn router(app_state: routers::AppState) -> Router {
     let public_routers = Router::new()
         .route("/", routing::get(routers::root_handler))
         .route("/health", routing::get(routers::health_handler))
-        .route("/login", routing::post(routers::login_handler));
+        .route(
+            "/.well-known/jwks.json",
+            routing::get(routers::jwks_handler),
+        )
+        .route("/login", routing::post(routers::login_handler))
+        .route("/login/2fa", routing::post(routers::login_2fa_handler))
+        .route(
+            "/login/2fa/enroll",
+            routing::post(routers::login_2fa_enroll_handler),
+        )
+        .route("/refresh", routing::post(routers::refresh_handler))
+        .route(
+            "/oidc/authorize",
+            routing::get(routers::oidc_authorize_handler),
+        )
+        .route(
+            "/oidc/callback",
+            routing::post(routers::oidc_callback_handler),
+        )
+        .route(
+            "/login/passkey",
+            routing::post(routers::login_passkey_handler),
+        )
+        .route(
+            "/login/passkey/finish",
+            routing::post(routers::login_passkey_finish_handler),
+        );
 
     let watchers_routers = Router::new()
         .route("/me", routing::get(routers::me_handler))
+        .route(
+            "/me/permissions",
+            routing::get(routers::me_permissions_handler),
+        )
+        .route("/me/password", routing::post(routers::me_password_handler))
+        .route("/logout", routing::post(routers::logout_handler))
+        .route("/me/sessions", routing::get(routers::me_sessions_handler))
+        .route(
+            "/me/sessions/{id}",
+            routing::delete(routers::me_sessions_id_delete_handler),
+        )
+        .route(
+            "/me/2fa/enroll",
+            routing::post(routers::me_2fa_enroll_handler),
+        )
+        .route(
+            "/me/2fa/confirm",
+            routing::post(routers::me_2fa_confirm_handler),
+        )
+        .route("/me/2fa", routing::delete(routers::me_2fa_delete_handler))
+        .route(
+            "/me/tokens",
+            routing::get(routers::me_tokens_handler).post(routers::me_tokens_create_handler),
+        )
+        .route(
+            "/me/tokens/{id}",
+            routing::delete(routers::me_tokens_id_delete_handler),
+        )
+        .route(
+            "/me/passkeys",
+            routing::get(routers::me_passkeys_handler).post(routers::me_passkeys_create_handler),
+        )
+        .route(
+            "/me/passkeys/register",
+            routing::post(routers::me_passkeys_register_handler),
+        )
+        .route(
+            "/me/passkeys/{id}",
+            routing::patch(routers::me_passkeys_id_patch_handler)
+                .delete(routers::me_passkeys_id_delete_handler),
+        )
         .route("/media/{hash}", routing::get(routers::media_handler))
         .route("/persons", routing::get(routers::persons_handler))
+        .route(
+            "/persons/search",
+            routing::get(routers::persons_search_handler),
+        )
+        .route(
+            "/persons/search/text",
+            routing::get(routers::persons_search_text_handler),
+        )
         .route("/persons/{id}", routing::get(routers::persons_id_handler))
+        .route(
+            "/persons/{id}/revisions",
+            routing::get(routers::persons_id_revisions_handler),
+        )
+        .route(
+            "/persons/{id}/revisions/diff",
+            routing::get(routers::persons_id_revisions_diff_handler),
+        )
+        .route(
+            "/persons/{id}/revisions/{revision}",
+            routing::get(routers::persons_id_revision_handler),
+        )
+        .route(
+            "/persons/{id}/relations",
+            routing::get(routers::persons_id_relations_handler),
+        )
+        .route(
+            "/persons/{id}/network",
+            routing::get(routers::persons_id_network_handler),
+        )
+        .route(
+            "/persons/{id}/path/{target_id}",
+            routing::get(routers::persons_id_path_handler),
+        )
+        .route(
+            "/persons/{id}/tags",
+            routing::get(routers::persons_id_tags_handler),
+        )
+        .route("/tags", routing::get(routers::tags_handler))
         .route_layer(axum::middleware::from_fn_with_state(
             app_state.clone(),
             middleware::auth_middleware,
@@ -311,6 +1147,22 @@ async fn main() -> anyhow::Result<()> {
             "/persons/{id}",
             routing::delete(routers::persons_delete_handler),
         )
+        .route(
+            "/persons/{id}/revisions/{revision}/revert",
+            routing::post(routers::persons_id_revision_revert_handler),
+        )
+        .route(
+            "/persons/{id}/relations",
+            routing::post(routers::persons_id_relations_add_handler),
+        )
+        .route(
+            "/persons/{id}/relations/{relation_id}",
+            routing::delete(routers::persons_id_relation_delete_handler),
+        )
+        .route(
+            "/persons/{id}/tags",
+            routing::post(routers::persons_id_tags_post_handler),
+        )
         .route(
             "/persons/{id}/verify",
             routing::get(routers::persons_id_verify_handler),
@@ -321,6 +1173,15 @@ async fn main() -> anyhow::Result<()> {
         ));
 
     let admin_routers = Router::new()
+        .route("/persons/trash", routing::get(routers::persons_trash_handler))
+        .route(
+            "/persons/trash/{id}/restore",
+            routing::post(routers::persons_trash_id_restore_handler),
+        )
+        .route(
+            "/persons/trash/{id}",
+            routing::delete(routers::persons_trash_id_delete_handler),
+        )
         .route("/users", routing::get(routers::users_handler))
         .route(
             "/users/create",
@@ -338,6 +1199,62 @@ async fn main() -> anyhow::Result<()> {
             "/users/{username}",
             routing::patch(routers::users_username_patch_handler),
         )
+        .route(
+            "/users/{username}/suspend",
+            routing::post(routers::users_username_suspend_handler),
+        )
+        .route(
+            "/users/{username}/reactivate",
+            routing::post(routers::users_username_reactivate_handler),
+        )
+        .route(
+            "/users/{username}/impersonate",
+            routing::post(routers::users_username_impersonate_handler),
+        )
+        .route(
+            "/users/{username}/sessions",
+            routing::get(routers::users_username_sessions_handler),
+        )
+        .route(
+            "/users/{username}/sessions/{id}",
+            routing::delete(routers::users_username_sessions_id_delete_handler),
+        )
+        .route(
+            "/users/{username}/2fa",
+            routing::delete(routers::users_username_2fa_delete_handler),
+        )
+        .route(
+            "/users/{username}/lockout",
+            routing::delete(routers::users_username_lockout_delete_handler),
+        )
+        .route("/roles", routing::get(routers::roles_handler))
+        .route("/roles", routing::post(routers::roles_create_handler))
+        .route(
+            "/roles/{name}",
+            routing::patch(routers::roles_name_patch_handler),
+        )
+        .route(
+            "/roles/{name}",
+            routing::delete(routers::roles_name_delete_handler),
+        )
+        .route("/tags", routing::post(routers::tags_create_handler))
+        .route(
+            "/tags/{id}",
+            routing::patch(routers::tags_id_patch_handler)
+                .delete(routers::tags_id_delete_handler),
+        )
+        .route(
+            "/tags/{id}/merge",
+            routing::post(routers::tags_id_merge_handler),
+        )
+        .route(
+            "/settings/security",
+            routing::get(routers::settings_security_handler),
+        )
+        .route(
+            "/settings/security",
+            routing::patch(routers::settings_security_patch_handler),
+        )
         .route(
             "/signature-keygen",
             routing::post(routers::signature_keygen_handler),
@@ -359,21 +1276,10 @@ async fn main() -> anyhow::Result<()> {
             middleware::auth_middleware,
         ));
 
-    let app = Router::new()
+    Router::new()
         .merge(public_routers)
         .merge(watchers_routers)
         .merge(editors_routers)
         .merge(admin_routers)
         .with_state(app_state)
-        .layer(cors);
-
-    log::info!("- Binding TCP Listener...");
-
-    let listener = tokio::net::TcpListener::bind(ENDPOINT).await?;
-
-    log::info!("🔗 Listening on http://{ENDPOINT}...");
-
-    axum::serve(listener, app.into_make_service()).await?;
-
-    Ok(())
 }
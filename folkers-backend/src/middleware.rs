@@ -1,32 +1,295 @@
-use super::auth;
+use super::{DATABASE, auth, auth::permission::Permission};
 use axum::{
     extract::{FromRequestParts, Request, State},
     http::{HeaderMap, StatusCode, request::Parts},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Response},
 };
+use std::{net::SocketAddr, sync::LazyLock};
 
-/// JWT token verification middleware
+/// Session `last_seen` and API token `last_used` fields are updated not more often than this interval
+const TOUCH_INTERVAL_SECONDS: i64 = 60;
+
+/// Endpoints available while password change is required
+const PASSWORD_CHANGE_ENDPOINTS: [&str; 3] = ["/me", "/me/password", "/logout"];
+
+/// Endpoints (prefixes) unavailable under impersonation: own credentials of impersonated user
+const IMPERSONATION_DENIED_ENDPOINTS: [&str; 4] =
+    ["/me/password", "/me/2fa", "/me/tokens", "/me/passkeys"];
+
+/// Permissions removed under impersonation: signing and administration
+const IMPERSONATION_DENIED_PERMISSIONS: [Permission; 4] = [
+    Permission::SignaturesSign,
+    Permission::UsersManage,
+    Permission::RolesManage,
+    Permission::SettingsManage,
+];
+
+/// Trust `X-Forwarded-For`/`X-Real-IP` headers (only when running behind reverse proxy)
+static TRUST_PROXY_HEADERS: LazyLock<bool> = LazyLock::new(|| {
+    std::env::var("FOLKERS_TRUST_PROXY_HEADERS")
+        .map(|value| value.eq_ignore_ascii_case("true") || value == "1")
+        .unwrap_or(false)
+});
+
+/// JWT token (or personal access token) verification middleware
 pub async fn auth_middleware(
     State(app_state): State<super::routers::AppState>,
     mut request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    let token = extract_token_from_headers(request.headers()).ok_or(StatusCode::BAD_REQUEST)?;
+    let token = match extract_token_from_headers(request.headers()) {
+        Some(token) => token,
+        None => extract_token_from_cookies(&app_state, &request)?,
+    };
+
+    let user = if auth::api_token::is_api_token(&token) {
+        authenticate_api_token(&app_state, &token).await?
+    } else {
+        authenticate_jwt(&app_state, &token).await?
+    };
+
+    if let Some(actor) = &user.impersonated_by {
+        let path = request.uri().path();
+
+        log::info!(
+            "`{} ({})` impersonated by `{}` [{} {}]",
+            user.username,
+            user.id,
+            actor,
+            request.method(),
+            path
+        );
 
+        if IMPERSONATION_DENIED_ENDPOINTS
+            .iter()
+            .any(|endpoint| path.starts_with(endpoint))
+        {
+            return Err(StatusCode::FORBIDDEN);
+        }
+    }
+
+    if user.password_change_required
+        && !PASSWORD_CHANGE_ENDPOINTS.contains(&request.uri().path())
+    {
+        return Ok((
+            StatusCode::FORBIDDEN,
+            [("X-Password-Change-Required", "true")],
+        )
+            .into_response());
+    }
+
+    request.extensions_mut().insert(user);
+    Ok(next.run(request).await)
+}
+
+async fn authenticate_jwt(
+    app_state: &super::routers::AppState,
+    token: &str,
+) -> Result<AuthUser, StatusCode> {
     let claims = app_state
         .jwt_service
-        .verify_token(&token)
+        .verify_token(token)
         .map_err(|_| StatusCode::UNAUTHORIZED)?;
 
-    let user = AuthUser {
-        id: claims.sub,
-        username: claims.username,
-        role: auth::user::UserRole::from_str(&claims.role),
+    let revoked = DATABASE
+        .is_access_token_revoked(&claims.jti)
+        .await
+        .map_err(|err| {
+            log::error!("Auth middleware got database error: {}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if revoked {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    let session = DATABASE
+        .get_session(&claims.sid)
+        .await
+        .map_err(|err| {
+            log::error!("Auth middleware got database error: {}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    let now = chrono::Utc::now();
+
+    if session.revoked || session.expires_at.0 < now || session.user_id != claims.sub {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    if (now - session.last_seen.0).num_seconds() >= TOUCH_INTERVAL_SECONDS {
+        let _ = DATABASE.touch_session(&claims.sid).await.map_err(|err| {
+            log::error!("Auth middleware got database error: {}", err);
+        });
+    }
+
+    // role and username are taken from database, so demotion and suspension apply immediately
+    let user = app_state
+        .user_repo
+        .current_user(&claims.sub)
+        .await
+        .filter(|user| user.is_active())
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    let mut permissions = app_state
+        .user_repo
+        .role_permissions(&user.role)
+        .await
+        .map_err(|err| {
+            log::error!("Auth middleware got database error: {}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let impersonated_by = match &claims.act {
+        Some(actor) => {
+            let actor = authenticate_actor(app_state, actor).await?;
+            permissions.retain(|permission| !IMPERSONATION_DENIED_PERMISSIONS.contains(permission));
+
+            Some(actor)
+        }
+        None => None,
     };
 
-    request.extensions_mut().insert(user);
-    Ok(next.run(request).await)
+    Ok(AuthUser {
+        // impersonating admin can't change user's password anyway
+        password_change_required: impersonated_by.is_none() && user.password_change_required(),
+        impersonated_by,
+        protected: user.protected,
+        id: user.id,
+        username: user.username,
+        role: user.role,
+        permissions,
+        token_id: claims.jti,
+        token_expiration: claims.exp,
+        session_id: Some(claims.sid),
+    })
+}
+
+/// Impersonating admin must still be active and allowed to manage users
+async fn authenticate_actor(
+    app_state: &super::routers::AppState,
+    actor: &auth::jwt::ActorClaim,
+) -> Result<String, StatusCode> {
+    let actor = app_state
+        .user_repo
+        .current_user(&actor.sub)
+        .await
+        .filter(|user| user.is_active())
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    let permissions = app_state
+        .user_repo
+        .role_permissions(&actor.role)
+        .await
+        .map_err(|err| {
+            log::error!("Auth middleware got database error: {}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if !permissions.contains(&Permission::UsersManage) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    Ok(actor.username)
+}
+
+async fn authenticate_api_token(
+    app_state: &super::routers::AppState,
+    token: &str,
+) -> Result<AuthUser, StatusCode> {
+    let record = DATABASE
+        .get_api_token_by_hash(auth::api_token::hash(token))
+        .await
+        .map_err(|err| {
+            log::error!("Auth middleware got database error: {}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    let now = chrono::Utc::now();
+
+    if record
+        .expires_at
+        .as_ref()
+        .is_some_and(|expires_at| expires_at.0 < now)
+    {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    // owner could be suspended or demoted after token creation
+    let owner = app_state
+        .user_repo
+        .current_user(&record.user_id)
+        .await
+        .filter(|user| user.is_active())
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    // tokens aren't refreshed, so directory leavers are checked here
+    let owner_role = match app_state.user_repo.current_account_status(&owner).await {
+        auth::backend::AccountStatus::Disabled => {
+            log::warn!(
+                "`{} ({})` used personal access token of disabled external account",
+                owner.username,
+                owner.id
+            );
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+        auth::backend::AccountStatus::Active { role } => role,
+        auth::backend::AccountStatus::Unmanaged => owner.role.clone(),
+    };
+
+    let role_permissions = |role: String| async move {
+        app_state
+            .user_repo
+            .role_permissions(&role)
+            .await
+            .map_err(|err| {
+                log::error!("Auth middleware got database error: {}", err);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })
+    };
+
+    // token scope is limited by owner's current permissions
+    let owner_permissions = role_permissions(owner_role).await?;
+    let permissions = role_permissions(record.role.clone())
+        .await?
+        .into_iter()
+        .filter(|permission| owner_permissions.contains(permission))
+        .collect();
+
+    let token_id = record
+        .id
+        .as_ref()
+        .map(|id| id.id.to_string())
+        .unwrap_or_default();
+
+    if record
+        .last_used
+        .as_ref()
+        .is_none_or(|last_used| (now - last_used.0).num_seconds() >= TOUCH_INTERVAL_SECONDS)
+    {
+        let _ = DATABASE.touch_api_token(&token_id).await.map_err(|err| {
+            log::error!("Auth middleware got database error: {}", err);
+        });
+    }
+
+    Ok(AuthUser {
+        password_change_required: owner.password_change_required(),
+        protected: owner.protected,
+        id: record.user_id,
+        username: owner.username,
+        role: record.role,
+        permissions,
+        token_id,
+        token_expiration: record
+            .expires_at
+            .map(|expires_at| expires_at.0.timestamp() as usize)
+            .unwrap_or_default(),
+        session_id: None,
+        impersonated_by: None,
+    })
 }
 
 fn extract_token_from_headers(headers: &HeaderMap) -> Option<String> {
@@ -37,13 +300,106 @@ fn extract_token_from_headers(headers: &HeaderMap) -> Option<String> {
         .map(|value| value.to_string())
 }
 
+/// Cookie session mode: access token from cookie, state-changing requests must pass CSRF check
+fn extract_token_from_cookies(
+    app_state: &super::routers::AppState,
+    request: &Request,
+) -> Result<String, StatusCode> {
+    if app_state.cookies.is_none() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let headers = request.headers();
+    let token = auth::cookie::get(headers, auth::cookie::ACCESS_COOKIE)
+        .ok_or(StatusCode::BAD_REQUEST)?;
+
+    if !auth::cookie::is_safe_method(request.method()) && !auth::cookie::verify_csrf(headers) {
+        log::warn!(
+            "[{} {}] rejected cookie authorized request without valid CSRF token",
+            request.method(),
+            request.uri().path()
+        );
+
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(token)
+}
+
+/// Resolves client IP address from proxy headers or connection info
+///
+/// `X-Real-IP` is set by trusted proxy itself, `X-Forwarded-For` is only used for its last entry
+/// (appended by trusted proxy): all previous entries are sent by client and can be forged
+pub fn client_ip(headers: &HeaderMap, addr: &SocketAddr) -> String {
+    if *TRUST_PROXY_HEADERS {
+        let forwarded = headers
+            .get("x-real-ip")
+            .and_then(|value| value.to_str().ok())
+            .or_else(|| {
+                headers
+                    .get_all("x-forwarded-for")
+                    .iter()
+                    .next_back()
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.rsplit(',').next())
+            })
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty());
+
+        if let Some(ip) = forwarded {
+            return ip.to_string();
+        }
+    }
+
+    addr.ip().to_string()
+}
+
 /// Extract for user authentication
 #[derive(Debug, Clone)]
 #[allow(unused)]
 pub struct AuthUser {
     pub id: String,
     pub username: String,
-    pub role: auth::user::UserRole,
+    /// Role name
+    pub role: String,
+    /// Permissions granted by role (and limited by personal access token scope)
+    pub permissions: Vec<Permission>,
+    /// JWT identifier (`jti` claim) or personal access token identifier
+    pub token_id: String,
+    /// Token expiration timestamp (`0` for never expiring personal access token)
+    pub token_expiration: usize,
+    /// Session identifier (`sid` claim), empty for personal access tokens
+    pub session_id: Option<String>,
+    /// Only password change is allowed
+    pub password_change_required: bool,
+    /// Static admin, bypasses users ownership hierarchy
+    pub protected: bool,
+    /// Username of admin who impersonates this user
+    pub impersonated_by: Option<String>,
+}
+
+impl AuthUser {
+    pub fn can(&self, permission: Permission) -> bool {
+        self.permissions.contains(&permission)
+    }
+
+    /// Handler guard, `403 FORBIDDEN` if permission is missing
+    pub fn require(&self, permission: Permission) -> Result<(), StatusCode> {
+        if self.can(permission) {
+            Ok(())
+        } else {
+            Err(StatusCode::FORBIDDEN)
+        }
+    }
+
+    /// Handler guard, `403 FORBIDDEN` if none of permissions is granted
+    pub fn require_any(&self, permissions: &[Permission]) -> Result<(), StatusCode> {
+        if permissions.iter().any(|permission| self.can(*permission)) {
+            Ok(())
+        } else {
+            Err(StatusCode::FORBIDDEN)
+        }
+    }
 }
 
 impl<S> FromRequestParts<S> for AuthUser
@@ -1,9 +1,12 @@
 use axum::{
     Json,
-    extract::{Multipart, Path, State},
-    http::StatusCode,
-    response::{Html, IntoResponse},
+    extract::{ConnectInfo, Multipart, Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{AppendHeaders, Html, IntoResponse, Response},
 };
+use base64::{Engine as _, engine::general_purpose};
+use std::net::SocketAddr;
+use webauthn_rs::prelude::{Passkey, PasskeyAuthentication, PasskeyRegistration};
 
 use super::{DATABASE, auth, database, middleware, uploads, signatures};
 
@@ -11,6 +14,12 @@ use super::{DATABASE, auth, database, middleware, uploads, signatures};
 pub struct AppState {
     pub user_repo: auth::UserRepository,
     pub jwt_service: auth::jwt::JwtService,
+    /// Single sign-on provider (disabled if empty)
+    pub oidc: Option<auth::oidc::OidcProvider>,
+    /// Passkey login (disabled if empty)
+    pub webauthn: Option<std::sync::Arc<webauthn_rs::Webauthn>>,
+    /// Cookie session mode (disabled if empty)
+    pub cookies: Option<auth::cookie::CookieConfig>,
 }
 
 // INFO: Public Routers
@@ -25,83 +34,3330 @@ pub async fn health_handler() -> impl IntoResponse {
     StatusCode::OK
 }
 
+/// GET `/.well-known/jwks.json`
+pub async fn jwks_handler(
+    State(state): State<AppState>,
+) -> Json<jsonwebtoken::jwk::JwkSet> {
+    Json(state.jwt_service.jwks())
+}
+
 /// POST `/login`
 pub async fn login_handler(
     State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Json(payload): Json<auth::LoginRequest>,
-) -> Result<Json<auth::AuthResponse>, StatusCode> {
-    let user = state
+) -> Result<Response, StatusCode> {
+    let client_ip = middleware::client_ip(&headers, &addr);
+    let throttle_keys = [
+        auth::throttle::username_key(&payload.username),
+        auth::throttle::ip_key(&client_ip),
+    ];
+
+    if let Some(response) =
+        check_login_lock(&throttle_keys, &payload.username, &client_ip, "POST /login").await?
+    {
+        return Ok(response);
+    }
+
+    // unknown username and wrong password must look the same
+
+    let Some(identity) = state
         .user_repo
-        .find_by_username(&payload.username)
+        .authenticate(&payload.username, &payload.password)
         .await
-        .ok_or(StatusCode::NOT_FOUND)?;
+    else {
+        register_login_failure(&throttle_keys, &payload.username, &client_ip, "POST /login")
+            .await;
+        return Err(StatusCode::UNAUTHORIZED);
+    };
 
-    if !state
+    reset_login_failures(&throttle_keys).await;
+
+    let user = match identity {
+        auth::backend::Identity::Local(user) => user,
+        auth::backend::Identity::External(account) => {
+            resolve_external_user(&state, &account, "POST /login").await?
+        }
+    };
+
+    check_account_active(&user, "POST /login")?;
+
+    // second factor verification
+
+    let settings = DATABASE.get_security_settings().await.map_err(|err| {
+        log::error!(
+            "`{} ({})` [POST /login] got database error: {}",
+            user.username,
+            user.id,
+            err
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let required_by_role = settings.requires_2fa(&user.role);
+
+    if user.two_factor_enabled || required_by_role {
+        let challenge = if user.two_factor_enabled {
+            "totp"
+        } else {
+            "totp_enrollment"
+        };
+
+        let (challenge_token, claims) = state
+            .jwt_service
+            .generate_challenge_token(&user, challenge)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        // every challenge can be answered only once
+        DATABASE
+            .add_two_factor_challenge(database::token::TwoFactorChallengeRecord {
+                id: None,
+                jti: claims.jti,
+                user_id: user.id.clone(),
+                expires_at: surrealdb::sql::Datetime::from(
+                    chrono::DateTime::from_timestamp(claims.exp as i64, 0).unwrap_or_default(),
+                ),
+            })
+            .await
+            .map_err(|err| {
+                log::error!(
+                    "`{} ({})` [POST /login] got database error: {}",
+                    user.username,
+                    user.id,
+                    err
+                );
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+        log::info!(
+            "User `{} ({})` [POST /login] verified password, pending `{}` challenge",
+            user.username,
+            user.id,
+            challenge
+        );
+
+        return Ok((
+            StatusCode::ACCEPTED,
+            Json(auth::TwoFactorChallenge {
+                challenge_token,
+                challenge: challenge.to_string(),
+            }),
+        )
+            .into_response());
+    }
+
+    let session_id = start_session(&user, &headers, &client_ip, None).await?;
+    let response = issue_auth_response(&state, &user, session_id).await?;
+
+    log::info!(
+        "User `{} ({})` [POST /login] authenticated via JWT token",
+        user.username,
+        user.id
+    );
+
+    Ok(auth_reply(&state, response))
+}
+
+/// POST `/login/2fa`
+pub async fn login_2fa_handler(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(payload): Json<auth::TwoFactorRequest>,
+) -> Result<Response, StatusCode> {
+    let claims = state
+        .jwt_service
+        .verify_challenge_token(&payload.challenge_token)
+        .map_err(|_| StatusCode::UNAUTHORIZED)?;
+
+    let client_ip = middleware::client_ip(&headers, &addr);
+    let throttle_keys = [
+        auth::throttle::username_key(&claims.username),
+        auth::throttle::ip_key(&client_ip),
+    ];
+
+    if let Some(response) =
+        check_login_lock(&throttle_keys, &claims.username, &client_ip, "POST /login/2fa").await?
+    {
+        return Ok(response);
+    }
+
+    let challenge = DATABASE
+        .take_two_factor_challenge(&claims.jti)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [POST /login/2fa] got database error: {}",
+                claims.username,
+                claims.sub,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .filter(|challenge| challenge.user_id == claims.sub)
+        .ok_or_else(|| {
+            log::warn!(
+                "User `{} ({})` [POST /login/2fa] used already answered challenge",
+                claims.username,
+                claims.sub
+            );
+            StatusCode::UNAUTHORIZED
+        })?;
+
+    let record = DATABASE
+        .get_user(&claims.sub)
+        .await
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    // two-factor state changed since challenge was issued
+    let expected_challenge = if record.totp_enabled {
+        "totp"
+    } else {
+        "totp_enrollment"
+    };
+
+    if claims.challenge != expected_challenge {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    // enrollment must be confirmed with authenticator code, not recovery one
+    let recovery_code = if record.totp_enabled {
+        payload.recovery_code.as_deref()
+    } else {
+        None
+    };
+
+    if !verify_second_factor(&record, payload.code.as_deref(), recovery_code).await? {
+        register_login_failure(&throttle_keys, &claims.username, &client_ip, "POST /login/2fa")
+            .await;
+
+        // mistyped code can be retyped, challenge is used up by correct one only
+        if let Err(err) = DATABASE.add_two_factor_challenge(challenge).await {
+            log::error!(
+                "`{} ({})` [POST /login/2fa] got database error: {}",
+                claims.username,
+                claims.sub,
+                err
+            );
+        }
+
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    reset_login_failures(&throttle_keys).await;
+
+    if !record.totp_enabled {
+        DATABASE
+            .update_user_totp(
+                &claims.sub,
+                record.totp_secret.clone(),
+                true,
+                record.recovery_codes.clone(),
+            )
+            .await
+            .map_err(|err| {
+                log::error!(
+                    "`{} ({})` [POST /login/2fa] got database error: {}",
+                    claims.username,
+                    claims.sub,
+                    err
+                );
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+        log::info!(
+            "User `{} ({})` [POST /login/2fa] enabled two-factor authentication",
+            claims.username,
+            claims.sub
+        );
+    }
+
+    let user = state
         .user_repo
-        .verify_password(&payload.username, &payload.password)
+        .find_by_username(&record.username)
+        .await
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    check_account_active(&user, "POST /login/2fa")?;
+
+    let session_id = start_session(&user, &headers, &client_ip, None).await?;
+    let response = issue_auth_response(&state, &user, session_id).await?;
+
+    log::info!(
+        "User `{} ({})` [POST /login/2fa] authenticated via JWT token",
+        user.username,
+        user.id
+    );
+
+    Ok(auth_reply(&state, response))
+}
+
+/// Suspended and expired accounts can't log in (`403 FORBIDDEN`)
+fn check_account_active(user: &auth::user::User, endpoint: &str) -> Result<(), StatusCode> {
+    if user.is_active() {
+        return Ok(());
+    }
+
+    log::warn!(
+        "User `{} ({})` [{}] tried to log in to suspended or expired account",
+        user.username,
+        user.id,
+        endpoint
+    );
+
+    Err(StatusCode::FORBIDDEN)
+}
+
+/// Returns `429 TOO MANY REQUESTS` response if username or client IP is locked
+async fn check_login_lock(
+    throttle_keys: &[String],
+    username: &str,
+    client_ip: &str,
+    endpoint: &str,
+) -> Result<Option<Response>, StatusCode> {
+    let locked_for = auth::throttle::locked_for(throttle_keys)
+        .await
+        .map_err(|err| {
+            log::error!("[{}] got database error: {}", endpoint, err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(locked_for.map(|seconds| {
+        log::warn!(
+            "[{}] rejected locked login attempt for `{}` from {} (retry after {}s)",
+            endpoint,
+            username,
+            client_ip,
+            seconds
+        );
+
+        (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, seconds.to_string())],
+        )
+            .into_response()
+    }))
+}
+
+/// Counts failed attempt for username and client IP (errors are only logged)
+async fn register_login_failure(
+    throttle_keys: &[String],
+    username: &str,
+    client_ip: &str,
+    endpoint: &str,
+) {
+    log::warn!(
+        "[{}] failed login attempt for `{}` from {}",
+        endpoint,
+        username,
+        client_ip
+    );
+
+    for key in throttle_keys {
+        match auth::throttle::register_failure(key).await {
+            Ok(Some(seconds)) => log::warn!(
+                "[{}] too many failed login attempts, `{}` locked for {}s",
+                endpoint,
+                key,
+                seconds
+            ),
+            Ok(None) => {}
+            Err(err) => log::error!("[{}] got database error: {}", endpoint, err),
+        }
+    }
+}
+
+/// Resets failed attempts counters of username and client IP (errors are only logged)
+async fn reset_login_failures(throttle_keys: &[String]) {
+    for key in throttle_keys {
+        if let Err(err) = auth::throttle::reset(key).await {
+            log::error!("Unable to reset failed login attempts of `{}`: {}", key, err);
+        }
+    }
+}
+
+/// POST `/login/2fa/enroll`
+pub async fn login_2fa_enroll_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<auth::ChallengeRequest>,
+) -> Result<Json<auth::TotpEnrollment>, StatusCode> {
+    let claims = state
+        .jwt_service
+        .verify_challenge_token(&payload.challenge_token)
+        .map_err(|_| StatusCode::UNAUTHORIZED)?;
+
+    if claims.challenge != "totp_enrollment" {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let record = DATABASE
+        .get_user(&claims.sub)
+        .await
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    if record.totp_enabled {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let enrollment = start_totp_enrollment(&claims.sub, &record.username).await?;
+
+    log::info!(
+        "User `{} ({})` [POST /login/2fa/enroll] started two-factor enrollment",
+        claims.username,
+        claims.sub
+    );
+
+    Ok(Json(enrollment))
+}
+
+/// Generates new TOTP secret with recovery codes and stores them (not enabled yet)
+async fn start_totp_enrollment(
+    user_id: &str,
+    username: &str,
+) -> Result<auth::TotpEnrollment, StatusCode> {
+    let secret = auth::totp::generate_secret();
+    let otpauth_uri = auth::totp::provisioning_uri(&secret, username).map_err(|err| {
+        log::error!("`{} ({})` got TOTP error: {}", username, user_id, err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let (recovery_codes, recovery_hashes) = auth::totp::generate_recovery_codes();
+
+    DATABASE
+        .update_user_totp(user_id, Some(secret.clone()), false, recovery_hashes)
         .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` got database error while storing TOTP secret: {}",
+                username,
+                user_id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(auth::TotpEnrollment {
+        secret,
+        otpauth_uri,
+        recovery_codes,
+    })
+}
+
+/// Checks TOTP code or recovery code (used recovery code is removed, TOTP code is accepted once)
+async fn verify_second_factor(
+    record: &database::user::UserRecord,
+    code: Option<&str>,
+    recovery_code: Option<&str>,
+) -> Result<bool, StatusCode> {
+    let Some(secret) = &record.totp_secret else {
+        return Ok(false);
+    };
+
+    let user_id = record
+        .id
+        .as_ref()
+        .map(|id| id.id.to_string())
+        .unwrap_or_default();
+
+    if let Some(code) = code
+    && let Some(step) =
+        auth::totp::verify_code(secret, &record.username, code, record.totp_last_step)
     {
+        // code is accepted once, even by concurrent requests
+        return DATABASE.use_totp_step(&user_id, step).await.map_err(|err| {
+            log::error!(
+                "`{} ({})` got database error while using TOTP code: {}",
+                record.username,
+                user_id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        });
+    }
+
+    if let Some(recovery_code) = recovery_code {
+        let hash = auth::totp::hash_recovery_code(recovery_code);
+
+        if record.recovery_codes.contains(&hash) {
+
+            let remaining_codes = record
+                .recovery_codes
+                .iter()
+                .filter(|stored| **stored != hash)
+                .cloned()
+                .collect();
+
+            DATABASE
+                .update_user_totp(
+                    &user_id,
+                    record.totp_secret.clone(),
+                    record.totp_enabled,
+                    remaining_codes,
+                )
+                .await
+                .map_err(|err| {
+                    log::error!(
+                        "`{} ({})` got database error while using recovery code: {}",
+                        record.username,
+                        user_id,
+                        err
+                    );
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+
+            log::warn!(
+                "User `{} ({})` used two-factor recovery code",
+                record.username,
+                user_id
+            );
+
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// POST `/refresh`
+pub async fn refresh_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<auth::RefreshRequest>,
+) -> Result<Response, StatusCode> {
+    let refresh_token = if !payload.refresh_token.is_empty() {
+        payload.refresh_token
+    } else {
+        // cookie session mode: browser sends refresh token cookie by itself
+        let token = state
+            .cookies
+            .as_ref()
+            .and_then(|_| auth::cookie::get(&headers, auth::cookie::REFRESH_COOKIE))
+            .ok_or(StatusCode::UNAUTHORIZED)?;
+
+        if !auth::cookie::verify_csrf(&headers) {
+            log::warn!("[POST /refresh] rejected refresh token cookie without valid CSRF token");
+            return Err(StatusCode::FORBIDDEN);
+        }
+
+        token
+    };
+
+    let token_hash = auth::jwt::JwtService::hash_refresh_token(&refresh_token);
+
+    let token_record = DATABASE
+        .get_refresh_token(&token_hash)
+        .await
+        .map_err(|err| {
+            log::error!("[POST /refresh] got database error: {}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    // rotated token can be used only once, second use means it was stolen
+
+    let consumed = DATABASE
+        .consume_refresh_token(&token_hash)
+        .await
+        .map_err(|err| {
+            log::error!("[POST /refresh] got database error: {}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if consumed.is_none() {
+        log::warn!(
+            "`{} ({})` [POST /refresh] reused revoked refresh token, revoking chain `{}`",
+            token_record.username,
+            token_record.user_id,
+            token_record.family
+        );
+
+        let _ = DATABASE
+            .revoke_session(&token_record.family)
+            .await
+            .map_err(|err| {
+                log::error!("[POST /refresh] got database error: {}", err);
+            });
+
         return Err(StatusCode::UNAUTHORIZED);
     }
 
-    let token = state
-        .jwt_service
-        .generate_token(&user)
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if token_record.expires_at < surrealdb::Datetime::from(chrono::Utc::now()) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    // refresh chain is bound to session, which could be terminated
+
+    let session = DATABASE
+        .get_session(&token_record.family)
+        .await
+        .map_err(|err| {
+            log::error!("[POST /refresh] got database error: {}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let now = chrono::Utc::now();
+
+    if session.is_none_or(|session| session.revoked || session.expires_at.0 < now) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    // user could be renamed or deleted since login
+    let user = state
+        .user_repo
+        .find_by_id(&token_record.user_id)
+        .await
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    if !user.is_active() {
+        log::warn!(
+            "`{} ({})` [POST /refresh] account is suspended or expired, terminating session `{}`",
+            user.username,
+            user.id,
+            token_record.family
+        );
+
+        let _ = DATABASE
+            .revoke_session(&token_record.family)
+            .await
+            .map_err(|err| {
+                log::error!("[POST /refresh] got database error: {}", err);
+            });
+
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    // directory leavers lose access, role follows their current groups
+    let user = match state.user_repo.account_status(&user).await {
+        auth::backend::AccountStatus::Disabled => {
+            log::warn!(
+                "`{} ({})` [POST /refresh] external account is disabled, terminating session `{}`",
+                user.username,
+                user.id,
+                token_record.family
+            );
+
+            let _ = DATABASE
+                .revoke_session(&token_record.family)
+                .await
+                .map_err(|err| {
+                    log::error!("[POST /refresh] got database error: {}", err);
+                });
+
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+        auth::backend::AccountStatus::Active { role } if role != user.role => {
+            DATABASE
+                .update_user_external(
+                    &user.id,
+                    user.external_id.as_deref().unwrap_or_default(),
+                    &role,
+                )
+                .await
+                .map_err(|err| {
+                    log::error!("[POST /refresh] got database error: {}", err);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+
+            state.user_repo.invalidate(&user.id);
+
+            auth::user::User { role, ..user }
+        }
+        _ => user,
+    };
+
+    let response = issue_auth_response(&state, &user, token_record.family).await?;
 
     log::info!(
-        "User `{} ({})` [POST /login] authenticated via JWT token",
+        "User `{} ({})` [POST /refresh] refreshed JWT token",
         user.username,
         user.id
     );
 
-    Ok(Json(auth::AuthResponse {
-        token,
-        token_type: "Bearer".to_string(),
-    }))
+    Ok(auth_reply(&state, response))
 }
 
-// INFO: Watchers Routers
+/// GET `/oidc/authorize`
+pub async fn oidc_authorize_handler(
+    State(state): State<AppState>,
+) -> Result<Json<auth::oidc::OidcAuthorization>, StatusCode> {
+    let provider = state.oidc.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let login = provider.begin();
+
+    let authorization_url = provider.authorization_url(&login).await.map_err(|err| {
+        log::error!("[GET /oidc/authorize] {}", err);
+        StatusCode::BAD_GATEWAY
+    })?;
+
+    let expires_at = chrono::Utc::now()
+        + chrono::Duration::minutes(auth::oidc::LOGIN_EXPIRATION_MINUTES);
+
+    DATABASE
+        .add_oidc_login(database::oidc_login::OidcLoginRecord {
+            id: None,
+            state: login.state,
+            nonce: login.nonce,
+            code_verifier: login.code_verifier,
+            expires_at: surrealdb::sql::Datetime::from(expires_at),
+        })
+        .await
+        .map_err(|err| {
+            log::error!("[GET /oidc/authorize] got database error: {}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(auth::oidc::OidcAuthorization { authorization_url }))
+}
+
+/// POST `/oidc/callback`
+pub async fn oidc_callback_handler(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(payload): Json<auth::oidc::OidcCallbackRequest>,
+) -> Result<Response, StatusCode> {
+    let provider = state.oidc.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+
+    // every login can be completed only once
+    let login = DATABASE
+        .take_oidc_login(&payload.state)
+        .await
+        .map_err(|err| {
+            log::error!("[POST /oidc/callback] got database error: {}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    let login = auth::oidc::PendingLogin {
+        state: login.state,
+        nonce: login.nonce,
+        code_verifier: login.code_verifier,
+    };
+
+    let identity = provider
+        .exchange(&payload.code, &login)
+        .await
+        .map_err(|err| {
+            log::warn!("[POST /oidc/callback] {}", err);
+
+            match err {
+                auth::oidc::OidcError::Provider(_) => StatusCode::BAD_GATEWAY,
+                auth::oidc::OidcError::Rejected(_) => StatusCode::UNAUTHORIZED,
+            }
+        })?;
+
+    let user = resolve_external_user(
+        &state,
+        &provider.account(&identity),
+        "POST /oidc/callback",
+    )
+    .await?;
+
+    check_account_active(&user, "POST /oidc/callback")?;
+
+    // second factor is enforced by identity provider
+    let client_ip = middleware::client_ip(&headers, &addr);
+    let session_id = start_session(&user, &headers, &client_ip, None).await?;
+    let response = issue_auth_response(&state, &user, session_id).await?;
+
+    log::info!(
+        "User `{} ({})` [POST /oidc/callback] authenticated via identity provider",
+        user.username,
+        user.id
+    );
+
+    Ok(auth_reply(&state, response))
+}
+
+/// POST `/login/passkey`
+pub async fn login_passkey_handler(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(payload): Json<auth::webauthn::PasskeyLoginRequest>,
+) -> Result<Response, StatusCode> {
+    let webauthn = state.webauthn.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+
+    let client_ip = middleware::client_ip(&headers, &addr);
+    let throttle_keys = [
+        auth::throttle::username_key(&payload.username),
+        auth::throttle::ip_key(&client_ip),
+    ];
+
+    if let Some(response) = check_login_lock(
+        &throttle_keys,
+        &payload.username,
+        &client_ip,
+        "POST /login/passkey",
+    )
+    .await?
+    {
+        return Ok(response);
+    }
+
+    let database_error = |err: surrealdb::Error| {
+        log::error!(
+            "`{}` [POST /login/passkey] got database error: {}",
+            payload.username,
+            err
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+
+    // unknown username and user without passkeys must look the same
+    let Some(user) = state
+        .user_repo
+        .find_by_username(&payload.username)
+        .await
+        .filter(|user| user.external_id.is_none())
+    else {
+        register_login_failure(
+            &throttle_keys,
+            &payload.username,
+            &client_ip,
+            "POST /login/passkey",
+        )
+        .await;
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+
+    let passkeys = DATABASE
+        .list_user_passkeys(&user.id)
+        .await
+        .map_err(database_error)?
+        .iter()
+        .filter_map(|record| serde_json::from_str::<Passkey>(&record.passkey).ok())
+        .collect::<Vec<_>>();
+
+    if passkeys.is_empty() {
+        register_login_failure(
+            &throttle_keys,
+            &payload.username,
+            &client_ip,
+            "POST /login/passkey",
+        )
+        .await;
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    let (options, authentication) = webauthn
+        .start_passkey_authentication(&passkeys)
+        .map_err(|err| {
+            log::error!("[POST /login/passkey] unable to start authentication: {}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let ceremony_id = auth::webauthn::ceremony_id();
+    let expires_at = chrono::Utc::now()
+        + chrono::Duration::minutes(auth::webauthn::CEREMONY_EXPIRATION_MINUTES);
+
+    DATABASE
+        .add_passkey_ceremony(database::passkey::PasskeyCeremonyRecord {
+            id: None,
+            ceremony_id: ceremony_id.clone(),
+            kind: auth::webauthn::AUTHENTICATION.to_string(),
+            user_id: user.id,
+            username: user.username,
+            state: serde_json::to_string(&authentication)
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+            expires_at: surrealdb::sql::Datetime::from(expires_at),
+        })
+        .await
+        .map_err(database_error)?;
+
+    Ok(Json(auth::webauthn::PasskeyLoginChallenge {
+        ceremony_id,
+        options,
+    })
+    .into_response())
+}
+
+/// POST `/login/passkey/finish`
+pub async fn login_passkey_finish_handler(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(payload): Json<auth::webauthn::PasskeyLoginFinishRequest>,
+) -> Result<Response, StatusCode> {
+    let webauthn = state.webauthn.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+
+    let database_error = |err: surrealdb::Error| {
+        log::error!("[POST /login/passkey/finish] got database error: {}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+
+    // every challenge can be answered only once
+    let ceremony = DATABASE
+        .take_passkey_ceremony(&payload.ceremony_id, auth::webauthn::AUTHENTICATION)
+        .await
+        .map_err(database_error)?
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    let client_ip = middleware::client_ip(&headers, &addr);
+    let throttle_keys = [
+        auth::throttle::username_key(&ceremony.username),
+        auth::throttle::ip_key(&client_ip),
+    ];
+
+    if let Some(response) = check_login_lock(
+        &throttle_keys,
+        &ceremony.username,
+        &client_ip,
+        "POST /login/passkey/finish",
+    )
+    .await?
+    {
+        return Ok(response);
+    }
+
+    let authentication = serde_json::from_str::<PasskeyAuthentication>(&ceremony.state)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let result = match webauthn.finish_passkey_authentication(&payload.credential, &authentication)
+    {
+        Ok(result) => result,
+        Err(err) => {
+            log::warn!(
+                "`{} ({})` [POST /login/passkey/finish] passkey verification failed: {}",
+                ceremony.username,
+                ceremony.user_id,
+                err
+            );
+            register_login_failure(
+                &throttle_keys,
+                &ceremony.username,
+                &client_ip,
+                "POST /login/passkey/finish",
+            )
+            .await;
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+    };
+
+    let credential_id = general_purpose::URL_SAFE_NO_PAD.encode(result.cred_id());
+    let record = DATABASE
+        .get_passkey_by_credential_id(&credential_id)
+        .await
+        .map_err(database_error)?
+        .filter(|record| record.user_id == ceremony.user_id)
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    // signature counter and backup state are updated after every login
+    let mut passkey = serde_json::from_str::<Passkey>(&record.passkey)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    passkey.update_credential(&result);
+
+    let passkey = serde_json::to_string(&passkey).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let passkey_id = record
+        .id
+        .as_ref()
+        .map(|id| id.id.to_string())
+        .unwrap_or_default();
+
+    DATABASE
+        .use_passkey(&passkey_id, passkey)
+        .await
+        .map_err(database_error)?;
+
+    reset_login_failures(&throttle_keys).await;
+
+    let user = state
+        .user_repo
+        .find_by_username(&ceremony.username)
+        .await
+        .filter(|user| user.id == ceremony.user_id)
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    check_account_active(&user, "POST /login/passkey/finish")?;
+
+    // passkey requires user verification, so it replaces both password and second factor
+    let session_id = start_session(&user, &headers, &client_ip, None).await?;
+    let response = issue_auth_response(&state, &user, session_id).await?;
+
+    log::info!(
+        "User `{} ({})` [POST /login/passkey/finish] authenticated via passkey `{}`",
+        user.username,
+        user.id,
+        record.name
+    );
+
+    Ok(auth_reply(&state, response))
+}
+
+/// Finds user linked with external account, links or provisions one on first login. <br/>
+/// Role granted by directory or identity provider replaces current one on every login.
+async fn resolve_external_user(
+    state: &AppState,
+    account: &auth::backend::ExternalAccount,
+    endpoint: &str,
+) -> Result<auth::user::User, StatusCode> {
+    let username = account.username.as_str();
+    let external_id = account.external_id.as_str();
+
+    let database_error = |err: surrealdb::Error| {
+        log::error!("`{}` [{}] got database error: {}", username, endpoint, err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+
+    let Some(role) = account.role.as_deref() else {
+        log::warn!(
+            "`{}` [{}] external account `{}` has no mapped role",
+            username,
+            endpoint,
+            external_id
+        );
+        return Err(StatusCode::FORBIDDEN);
+    };
+
+    if DATABASE.get_role(role).await.map_err(database_error)?.is_none() {
+        log::error!(
+            "`{}` [{}] mapped role `{}` doesn't exist",
+            username,
+            endpoint,
+            role
+        );
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let record = match DATABASE
+        .get_user_by_external_id(external_id)
+        .await
+        .map_err(database_error)?
+    {
+        Some(record) if record.role == role => record,
+        // static admin's role is managed only by server configuration
+        Some(record) if record.protected => {
+            log::warn!(
+                "`{}` [{}] external account `{}` can't change role of protected user",
+                username,
+                endpoint,
+                external_id
+            );
+            return Err(StatusCode::CONFLICT);
+        }
+        Some(record) => {
+            let id = record.id.map(|id| id.id.to_string()).unwrap_or_default();
+
+            DATABASE
+                .update_user_external(&id, external_id, role)
+                .await
+                .map_err(database_error)?
+                .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?
+        }
+        None => match DATABASE
+            .get_user_by_username(username.to_owned())
+            .await
+            .map_err(database_error)?
+        {
+            Some(record) if record.protected => {
+                log::warn!(
+                    "`{}` [{}] external account `{}` can't be linked with protected user",
+                    username,
+                    endpoint,
+                    external_id
+                );
+                return Err(StatusCode::CONFLICT);
+            }
+            Some(record) if account.link_existing && record.external_id.is_none() => {
+                let id = record.id.map(|id| id.id.to_string()).unwrap_or_default();
+
+                log::info!(
+                    "`{} ({})` [{}] linked with external account `{}`",
+                    username,
+                    id,
+                    endpoint,
+                    external_id
+                );
+
+                DATABASE
+                    .update_user_external(&id, external_id, role)
+                    .await
+                    .map_err(database_error)?
+                    .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?
+            }
+            Some(_) => {
+                log::warn!(
+                    "`{}` [{}] username is taken by another account, external account `{}` is not linked",
+                    username,
+                    endpoint,
+                    external_id
+                );
+                return Err(StatusCode::CONFLICT);
+            }
+            None if account.auto_provision => {
+                let record = DATABASE
+                    .create_external_user(
+                        database::user::CreateUserRecord {
+                            username: username.to_owned(),
+                            password: auth::EXTERNAL_PASSWORD.to_owned(),
+                            role: role.to_owned(),
+                            created_by: String::from("system"),
+                            expires_at: None,
+                        },
+                        external_id,
+                    )
+                    .await
+                    .map_err(database_error)?
+                    .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+                log::info!(
+                    "`{}` [{}] provisioned for external account `{}` with role `{}`",
+                    username,
+                    endpoint,
+                    external_id,
+                    role
+                );
+
+                record
+            }
+            None => {
+                log::warn!(
+                    "`{}` [{}] has no account and auto-provisioning is disabled",
+                    username,
+                    endpoint
+                );
+                return Err(StatusCode::FORBIDDEN);
+            }
+        },
+    };
+
+    let id = record
+        .id
+        .map(|id| id.id.to_string())
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    state.user_repo.invalidate(&id);
+
+    state
+        .user_repo
+        .find_by_id(&id)
+        .await
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Creates new session record for successfully authenticated user
+async fn start_session(
+    user: &auth::user::User,
+    headers: &HeaderMap,
+    client_ip: &str,
+    impersonated_by: Option<String>,
+) -> Result<String, StatusCode> {
+    let now = surrealdb::sql::Datetime::from(chrono::Utc::now());
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    let session = DATABASE
+        .create_session(database::session::SessionRecord {
+            id: None,
+            user_id: user.id.clone(),
+            username: user.username.clone(),
+            user_agent: user_agent.to_string(),
+            ip: client_ip.to_string(),
+            revoked: false,
+            creation_datetime: now.clone(),
+            last_seen: now.clone(),
+            expires_at: now,
+            impersonated_by,
+        })
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` got database error while creating session: {}",
+                user.username,
+                user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    session
+        .and_then(|session| session.id)
+        .map(|id| id.id.to_string())
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Sends auth response, in cookie session mode tokens are moved to `HttpOnly` cookies
+fn auth_reply(state: &AppState, mut response: auth::AuthResponse) -> Response {
+    let Some(cookies) = &state.cookies else {
+        return Json(response).into_response();
+    };
+
+    let lifetime = state.jwt_service.refresh_expiration().num_seconds();
+    let set_cookies = cookies
+        .session_cookies(&response.token, &response.refresh_token, lifetime)
+        .map(|cookie| (header::SET_COOKIE, cookie));
+
+    response.token.clear();
+    response.refresh_token.clear();
+
+    (AppendHeaders(set_cookies), Json(response)).into_response()
+}
+
+/// Generates access token and stores new refresh token in session's chain
+async fn issue_auth_response(
+    state: &AppState,
+    user: &auth::user::User,
+    session_id: String,
+) -> Result<auth::AuthResponse, StatusCode> {
+    let token = state
+        .jwt_service
+        .generate_token(user, &session_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let refresh_token = state.jwt_service.generate_refresh_token();
+    let now = chrono::Utc::now();
+    let expires_at = surrealdb::Datetime::from(now + state.jwt_service.refresh_expiration());
+
+    DATABASE
+        .add_refresh_token(database::token::RefreshTokenRecord {
+            id: None,
+            token_hash: auth::jwt::JwtService::hash_refresh_token(&refresh_token),
+            family: session_id.clone(),
+            user_id: user.id.clone(),
+            username: user.username.clone(),
+            revoked: false,
+            expires_at: expires_at.clone(),
+            creation_datetime: surrealdb::Datetime::from(now),
+        })
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` got database error while storing refresh token: {}",
+                user.username,
+                user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    DATABASE
+        .extend_session(&session_id, expires_at)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` got database error while extending session: {}",
+                user.username,
+                user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(auth::AuthResponse {
+        token,
+        token_type: "Bearer".to_string(),
+        expires_in: state.jwt_service.expiration().num_seconds(),
+        refresh_token,
+        password_change_required: user.password_change_required(),
+    })
+}
+
+// INFO: Watchers Routers
+
+/// POST `/logout`
+pub async fn logout_handler(
+    State(state): State<AppState>,
+    auth_user: middleware::AuthUser,
+) -> Result<Response, StatusCode> {
+    // personal access tokens are revoked with `DELETE /me/tokens/{id}`
+    let session_id = auth_user
+        .session_id
+        .clone()
+        .ok_or(StatusCode::BAD_REQUEST)?;
+
+    let expires_at = chrono::DateTime::from_timestamp(auth_user.token_expiration as i64, 0)
+        .unwrap_or_else(chrono::Utc::now);
+
+    DATABASE
+        .revoke_access_token(&auth_user.token_id, surrealdb::Datetime::from(expires_at))
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [POST /logout] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    DATABASE
+        .revoke_session(&session_id)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [POST /logout] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    // good moment to clean up outdated tokens
+    let _ = DATABASE.purge_expired_tokens().await.map_err(|err| {
+        log::error!("[POST /logout] got database error while purging tokens: {}", err);
+    });
+
+    log::info!(
+        "`{} ({})` [POST /logout] logged out",
+        auth_user.username,
+        auth_user.id
+    );
+
+    // impersonation token isn't kept in cookies, admin's own session stays
+    match &state.cookies {
+        Some(cookies) if auth_user.impersonated_by.is_none() => {
+            let set_cookies = cookies
+                .clear_cookies()
+                .map(|cookie| (header::SET_COOKIE, cookie));
+
+            Ok((StatusCode::OK, AppendHeaders(set_cookies)).into_response())
+        }
+        _ => Ok(StatusCode::OK.into_response()),
+    }
+}
+
+/// GET `/me/sessions`
+pub async fn me_sessions_handler(
+    auth_user: middleware::AuthUser,
+) -> Result<Json<Vec<database::session::SessionRecord>>, StatusCode> {
+    let sessions = DATABASE
+        .list_user_sessions(&auth_user.id)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [GET /me/sessions] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(sessions))
+}
+
+/// DELETE `/me/sessions/{id}`
+pub async fn me_sessions_id_delete_handler(
+    auth_user: middleware::AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<database::session::SessionRecord>, StatusCode> {
+    terminate_session(&auth_user, &auth_user.id, &id, "DELETE /me/sessions/{id}").await
+}
+
+/// Revokes session if it belongs to provided user
+async fn terminate_session(
+    auth_user: &middleware::AuthUser,
+    owner_id: &str,
+    session_id: &str,
+    endpoint: &str,
+) -> Result<Json<database::session::SessionRecord>, StatusCode> {
+    let session = DATABASE
+        .get_session(session_id)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [{}] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                endpoint,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .filter(|session| session.user_id == owner_id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    DATABASE.revoke_session(session_id).await.map_err(|err| {
+        log::error!(
+            "`{} ({})` [{}] got database error: {}",
+            auth_user.username,
+            auth_user.id,
+            endpoint,
+            err
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    log::info!(
+        "`{} ({})` [{}] terminated session `{}` of `{} ({})`",
+        auth_user.username,
+        auth_user.id,
+        endpoint,
+        session_id,
+        session.username,
+        session.user_id
+    );
+
+    Ok(Json(session))
+}
+
+/// GET `/me/tokens`
+pub async fn me_tokens_handler(
+    auth_user: middleware::AuthUser,
+) -> Result<Json<Vec<database::api_token::ApiTokenRecord>>, StatusCode> {
+    let tokens = DATABASE
+        .list_user_api_tokens(&auth_user.id)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [GET /me/tokens] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(tokens))
+}
+
+/// POST `/me/tokens`
+pub async fn me_tokens_create_handler(
+    auth_user: middleware::AuthUser,
+    Json(payload): Json<database::api_token::CreateApiTokenRecord>,
+) -> Result<Json<database::api_token::CreatedApiToken>, StatusCode> {
+    if payload.name.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let role = find_role(&auth_user, &payload.role, "POST /me/tokens")
+        .await?
+        .ok_or(StatusCode::BAD_REQUEST)?;
+
+    // token scope can't be wider than owner's permissions
+    if !role
+        .permissions
+        .iter()
+        .all(|permission| auth_user.can(*permission))
+    {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    if payload
+        .expires_at
+        .as_ref()
+        .is_some_and(|expires_at| expires_at.0 <= chrono::Utc::now())
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let token = auth::api_token::generate();
+
+    let record = database::api_token::ApiTokenRecord {
+        id: None,
+        name: payload.name.trim().to_owned(),
+        user_id: auth_user.id.clone(),
+        username: auth_user.username.clone(),
+        token_hash: auth::api_token::hash(&token),
+        prefix: auth::api_token::display_prefix(&token),
+        role: payload.role,
+        creation_datetime: surrealdb::sql::Datetime::default(),
+        expires_at: payload.expires_at,
+        last_used: None,
+    };
+
+    let record = DATABASE
+        .add_api_token(record)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [POST /me/tokens] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    log::info!(
+        "`{} ({})` [POST /me/tokens] created personal access token `{}` ({})",
+        auth_user.username,
+        auth_user.id,
+        record.name,
+        record.role
+    );
+
+    Ok(Json(database::api_token::CreatedApiToken { token, record }))
+}
+
+/// DELETE `/me/tokens/{id}`
+pub async fn me_tokens_id_delete_handler(
+    auth_user: middleware::AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<database::api_token::ApiTokenRecord>, StatusCode> {
+    DATABASE
+        .get_api_token(&id)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [DELETE /me/tokens/{{id}}] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .filter(|token| token.user_id == auth_user.id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let record = DATABASE
+        .delete_api_token(&id)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [DELETE /me/tokens/{{id}}] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    log::info!(
+        "`{} ({})` [DELETE /me/tokens/{{id}}] revoked personal access token `{}`",
+        auth_user.username,
+        auth_user.id,
+        record.name
+    );
+
+    Ok(Json(record))
+}
+
+/// GET `/me/passkeys`
+pub async fn me_passkeys_handler(
+    auth_user: middleware::AuthUser,
+) -> Result<Json<Vec<database::passkey::PasskeyRecord>>, StatusCode> {
+    let passkeys = DATABASE
+        .list_user_passkeys(&auth_user.id)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [GET /me/passkeys] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(
+        passkeys
+            .into_iter()
+            .map(database::passkey::PasskeyRecord::redacted)
+            .collect(),
+    ))
+}
+
+/// POST `/me/passkeys/register`
+pub async fn me_passkeys_register_handler(
+    State(state): State<AppState>,
+    auth_user: middleware::AuthUser,
+) -> Result<Json<auth::webauthn::PasskeyRegistrationChallenge>, StatusCode> {
+    let webauthn = state.webauthn.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+
+    let database_error = |err: surrealdb::Error| {
+        log::error!(
+            "`{} ({})` [POST /me/passkeys/register] got database error: {}",
+            auth_user.username,
+            auth_user.id,
+            err
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+
+    let user = state
+        .user_repo
+        .find_by_id(&auth_user.id)
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    // login of external accounts is managed by identity provider
+    if user.external_id.is_some() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    // same authenticator can't be registered twice
+    let exclude_credentials = DATABASE
+        .list_user_passkeys(&auth_user.id)
+        .await
+        .map_err(database_error)?
+        .iter()
+        .filter_map(|record| serde_json::from_str::<Passkey>(&record.passkey).ok())
+        .map(|passkey| passkey.cred_id().clone())
+        .collect::<Vec<_>>();
+
+    let (options, registration) = webauthn
+        .start_passkey_registration(
+            auth::webauthn::user_handle(&auth_user.id),
+            &auth_user.username,
+            &auth_user.username,
+            Some(exclude_credentials),
+        )
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [POST /me/passkeys/register] unable to start registration: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let ceremony_id = auth::webauthn::ceremony_id();
+    let expires_at = chrono::Utc::now()
+        + chrono::Duration::minutes(auth::webauthn::CEREMONY_EXPIRATION_MINUTES);
+
+    DATABASE
+        .add_passkey_ceremony(database::passkey::PasskeyCeremonyRecord {
+            id: None,
+            ceremony_id: ceremony_id.clone(),
+            kind: auth::webauthn::REGISTRATION.to_string(),
+            user_id: auth_user.id.clone(),
+            username: auth_user.username.clone(),
+            state: serde_json::to_string(&registration)
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+            expires_at: surrealdb::sql::Datetime::from(expires_at),
+        })
+        .await
+        .map_err(database_error)?;
+
+    Ok(Json(auth::webauthn::PasskeyRegistrationChallenge {
+        ceremony_id,
+        options,
+    }))
+}
+
+/// POST `/me/passkeys`
+pub async fn me_passkeys_create_handler(
+    State(state): State<AppState>,
+    auth_user: middleware::AuthUser,
+    Json(payload): Json<auth::webauthn::PasskeyRegistrationRequest>,
+) -> Result<Json<database::passkey::PasskeyRecord>, StatusCode> {
+    let webauthn = state.webauthn.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+
+    if payload.name.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let database_error = |err: surrealdb::Error| {
+        log::error!(
+            "`{} ({})` [POST /me/passkeys] got database error: {}",
+            auth_user.username,
+            auth_user.id,
+            err
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+
+    let ceremony = DATABASE
+        .take_passkey_ceremony(&payload.ceremony_id, auth::webauthn::REGISTRATION)
+        .await
+        .map_err(database_error)?
+        .filter(|ceremony| ceremony.user_id == auth_user.id)
+        .ok_or(StatusCode::BAD_REQUEST)?;
+
+    let registration = serde_json::from_str::<PasskeyRegistration>(&ceremony.state)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let passkey = webauthn
+        .finish_passkey_registration(&payload.credential, &registration)
+        .map_err(|err| {
+            log::warn!(
+                "`{} ({})` [POST /me/passkeys] passkey registration failed: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::BAD_REQUEST
+        })?;
+
+    let record = database::passkey::PasskeyRecord {
+        id: None,
+        user_id: auth_user.id.clone(),
+        username: auth_user.username.clone(),
+        name: payload.name.trim().to_owned(),
+        credential_id: general_purpose::URL_SAFE_NO_PAD.encode(passkey.cred_id()),
+        passkey: serde_json::to_string(&passkey).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+        creation_datetime: surrealdb::sql::Datetime::default(),
+        last_used: None,
+    };
+
+    let record = DATABASE
+        .add_passkey(record)
+        .await
+        .map_err(database_error)?
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    log::info!(
+        "`{} ({})` [POST /me/passkeys] registered passkey `{}`",
+        auth_user.username,
+        auth_user.id,
+        record.name
+    );
+
+    Ok(Json(record.redacted()))
+}
+
+/// PATCH `/me/passkeys/{id}`
+pub async fn me_passkeys_id_patch_handler(
+    auth_user: middleware::AuthUser,
+    Path(id): Path<String>,
+    Json(payload): Json<database::passkey::RenamePasskeyRecord>,
+) -> Result<Json<database::passkey::PasskeyRecord>, StatusCode> {
+    if payload.name.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let database_error = |err: surrealdb::Error| {
+        log::error!(
+            "`{} ({})` [PATCH /me/passkeys/{{id}}] got database error: {}",
+            auth_user.username,
+            auth_user.id,
+            err
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+
+    DATABASE
+        .get_passkey(&id)
+        .await
+        .map_err(database_error)?
+        .filter(|passkey| passkey.user_id == auth_user.id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let record = DATABASE
+        .rename_passkey(&id, payload.name.trim())
+        .await
+        .map_err(database_error)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(record.redacted()))
+}
+
+/// DELETE `/me/passkeys/{id}`
+pub async fn me_passkeys_id_delete_handler(
+    auth_user: middleware::AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<database::passkey::PasskeyRecord>, StatusCode> {
+    let database_error = |err: surrealdb::Error| {
+        log::error!(
+            "`{} ({})` [DELETE /me/passkeys/{{id}}] got database error: {}",
+            auth_user.username,
+            auth_user.id,
+            err
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+
+    DATABASE
+        .get_passkey(&id)
+        .await
+        .map_err(database_error)?
+        .filter(|passkey| passkey.user_id == auth_user.id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let record = DATABASE
+        .delete_passkey(&id)
+        .await
+        .map_err(database_error)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    log::info!(
+        "`{} ({})` [DELETE /me/passkeys/{{id}}] revoked passkey `{}`",
+        auth_user.username,
+        auth_user.id,
+        record.name
+    );
+
+    Ok(Json(record.redacted()))
+}
+
+/// GET `/me`
+pub async fn me_handler(
+    auth_user: middleware::AuthUser,
+) -> Result<Json<database::user::UserRecord>, StatusCode> {
+    let user = DATABASE.get_user(auth_user.id).await;
+
+    match user {
+        Some(record) => Ok(Json(record.redacted())),
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+/// POST `/me/password`
+pub async fn me_password_handler(
+    State(state): State<AppState>,
+    auth_user: middleware::AuthUser,
+    Json(payload): Json<database::user::ChangePasswordRecord>,
+) -> Result<StatusCode, StatusCode> {
+    if !state
+        .user_repo
+        .verify_password(&auth_user.username, &payload.current_password)
+        .await
+    {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    if payload.new_password == payload.current_password {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    check_password_policy(
+        &auth_user,
+        &auth_user.username,
+        &payload.new_password,
+        "POST /me/password",
+    )?;
+
+    let password_hash =
+        auth::UserRepository::hash_password(&payload.new_password).map_err(|err| {
+            log::error!(
+                "`{} ({})` [POST /me/password] got HASHING ERROR: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    DATABASE
+        .change_user_password(&auth_user.id, password_hash, false)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [POST /me/password] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    state.user_repo.invalidate(&auth_user.id);
+
+    // other sessions could be opened with old password
+    let sessions = DATABASE
+        .list_user_sessions(&auth_user.id)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [POST /me/password] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    for session in sessions {
+        let session_id = session
+            .id
+            .map(|id| id.id.to_string())
+            .unwrap_or_default();
+
+        if Some(&session_id) == auth_user.session_id.as_ref() {
+            continue;
+        }
+
+        let _ = DATABASE.revoke_session(&session_id).await.map_err(|err| {
+            log::error!(
+                "`{} ({})` [POST /me/password] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+        });
+    }
+
+    // personal access tokens could be created by whoever knew old password
+    let revoked_tokens = DATABASE
+        .delete_user_api_tokens(&auth_user.id)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [POST /me/password] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    log::info!(
+        "`{} ({})` [POST /me/password] changed password, revoked {} personal access tokens",
+        auth_user.username,
+        auth_user.id,
+        revoked_tokens.len()
+    );
+
+    Ok(StatusCode::OK)
+}
+
+/// Checks new password against [password policy](auth::password_policy::PasswordPolicy)
+fn check_password_policy(
+    auth_user: &middleware::AuthUser,
+    username: &str,
+    password: &str,
+    endpoint: &str,
+) -> Result<(), StatusCode> {
+    auth::password_policy::POLICY
+        .check(username, password)
+        .map_err(|violation| {
+            log::info!(
+                "`{} ({})` [{}] password rejected by policy: {:?}",
+                auth_user.username,
+                auth_user.id,
+                endpoint,
+                violation
+            );
+            StatusCode::BAD_REQUEST
+        })
+}
+
+/// GET `/me/permissions`
+pub async fn me_permissions_handler(
+    auth_user: middleware::AuthUser,
+) -> Json<Vec<auth::permission::Permission>> {
+    Json(auth_user.permissions)
+}
+
+/// POST `/me/2fa/enroll`
+pub async fn me_2fa_enroll_handler(
+    State(state): State<AppState>,
+    auth_user: middleware::AuthUser,
+    Json(payload): Json<auth::PasswordConfirmation>,
+) -> Result<Json<auth::TotpEnrollment>, StatusCode> {
+    // stolen access token must not be enough to replace owner's second factor
+    if !state
+        .user_repo
+        .verify_password(&auth_user.username, &payload.password)
+        .await
+    {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let record = DATABASE
+        .get_user(&auth_user.id)
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if record.totp_enabled {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let enrollment = start_totp_enrollment(&auth_user.id, &record.username).await?;
+
+    log::info!(
+        "`{} ({})` [POST /me/2fa/enroll] started two-factor enrollment",
+        auth_user.username,
+        auth_user.id
+    );
+
+    Ok(Json(enrollment))
+}
+
+/// POST `/me/2fa/confirm`
+pub async fn me_2fa_confirm_handler(
+    auth_user: middleware::AuthUser,
+    Json(payload): Json<auth::TotpCodeRequest>,
+) -> Result<StatusCode, StatusCode> {
+    let record = DATABASE
+        .get_user(&auth_user.id)
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if record.totp_enabled {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    if record.totp_secret.is_none() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if !verify_second_factor(&record, Some(&payload.code), None).await? {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    DATABASE
+        .update_user_totp(
+            &auth_user.id,
+            record.totp_secret,
+            true,
+            record.recovery_codes,
+        )
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [POST /me/2fa/confirm] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    log::info!(
+        "`{} ({})` [POST /me/2fa/confirm] enabled two-factor authentication",
+        auth_user.username,
+        auth_user.id
+    );
+
+    Ok(StatusCode::OK)
+}
+
+/// DELETE `/me/2fa`
+pub async fn me_2fa_delete_handler(
+    auth_user: middleware::AuthUser,
+    Json(payload): Json<auth::TotpCodeRequest>,
+) -> Result<StatusCode, StatusCode> {
+    let record = DATABASE
+        .get_user(&auth_user.id)
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if !record.totp_enabled {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let settings = DATABASE.get_security_settings().await.map_err(|err| {
+        log::error!(
+            "`{} ({})` [DELETE /me/2fa] got database error: {}",
+            auth_user.username,
+            auth_user.id,
+            err
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if settings.requires_2fa(&record.role) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    if !verify_second_factor(&record, Some(&payload.code), Some(&payload.code)).await? {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    DATABASE
+        .update_user_totp(&auth_user.id, None, false, Vec::new())
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [DELETE /me/2fa] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    log::info!(
+        "`{} ({})` [DELETE /me/2fa] disabled two-factor authentication",
+        auth_user.username,
+        auth_user.id
+    );
+
+    Ok(StatusCode::OK)
+}
+
+/// GET `/persons`
+pub async fn persons_handler(
+    auth_user: middleware::AuthUser,
+    Query(filter): Query<database::person::FilterPersonRecord>,
+) -> Result<Json<database::person::PersonPage>, StatusCode> {
+    auth_user.require(auth::permission::Permission::PersonsRead)?;
+
+    let (birthday_from, birthday_to) = filter
+        .birthday_range()
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    let page = DATABASE
+        .filter_persons(&filter, birthday_from, birthday_to)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [GET /persons] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(page))
+}
+
+/// GET `/persons/search`
+pub async fn persons_search_handler(
+    auth_user: middleware::AuthUser,
+    Query(search): Query<database::person::SearchPersonRecord>,
+) -> Result<Json<database::person::PersonPage>, StatusCode> {
+    auth_user.require(auth::permission::Permission::PersonsRead)?;
+
+    if search.search_query.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let page = DATABASE.search_persons(&search).await.map_err(|err| {
+        log::error!(
+            "`{} ({})` [GET /persons/search] [QUERY: '{}'] got database error: {}",
+            auth_user.username,
+            auth_user.id,
+            search.search_query,
+            err
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(page))
+}
+
+/// GET `/persons/search/text`
+pub async fn persons_search_text_handler(
+    auth_user: middleware::AuthUser,
+    Query(search): Query<database::person::TextSearchPersonRecord>,
+) -> Result<Json<database::person::PersonPage<database::person::PersonSearchHit>>, StatusCode> {
+    auth_user.require(auth::permission::Permission::PersonsRead)?;
+
+    if search.search_query.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let page = DATABASE.text_search_persons(&search).await.map_err(|err| {
+        log::error!(
+            "`{} ({})` [GET /persons/search/text] [QUERY: '{}'] got database error: {}",
+            auth_user.username,
+            auth_user.id,
+            search.search_query,
+            err
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(page))
+}
+
+/// GET `/persons/{id}`
+pub async fn persons_id_handler(
+    auth_user: middleware::AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<database::person::PersonRecord>, StatusCode> {
+    auth_user.require(auth::permission::Permission::PersonsRead)?;
+
+    match DATABASE.get_person(id).await {
+        Some(record) => Ok(Json(record)),
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+pub async fn media_handler(
+    auth_user: middleware::AuthUser,
+    Path(hash): Path<String>,
+) -> impl IntoResponse {
+    if !auth_user.can(auth::permission::Permission::PersonsRead) {
+        return Err((StatusCode::FORBIDDEN, "Not enough permissions".to_string()));
+    }
+
+    uploads::get_photo(hash).await
+}
+
+// INFO: Editors Routers
+
+pub async fn upload_handler(
+    auth_user: middleware::AuthUser,
+    multipart: Multipart,
+) -> Result<Json<String>, (StatusCode, String)> {
+    if !auth_user.can(auth::permission::Permission::MediaUpload) {
+        return Err((StatusCode::FORBIDDEN, "Not enough permissions".to_string()));
+    }
+
+    let result = uploads::upload_photo(multipart).await?;
+
+    log::info!(
+        "`{} ({})` uploaded photo with hash: '{}'",
+        auth_user.username,
+        auth_user.id,
+        result.0
+    );
+
+    Ok(result)
+}
+
+/// POST `/persons/create`
+pub async fn persons_create_handler(
+    auth_user: middleware::AuthUser,
+    new_record: Json<database::person::CreatePersonRecord>,
+) -> Result<Json<database::person::PersonRecord>, StatusCode> {
+    auth_user.require(auth::permission::Permission::PersonsWriteOwn)?;
+
+    // unique record verification
+    if !DATABASE
+        .find_person(format!(
+            "{} {} {}",
+            new_record.surname, new_record.name, new_record.patronymic
+        ))
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [POST /persons/create] got database verification error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .is_empty()
+    {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let option_record = DATABASE
+        .add_person(new_record.0, &auth_user.username)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [POST /persons/create] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if option_record.is_none() {
+        log::error!(
+            "`{} ({})` [POST /persons/create] got empty database response",
+            auth_user.username,
+            auth_user.id
+        );
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    let person_record = option_record.unwrap();
+
+    log::info!(
+        "`{} ({})` [POST /persons/create] created person record: {} {} {}",
+        auth_user.username,
+        auth_user.id,
+        person_record.name,
+        person_record.surname,
+        person_record.patronymic
+    );
+
+    Ok(Json(person_record))
+}
+
+/// PATCH `/persons/{id}`
+pub async fn persons_patch_handler(
+    auth_user: middleware::AuthUser,
+    Path(id): Path<String>,
+    patched: Json<database::person::CreatePersonRecord>,
+) -> Result<Json<database::person::PersonRecord>, StatusCode> {
+    auth_user.require_any(&[auth::permission::Permission::PersonsWriteOwn, auth::permission::Permission::PersonsWriteAny])?;
+
+    let person_record = DATABASE.get_person(&id).await;
+
+    match person_record {
+        Some(record) => {
+            // verifying if we have access
+
+            if !auth_user.can(auth::permission::Permission::PersonsWriteAny) && record.author != auth_user.username {
+                return Err(StatusCode::FORBIDDEN);
+            }
+
+            let record = DATABASE
+                .update_person(id, patched.0.clone(), &auth_user.username)
+                .await
+                .map_err(|err| {
+                    log::error!(
+                        "`{} ({})` [PATCH /persons/{{id}}] got database error: {}",
+                        auth_user.username,
+                        auth_user.id,
+                        err
+                    );
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?
+                // moved to trash after it was read
+                .ok_or(StatusCode::NOT_FOUND)?;
+
+            log::info!(
+                "`{} ({})` [PATCH /persons/{{id}}] updated `{} {} {}` -> `{} {} {}`",
+                auth_user.username,
+                auth_user.id,
+                record.surname,
+                record.name,
+                record.patronymic,
+                patched.surname,
+                patched.name,
+                patched.patronymic
+            );
+
+            Ok(Json(record))
+        }
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+/// DELETE `/persons/{id}`
+pub async fn persons_delete_handler(
+    auth_user: middleware::AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<database::person::PersonRecord>, StatusCode> {
+    auth_user.require_any(&[auth::permission::Permission::PersonsDeleteOwn, auth::permission::Permission::PersonsDeleteAny])?;
+
+    let person_record = DATABASE.get_person(&id).await;
+
+    match person_record {
+        Some(record) => {
+            // verifying if we have access
+
+            if !auth_user.can(auth::permission::Permission::PersonsDeleteAny) && record.author != auth_user.username {
+                return Err(StatusCode::FORBIDDEN);
+            }
+
+            DATABASE
+                .trash_person(id, &auth_user.username)
+                .await
+                .map_err(|err| {
+                    log::error!(
+                        "`{} ({})` [DELETE /persons/{{id}}] got database error: {}",
+                        auth_user.username,
+                        auth_user.id,
+                        err
+                    );
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?
+                .ok_or(StatusCode::NOT_FOUND)?;
+
+            log::info!(
+                "`{} ({})` [DELETE /persons/{{id}}] moved to trash `{} {} {}`",
+                auth_user.username,
+                auth_user.id,
+                record.surname,
+                record.name,
+                record.patronymic
+            );
+
+            Ok(Json(record))
+        }
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+/// History is shown for persons in trash only to users who manage trash, `404 NOT FOUND` for others
+async fn check_person_history_access(
+    auth_user: &middleware::AuthUser,
+    id: &str,
+) -> Result<(), StatusCode> {
+    if DATABASE.get_person(id).await.is_none()
+        && !auth_user.can(auth::permission::Permission::PersonsDeleteAny)
+    {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    Ok(())
+}
+
+/// GET `/persons/{id}/revisions`
+pub async fn persons_id_revisions_handler(
+    auth_user: middleware::AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<database::revision::PersonRevisionRecord>>, StatusCode> {
+    auth_user.require(auth::permission::Permission::PersonsRead)?;
+    check_person_history_access(&auth_user, &id).await?;
+
+    let revisions = DATABASE.list_person_revisions(&id).await.map_err(|err| {
+        log::error!(
+            "`{} ({})` [GET /persons/{{id}}/revisions] got database error: {}",
+            auth_user.username,
+            auth_user.id,
+            err
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if revisions.is_empty() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    Ok(Json(revisions))
+}
+
+/// Revision with snapshot or `404 NOT FOUND`
+async fn find_person_revision(
+    auth_user: &middleware::AuthUser,
+    id: &str,
+    revision: u64,
+    endpoint: &str,
+) -> Result<database::revision::PersonRevisionRecord, StatusCode> {
+    DATABASE
+        .get_person_revision(id, revision)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [{}] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                endpoint,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .filter(|revision| revision.snapshot.is_some())
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// GET `/persons/{id}/revisions/{revision}`
+pub async fn persons_id_revision_handler(
+    auth_user: middleware::AuthUser,
+    Path((id, revision)): Path<(String, u64)>,
+) -> Result<Json<database::revision::PersonRevisionRecord>, StatusCode> {
+    auth_user.require(auth::permission::Permission::PersonsRead)?;
+    check_person_history_access(&auth_user, &id).await?;
+
+    let revision = find_person_revision(
+        &auth_user,
+        &id,
+        revision,
+        "GET /persons/{id}/revisions/{revision}",
+    )
+    .await?;
+
+    Ok(Json(revision))
+}
+
+/// GET `/persons/{id}/revisions/diff`
+pub async fn persons_id_revisions_diff_handler(
+    auth_user: middleware::AuthUser,
+    Path(id): Path<String>,
+    Query(diff): Query<database::revision::RevisionDiffQuery>,
+) -> Result<Json<Vec<database::revision::PersonFieldChange>>, StatusCode> {
+    auth_user.require(auth::permission::Permission::PersonsRead)?;
+    check_person_history_access(&auth_user, &id).await?;
+
+    const ENDPOINT: &str = "GET /persons/{id}/revisions/diff";
+
+    let from = find_person_revision(&auth_user, &id, diff.from, ENDPOINT).await?;
+    let to = find_person_revision(&auth_user, &id, diff.to, ENDPOINT).await?;
+
+    let (Some(before), Some(after)) = (&from.snapshot, &to.snapshot) else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+
+    let mut changes = database::revision::diff_persons(before, after).map_err(|err| {
+        log::error!(
+            "`{} ({})` [{}] got serialization error: {}",
+            auth_user.username,
+            auth_user.id,
+            ENDPOINT,
+            err
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    changes.extend(database::revision::diff_tags(&from.tags, &to.tags));
+
+    Ok(Json(changes))
+}
+
+/// POST `/persons/{id}/revisions/{revision}/revert`
+pub async fn persons_id_revision_revert_handler(
+    auth_user: middleware::AuthUser,
+    Path((id, revision)): Path<(String, u64)>,
+) -> Result<Json<database::person::PersonRecord>, StatusCode> {
+    auth_user.require_any(&[auth::permission::Permission::PersonsWriteOwn, auth::permission::Permission::PersonsWriteAny])?;
+
+    const ENDPOINT: &str = "POST /persons/{id}/revisions/{revision}/revert";
+
+    let record = DATABASE.get_person(&id).await.ok_or(StatusCode::NOT_FOUND)?;
+
+    // verifying if we have access
+
+    if !auth_user.can(auth::permission::Permission::PersonsWriteAny) && record.author != auth_user.username {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let revision = find_person_revision(&auth_user, &id, revision, ENDPOINT).await?;
+
+    let reverted = DATABASE
+        .revert_person(&id, &revision, &auth_user.username)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [{}] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                ENDPOINT,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    log::info!(
+        "`{} ({})` [{}] reverted `{} {} {}` to revision {}",
+        auth_user.username,
+        auth_user.id,
+        ENDPOINT,
+        reverted.surname,
+        reverted.name,
+        reverted.patronymic,
+        revision.revision
+    );
+
+    Ok(Json(reverted))
+}
+
+/// Person's record if user may change it or error status
+async fn editable_person(
+    auth_user: &middleware::AuthUser,
+    id: &str,
+) -> Result<database::person::PersonRecord, StatusCode> {
+    auth_user.require_any(&[auth::permission::Permission::PersonsWriteOwn, auth::permission::Permission::PersonsWriteAny])?;
+
+    let record = DATABASE.get_person(id).await.ok_or(StatusCode::NOT_FOUND)?;
+
+    // verifying if we have access
+
+    if !auth_user.can(auth::permission::Permission::PersonsWriteAny) && record.author != auth_user.username {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(record)
+}
+
+/// GET `/persons/{id}/relations`
+pub async fn persons_id_relations_handler(
+    auth_user: middleware::AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<database::relation::PersonConnectionRecord>>, StatusCode> {
+    auth_user.require(auth::permission::Permission::PersonsRead)?;
+
+    DATABASE.get_person(&id).await.ok_or(StatusCode::NOT_FOUND)?;
+
+    let connections = DATABASE.list_person_connections(&id).await.map_err(|err| {
+        log::error!(
+            "`{} ({})` [GET /persons/{{id}}/relations] got database error: {}",
+            auth_user.username,
+            auth_user.id,
+            err
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(connections))
+}
+
+/// POST `/persons/{id}/relations`
+pub async fn persons_id_relations_add_handler(
+    auth_user: middleware::AuthUser,
+    Path(id): Path<String>,
+    relation: Json<database::relation::CreateRelationRecord>,
+) -> Result<Json<database::relation::PersonRelationRecord>, StatusCode> {
+    let record = editable_person(&auth_user, &id).await?;
+
+    if relation.person_id == id || !relation.is_valid() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let related = DATABASE
+        .get_person(&relation.person_id)
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let created = DATABASE
+        .add_person_relation(&id, &relation, &auth_user.username)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [POST /persons/{{id}}/relations] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::CONFLICT)?;
+
+    log::info!(
+        "`{} ({})` [POST /persons/{{id}}/relations] related `{} {} {}` -> `{} {} {}` ({})",
+        auth_user.username,
+        auth_user.id,
+        record.surname,
+        record.name,
+        record.patronymic,
+        related.surname,
+        related.name,
+        related.patronymic,
+        created.kind.name()
+    );
+
+    Ok(Json(created))
+}
+
+/// DELETE `/persons/{id}/relations/{relation_id}`
+pub async fn persons_id_relation_delete_handler(
+    auth_user: middleware::AuthUser,
+    Path((id, relation_id)): Path<(String, String)>,
+) -> Result<Json<database::relation::PersonRelationRecord>, StatusCode> {
+    let record = editable_person(&auth_user, &id).await?;
+
+    let removed = DATABASE
+        .remove_person_relation(&id, &relation_id)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [DELETE /persons/{{id}}/relations/{{relation_id}}] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    log::info!(
+        "`{} ({})` [DELETE /persons/{{id}}/relations/{{relation_id}}] removed relation `{}` of `{} {} {}`",
+        auth_user.username,
+        auth_user.id,
+        relation_id,
+        record.surname,
+        record.name,
+        record.patronymic
+    );
+
+    Ok(Json(removed))
+}
+
+/// GET `/persons/{id}/network`
+pub async fn persons_id_network_handler(
+    auth_user: middleware::AuthUser,
+    Path(id): Path<String>,
+    Query(network): Query<database::relation::NetworkQuery>,
+) -> Result<Json<database::relation::PersonNetwork>, StatusCode> {
+    auth_user.require(auth::permission::Permission::PersonsRead)?;
+
+    DATABASE.get_person(&id).await.ok_or(StatusCode::NOT_FOUND)?;
+
+    let network = DATABASE
+        .person_network(&id, network.depth())
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [GET /persons/{{id}}/network] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(network))
+}
+
+/// GET `/persons/{id}/path/{target_id}`
+pub async fn persons_id_path_handler(
+    auth_user: middleware::AuthUser,
+    Path((id, target_id)): Path<(String, String)>,
+) -> Result<Json<database::relation::PersonPath>, StatusCode> {
+    auth_user.require(auth::permission::Permission::PersonsRead)?;
+
+    DATABASE.get_person(&id).await.ok_or(StatusCode::NOT_FOUND)?;
+    DATABASE.get_person(&target_id).await.ok_or(StatusCode::NOT_FOUND)?;
+
+    let path = DATABASE
+        .person_path(&id, &target_id)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [GET /persons/{{id}}/path/{{target_id}}] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(path))
+}
+
+/// GET `/persons/{id}/tags`
+pub async fn persons_id_tags_handler(
+    auth_user: middleware::AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<database::tag::TagRecord>>, StatusCode> {
+    auth_user.require(auth::permission::Permission::PersonsRead)?;
+
+    DATABASE.get_person(&id).await.ok_or(StatusCode::NOT_FOUND)?;
+
+    let tags = DATABASE.get_person_tags(&id).await.map_err(|err| {
+        log::error!(
+            "`{} ({})` [GET /persons/{{id}}/tags] got database error: {}",
+            auth_user.username,
+            auth_user.id,
+            err
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(tags))
+}
+
+/// POST `/persons/{id}/tags`
+pub async fn persons_id_tags_post_handler(
+    auth_user: middleware::AuthUser,
+    Path(id): Path<String>,
+    Json(payload): Json<database::tag::PersonTagsRecord>,
+) -> Result<Json<Vec<database::tag::TagRecord>>, StatusCode> {
+    let record = editable_person(&auth_user, &id).await?;
+
+    let outcome = DATABASE
+        .set_person_tags(&id, payload.tags, &auth_user.username)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [POST /persons/{{id}}/tags] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let tags = match outcome {
+        database::tag::PersonTagsOutcome::Tagged { tags } => tags,
+        database::tag::PersonTagsOutcome::UnknownTags => return Err(StatusCode::BAD_REQUEST),
+        // moved to trash after it was read
+        database::tag::PersonTagsOutcome::PersonNotFound => return Err(StatusCode::NOT_FOUND),
+    };
+
+    log::info!(
+        "`{} ({})` [POST /persons/{{id}}/tags] tagged `{} {} {}` with: {:?}",
+        auth_user.username,
+        auth_user.id,
+        record.surname,
+        record.name,
+        record.patronymic,
+        tags.iter().map(|tag| tag.name.as_str()).collect::<Vec<_>>()
+    );
+
+    Ok(Json(tags))
+}
+
+/// GET `/persons/trash`
+pub async fn persons_trash_handler(
+    auth_user: middleware::AuthUser,
+) -> Result<Json<Vec<database::person::TrashedPersonRecord>>, StatusCode> {
+    auth_user.require(auth::permission::Permission::PersonsDeleteAny)?;
+
+    let trashed = DATABASE.list_trashed_persons().await.map_err(|err| {
+        log::error!(
+            "`{} ({})` [GET /persons/trash] got database error: {}",
+            auth_user.username,
+            auth_user.id,
+            err
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(trashed))
+}
+
+/// POST `/persons/trash/{id}/restore`
+pub async fn persons_trash_id_restore_handler(
+    auth_user: middleware::AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<database::person::PersonRecord>, StatusCode> {
+    auth_user.require(auth::permission::Permission::PersonsDeleteAny)?;
+
+    let record = DATABASE
+        .restore_person(&id, &auth_user.username)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [POST /persons/trash/{{id}}/restore] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    log::info!(
+        "`{} ({})` [POST /persons/trash/{{id}}/restore] restored `{} {} {}`",
+        auth_user.username,
+        auth_user.id,
+        record.surname,
+        record.name,
+        record.patronymic
+    );
+
+    Ok(Json(record))
+}
+
+/// DELETE `/persons/trash/{id}`
+pub async fn persons_trash_id_delete_handler(
+    auth_user: middleware::AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<database::person::PersonRecord>, StatusCode> {
+    auth_user.require(auth::permission::Permission::PersonsDeleteAny)?;
+
+    let record = DATABASE
+        .purge_person(&id)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [DELETE /persons/trash/{{id}}] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    log::info!(
+        "`{} ({})` [DELETE /persons/trash/{{id}}] permanently deleted `{} {} {}`",
+        auth_user.username,
+        auth_user.id,
+        record.surname,
+        record.name,
+        record.patronymic
+    );
+
+    Ok(Json(record))
+}
+
+// INFO: Admins Routers
+
+/// GET `/users`
+pub async fn users_handler(
+    auth_user: middleware::AuthUser,
+) -> Result<Json<Vec<database::user::UserRecord>>, StatusCode> {
+    auth_user.require(auth::permission::Permission::UsersManage)?;
+
+    let users_list = DATABASE.list_users().await.map_err(|err| {
+        log::error!(
+            "`{} ({})` [GET /users] got database error: {}",
+            auth_user.username,
+            auth_user.id,
+            err
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(users_list.into_iter().map(|user| user.redacted()).collect()))
+}
+
+/// POST `/users/create`
+pub async fn users_create_handler(
+    auth_user: middleware::AuthUser,
+    mut new_record: Json<database::user::CreateUserRecord>,
+) -> Result<Json<database::user::UserRecord>, StatusCode> {
+    auth_user.require(auth::permission::Permission::UsersManage)?;
+
+    let role = find_role(&auth_user, &new_record.role, "POST /users/create")
+        .await?
+        .ok_or(StatusCode::BAD_REQUEST)?;
+
+    check_role_escalation(&auth_user, &role, "POST /users/create")?;
+
+    check_password_policy(
+        &auth_user,
+        &new_record.username,
+        &new_record.password,
+        "POST /users/create",
+    )?;
+
+    // hashing password
+    new_record.password =
+        auth::UserRepository::hash_password(&new_record.password).map_err(|err| {
+            log::error!(
+                "`{} ({})` [POST /users/create] got HASHING ERROR: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    // replacing field with current username (to avoid replacements)
+    new_record.created_by = auth_user.username.clone();
+
+    // admin knows the initial password, so user must replace it
+    let option_record = DATABASE.create_user(new_record.0, true).await.map_err(|err| {
+        log::error!(
+            "`{} ({})` [POST /users/create] got database error: {}",
+            auth_user.username,
+            auth_user.id,
+            err
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if option_record.is_none() {
+        log::error!(
+            "`{} ({})` [POST /users/create] got empty database response",
+            auth_user.username,
+            auth_user.id
+        );
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    let user_record = option_record.unwrap();
+
+    log::info!(
+        "`{} ({})` [POST /users/create] created `{} ({}) role: {}`",
+        auth_user.username,
+        auth_user.id,
+        user_record.username,
+        user_record
+            .id
+            .clone()
+            .map(|id| id.id.to_string())
+            .unwrap_or_default(),
+        user_record.role
+    );
+
+    Ok(Json(user_record.redacted()))
+}
+
+/// GET `/users/{username}`
+pub async fn users_username_handler(
+    auth_user: middleware::AuthUser,
+    Path(username): Path<String>,
+) -> Result<Json<database::user::UserRecord>, StatusCode> {
+    if !auth_user.can(auth::permission::Permission::UsersManage) && auth_user.username != username {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let option_record = DATABASE
+        .get_user_by_username(username)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [GET /users/{{username}}] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    match option_record {
+        Some(record) => Ok(Json(record.redacted())),
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+/// DELETE `/users/{username}`
+pub async fn users_username_delete_handler(
+    State(state): State<AppState>,
+    auth_user: middleware::AuthUser,
+    Path(username): Path<String>,
+) -> Result<Json<database::user::UserRecord>, StatusCode> {
+    auth_user.require(auth::permission::Permission::UsersManage)?;
+
+    // users are suspended instead of deletion, so their records and signatures keep authors
+    let reason = format!("Deleted by `{}`", auth_user.username);
+
+    suspend_user(&state, &auth_user, username, Some(reason), "DELETE /users/{username}").await
+}
+
+/// POST `/users/{username}/suspend`
+pub async fn users_username_suspend_handler(
+    State(state): State<AppState>,
+    auth_user: middleware::AuthUser,
+    Path(username): Path<String>,
+    Json(payload): Json<database::user::SuspendUserRecord>,
+) -> Result<Json<database::user::UserRecord>, StatusCode> {
+    auth_user.require(auth::permission::Permission::UsersManage)?;
+
+    suspend_user(
+        &state,
+        &auth_user,
+        username,
+        payload.reason,
+        "POST /users/{username}/suspend",
+    )
+    .await
+}
+
+/// Disables account and terminates all its sessions
+async fn suspend_user(
+    state: &AppState,
+    auth_user: &middleware::AuthUser,
+    username: String,
+    reason: Option<String>,
+    endpoint: &str,
+) -> Result<Json<database::user::UserRecord>, StatusCode> {
+    let database_error = |err: surrealdb::Error| {
+        log::error!(
+            "`{} ({})` [{}] got database error: {}",
+            auth_user.username,
+            auth_user.id,
+            endpoint,
+            err
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+
+    let record = DATABASE
+        .get_user_by_username(username)
+        .await
+        .map_err(database_error)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let user_id = record
+        .id
+        .as_ref()
+        .map(|id| id.id.to_string())
+        .unwrap_or_default();
+
+    if user_id == auth_user.id {
+        log::warn!(
+            "`{} ({})` [{}] tried to suspend own account",
+            auth_user.username,
+            auth_user.id,
+            endpoint
+        );
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    check_user_ownership(auth_user, &record, endpoint)?;
+
+    let updated = DATABASE
+        .update_user_status(&user_id, true, reason.clone(), record.expires_at.clone())
+        .await
+        .map_err(database_error)?
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    DATABASE
+        .revoke_user_sessions(&user_id)
+        .await
+        .map_err(database_error)?;
+
+    state.user_repo.invalidate(&user_id);
+
+    log::info!(
+        "`{} ({})` [{}] suspended user `{} ({}) role: {}`, reason: {}",
+        auth_user.username,
+        auth_user.id,
+        endpoint,
+        record.username,
+        user_id,
+        record.role,
+        reason.as_deref().unwrap_or("-")
+    );
+
+    Ok(Json(updated.redacted()))
+}
+
+/// POST `/users/{username}/reactivate`
+pub async fn users_username_reactivate_handler(
+    State(state): State<AppState>,
+    auth_user: middleware::AuthUser,
+    Path(username): Path<String>,
+    Json(payload): Json<database::user::ReactivateUserRecord>,
+) -> Result<Json<database::user::UserRecord>, StatusCode> {
+    auth_user.require(auth::permission::Permission::UsersManage)?;
+
+    let database_error = |err: surrealdb::Error| {
+        log::error!(
+            "`{} ({})` [POST /users/{{username}}/reactivate] got database error: {}",
+            auth_user.username,
+            auth_user.id,
+            err
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+
+    let record = DATABASE
+        .get_user_by_username(username)
+        .await
+        .map_err(database_error)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    check_user_ownership(&auth_user, &record, "POST /users/{username}/reactivate")?;
+
+    if payload
+        .expires_at
+        .as_ref()
+        .is_some_and(|expires_at| expires_at.0 <= chrono::Utc::now())
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let user_id = record
+        .id
+        .as_ref()
+        .map(|id| id.id.to_string())
+        .unwrap_or_default();
+
+    let updated = DATABASE
+        .update_user_status(&user_id, false, None, payload.expires_at)
+        .await
+        .map_err(database_error)?
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    state.user_repo.invalidate(&user_id);
+
+    log::info!(
+        "`{} ({})` [POST /users/{{username}}/reactivate] reactivated user `{} ({}) role: {}`",
+        auth_user.username,
+        auth_user.id,
+        record.username,
+        user_id,
+        record.role
+    );
+
+    Ok(Json(updated.redacted()))
+}
+
+/// POST `/users/{username}/impersonate`
+pub async fn users_username_impersonate_handler(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    auth_user: middleware::AuthUser,
+    Path(username): Path<String>,
+) -> Result<Json<auth::ImpersonationResponse>, StatusCode> {
+    auth_user.require(auth::permission::Permission::UsersManage)?;
+
+    // personal access tokens can't start interactive sessions
+    if auth_user.session_id.is_none() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let endpoint = "POST /users/{username}/impersonate";
+
+    let record = DATABASE
+        .get_user_by_username(username)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [POST /users/{{username}}/impersonate] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    check_user_ownership(&auth_user, &record, endpoint)?;
+
+    // admin can't gain permissions through user with wider role
+    let role = find_role(&auth_user, &record.role, endpoint)
+        .await?
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    check_role_escalation(&auth_user, &role, endpoint)?;
+
+    let user = auth::UserRepository::user_from_record(record);
+
+    if user.id == auth_user.id || !user.is_active() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let client_ip = middleware::client_ip(&headers, &addr);
+    let session_id =
+        start_session(&user, &headers, &client_ip, Some(auth_user.username.clone())).await?;
+
+    let expiration = chrono::Duration::minutes(auth::jwt::IMPERSONATION_EXPIRATION_MINUTES);
+
+    DATABASE
+        .extend_session(
+            &session_id,
+            surrealdb::Datetime::from(chrono::Utc::now() + expiration),
+        )
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [POST /users/{{username}}/impersonate] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let token = state
+        .jwt_service
+        .generate_impersonation_token(
+            &user,
+            &session_id,
+            auth::jwt::ActorClaim {
+                sub: auth_user.id.clone(),
+                username: auth_user.username.clone(),
+            },
+        )
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    log::warn!(
+        "`{} ({})` [POST /users/{{username}}/impersonate] started impersonation of `{} ({})`, session `{}`",
+        auth_user.username,
+        auth_user.id,
+        user.username,
+        user.id,
+        session_id
+    );
+
+    Ok(Json(auth::ImpersonationResponse {
+        token,
+        token_type: "Bearer".to_string(),
+        expires_in: expiration.num_seconds(),
+    }))
+}
+
+/// PATCH `/users/{username}`
+pub async fn users_username_patch_handler(
+    State(state): State<AppState>,
+    auth_user: middleware::AuthUser,
+    Path(username): Path<String>,
+    mut patched: Json<database::user::CreateUserRecord>,
+) -> Result<Json<database::user::UserRecord>, StatusCode> {
+    auth_user.require(auth::permission::Permission::UsersManage)?;
+
+    let role = find_role(&auth_user, &patched.role, "PATCH /users/{username}")
+        .await?
+        .ok_or(StatusCode::BAD_REQUEST)?;
+
+    let user_record = DATABASE
+        .get_user_by_username(username)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [PATCH /users/{{username}}] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    match user_record {
+        Some(record) => {
+            check_user_ownership(&auth_user, &record, "PATCH /users/{username}")?;
+
+            // records, signatures and created users refer to username
+            if patched.username != record.username {
+                return Err(StatusCode::BAD_REQUEST);
+            }
+
+            // current role may be kept, only granting new one is limited
+            if role.name != record.role {
+                check_role_escalation(&auth_user, &role, "PATCH /users/{username}")?;
+            }
+
+            // disabling `created_by` field patching
+
+            patched.created_by = record.created_by.clone();
+            
+            let new_password = if patched.password.is_empty() {
+                None
+            } else {
+                check_password_policy(
+                    &auth_user,
+                    &patched.username,
+                    &patched.password,
+                    "PATCH /users/{username}",
+                )?;
+
+                Some(auth::UserRepository::hash_password(&patched.password).map_err(|err| {
+                    log::error!(
+                        "`{} ({})` [PATCH /users/{{username}}] got HASHING ERROR: {}",
+                        auth_user.username,
+                        auth_user.id,
+                        err
+                    );
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?)
+            };
+
+            patched.password = record.password.clone();
+
+            let _ = DATABASE
+                .update_user(record.id.clone().unwrap().id.to_string(), patched.0.clone())
+                .await
+                .map_err(|err| {
+                    log::error!(
+                        "`{} ({})` [PATCH /users/{{username}}] got database error: {}",
+                        auth_user.username,
+                        auth_user.id,
+                        err
+                    );
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+
+            // new role takes effect on the next request
+            state
+                .user_repo
+                .invalidate(&record.id.as_ref().unwrap().id.to_string());
+
+            // password set by admin must be replaced by user
+            if let Some(password_hash) = &new_password {
+                DATABASE
+                    .change_user_password(
+                        record.id.clone().unwrap().id.to_string(),
+                        password_hash.clone(),
+                        true,
+                    )
+                    .await
+                    .map_err(|err| {
+                        log::error!(
+                            "`{} ({})` [PATCH /users/{{username}}] got database error: {}",
+                            auth_user.username,
+                            auth_user.id,
+                            err
+                        );
+                        StatusCode::INTERNAL_SERVER_ERROR
+                    })?;
+            }
+
+            let password_changed = new_password.is_some();
+
+            log::info!(
+                "`{} ({})` [PATCH /users/{{username}}] updated user `{} ({}), role: {}` -> `{}, role: {}, password changed: {}`",
+                auth_user.username,
+                auth_user.id,
+                record.username,
+                record.id.as_ref().map(|id| id.id.to_string()).unwrap(),
+                record.role,
+                patched.username,
+                patched.role,
+                password_changed
+            );
 
-/// GET `/me`
-pub async fn me_handler(
-    auth_user: middleware::AuthUser,
-) -> Result<Json<database::user::UserRecord>, StatusCode> {
-    let user = DATABASE.get_user(auth_user.id).await;
+            let updated = DATABASE
+                .get_user(record.id.as_ref().unwrap().id.to_string())
+                .await
+                .ok_or(StatusCode::NOT_FOUND)?;
 
-    match user {
-        Some(record) => Ok(Json(record)),
+            Ok(Json(updated.redacted()))
+        }
         None => Err(StatusCode::NOT_FOUND),
     }
 }
 
-/// GET `/persons`
-pub async fn persons_handler(
+/// GET `/users/{username}/sessions`
+pub async fn users_username_sessions_handler(
     auth_user: middleware::AuthUser,
-    // Json(payload): Json<Option<database::person::SearchPersonRecord>>
-) -> Result<Json<Vec<database::person::PersonRecord>>, StatusCode> {
-    if auth_user.role < auth::user::UserRole::Watcher {
-        return Err(StatusCode::FORBIDDEN);
-    }
+    Path(username): Path<String>,
+) -> Result<Json<Vec<database::session::SessionRecord>>, StatusCode> {
+    auth_user.require(auth::permission::Permission::UsersManage)?;
 
-    // let records_list = match payload {
-    //     Some(search_payload) => {
-    //         DATABASE.find_person(&search_payload.search_query).await.or_else(|err| {
-    //             log::error!("`{} ({})` [GET /persons] [PAYLOAD: '{}'] got database error: {}", auth_user.username, auth_user.id, search_payload.search_query, err);
-    //             Err(StatusCode::INTERNAL_SERVER_ERROR)
-    //         })?
-    //     },
-    //     None => {
-    //         DATABASE.list_persons().await.or_else(|err| {
-    //             log::error!("`{} ({})` [GET /persons] [NO PAYLOAD] got database error: {}", auth_user.username, auth_user.id, err);
-    //             Err(StatusCode::INTERNAL_SERVER_ERROR)
-    //         })?
-    //     }
-    // };
-
-    let records_list = DATABASE.list_persons().await.map_err(|err| {
+    let user_id = find_managed_user_id(&auth_user, username, "GET /users/{username}/sessions").await?;
+
+    let sessions = DATABASE.list_user_sessions(&user_id).await.map_err(|err| {
         log::error!(
-            "`{} ({})` [GET /persons] got database error: {}",
+            "`{} ({})` [GET /users/{{username}}/sessions] got database error: {}",
             auth_user.username,
             auth_user.id,
             err
@@ -109,93 +3365,154 @@ pub async fn persons_handler(
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
-    Ok(Json(records_list))
+    Ok(Json(sessions))
 }
 
-/// GET `/persons/{id}`
-pub async fn persons_id_handler(
+/// DELETE `/users/{username}/sessions/{id}`
+pub async fn users_username_sessions_id_delete_handler(
     auth_user: middleware::AuthUser,
-    Path(id): Path<String>,
-) -> Result<Json<database::person::PersonRecord>, StatusCode> {
-    if auth_user.role < auth::user::UserRole::Watcher {
-        return Err(StatusCode::FORBIDDEN);
-    }
+    Path((username, id)): Path<(String, String)>,
+) -> Result<Json<database::session::SessionRecord>, StatusCode> {
+    auth_user.require(auth::permission::Permission::UsersManage)?;
 
-    match DATABASE.get_person(id).await {
-        Some(record) => Ok(Json(record)),
-        None => Err(StatusCode::NOT_FOUND),
-    }
+    let endpoint = "DELETE /users/{username}/sessions/{id}";
+    let user_id = find_managed_user_id(&auth_user, username, endpoint).await?;
+
+    terminate_session(&auth_user, &user_id, &id, endpoint).await
 }
 
-pub async fn media_handler(
-    auth_user: middleware::AuthUser,
-    Path(hash): Path<String>,
-) -> impl IntoResponse {
-    if auth_user.role < auth::user::UserRole::Watcher {
-        return Err((StatusCode::FORBIDDEN, "Not enough permissions".to_string()));
-    }
+/// Resolves SurrealDB identifier of user managed by authorized user (see [check_user_ownership])
+async fn find_managed_user_id(
+    auth_user: &middleware::AuthUser,
+    username: String,
+    endpoint: &str,
+) -> Result<String, StatusCode> {
+    let record = DATABASE
+        .get_user_by_username(username)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [{}] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                endpoint,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
 
-    uploads::get_photo(hash).await
+    check_user_ownership(auth_user, &record, endpoint)?;
+
+    record
+        .id
+        .map(|id| id.id.to_string())
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)
 }
 
-// INFO: Editors Routers
+/// Protected accounts can't be changed, other users are managed only by their creator
+/// (static admin manages everyone)
+fn check_user_ownership(
+    auth_user: &middleware::AuthUser,
+    record: &database::user::UserRecord,
+    endpoint: &str,
+) -> Result<(), StatusCode> {
+    if record.protected {
+        log::warn!(
+            "`{} ({})` [{}] tried to change protected user `{}`",
+            auth_user.username,
+            auth_user.id,
+            endpoint,
+            record.username
+        );
+        return Err(StatusCode::FORBIDDEN);
+    }
 
-pub async fn upload_handler(
-    auth_user: middleware::AuthUser,
-    multipart: Multipart,
-) -> Result<Json<String>, (StatusCode, String)> {
-    if auth_user.role < auth::user::UserRole::Editor {
-        return Err((StatusCode::FORBIDDEN, "Not enough permissions".to_string()));
+    if !auth_user.protected && record.created_by != auth_user.username {
+        log::warn!(
+            "`{} ({})` [{}] tried to change user `{}` created by `{}`",
+            auth_user.username,
+            auth_user.id,
+            endpoint,
+            record.username,
+            record.created_by
+        );
+        return Err(StatusCode::FORBIDDEN);
     }
 
-    let result = uploads::upload_photo(multipart).await?;
+    Ok(())
+}
 
-    log::info!(
-        "`{} ({})` uploaded photo with hash: '{}'",
+/// Role can be granted only by users having all of its permissions (static admin grants any)
+fn check_role_escalation(
+    auth_user: &middleware::AuthUser,
+    role: &database::role::RoleRecord,
+    endpoint: &str,
+) -> Result<(), StatusCode> {
+    check_permissions_escalation(auth_user, &role.name, &role.permissions, endpoint)
+}
+
+/// Permissions can be given to role only by users having all of them (static admin gives any)
+fn check_permissions_escalation(
+    auth_user: &middleware::AuthUser,
+    role_name: &str,
+    permissions: &[auth::permission::Permission],
+    endpoint: &str,
+) -> Result<(), StatusCode> {
+    if auth_user.protected
+        || permissions
+            .iter()
+            .all(|permission| auth_user.can(*permission))
+    {
+        return Ok(());
+    }
+
+    log::warn!(
+        "`{} ({})` [{}] tried to grant role `{}` above own permissions",
         auth_user.username,
         auth_user.id,
-        result.0
+        endpoint,
+        role_name
     );
 
-    Ok(result)
+    Err(StatusCode::FORBIDDEN)
 }
 
-/// POST `/persons/create`
-pub async fn persons_create_handler(
+/// DELETE `/users/{username}/2fa`
+pub async fn users_username_2fa_delete_handler(
     auth_user: middleware::AuthUser,
-    new_record: Json<database::person::CreatePersonRecord>,
-) -> Result<Json<database::person::PersonRecord>, StatusCode> {
-    if auth_user.role < auth::user::UserRole::Editor {
-        return Err(StatusCode::FORBIDDEN);
-    }
+    Path(username): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    auth_user.require(auth::permission::Permission::UsersManage)?;
 
-    // unique record verification
-    if !DATABASE
-        .find_person(format!(
-            "{} {} {}",
-            new_record.surname, new_record.name, new_record.patronymic
-        ))
+    let record = DATABASE
+        .get_user_by_username(username)
         .await
         .map_err(|err| {
             log::error!(
-                "`{} ({})` [POST /persons/create] got database verification error: {}",
+                "`{} ({})` [DELETE /users/{{username}}/2fa] got database error: {}",
                 auth_user.username,
                 auth_user.id,
                 err
             );
             StatusCode::INTERNAL_SERVER_ERROR
         })?
-        .is_empty()
-    {
-        return Err(StatusCode::CONFLICT);
-    }
+        .ok_or(StatusCode::NOT_FOUND)?;
 
-    let option_record = DATABASE
-        .add_person(new_record.0, &auth_user.username)
+    check_user_ownership(&auth_user, &record, "DELETE /users/{username}/2fa")?;
+
+    let user_id = record
+        .id
+        .as_ref()
+        .map(|id| id.id.to_string())
+        .unwrap_or_default();
+
+    DATABASE
+        .update_user_totp(&user_id, None, false, Vec::new())
         .await
         .map_err(|err| {
             log::error!(
-                "`{} ({})` [POST /persons/create] got database error: {}",
+                "`{} ({})` [DELETE /users/{{username}}/2fa] got database error: {}",
                 auth_user.username,
                 auth_user.id,
                 err
@@ -203,138 +3520,215 @@ pub async fn persons_create_handler(
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
-    if option_record.is_none() {
-        log::error!(
-            "`{} ({})` [POST /persons/create] got empty database response",
-            auth_user.username,
-            auth_user.id
-        );
-        return Err(StatusCode::INTERNAL_SERVER_ERROR);
-    }
+    log::info!(
+        "`{} ({})` [DELETE /users/{{username}}/2fa] reset two-factor authentication of `{} ({})`",
+        auth_user.username,
+        auth_user.id,
+        record.username,
+        user_id
+    );
 
-    let person_record = option_record.unwrap();
+    Ok(StatusCode::OK)
+}
+
+/// DELETE `/users/{username}/lockout`
+pub async fn users_username_lockout_delete_handler(
+    auth_user: middleware::AuthUser,
+    Path(username): Path<String>,
+    Query(unlock): Query<database::user::UnlockUserQuery>,
+) -> Result<StatusCode, StatusCode> {
+    auth_user.require(auth::permission::Permission::UsersManage)?;
+
+    find_managed_user_id(&auth_user, username.clone(), "DELETE /users/{username}/lockout").await?;
+
+    let mut throttle_keys = vec![auth::throttle::username_key(&username)];
+    throttle_keys.extend(unlock.ip.as_deref().map(auth::throttle::ip_key));
+
+    for key in &throttle_keys {
+        auth::throttle::reset(key).await.map_err(|err| {
+            log::error!(
+                "`{} ({})` [DELETE /users/{{username}}/lockout] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    }
 
     log::info!(
-        "`{} ({})` [POST /persons/create] created person record: {} {} {}",
+        "`{} ({})` [DELETE /users/{{username}}/lockout] unlocked login for `{}` (IP: {})",
         auth_user.username,
         auth_user.id,
-        person_record.name,
-        person_record.surname,
-        person_record.patronymic
+        username,
+        unlock.ip.as_deref().unwrap_or("-")
     );
 
-    Ok(Json(person_record))
+    Ok(StatusCode::OK)
 }
 
-/// PATCH `/persons/{id}`
-pub async fn persons_patch_handler(
+/// GET `/roles`
+pub async fn roles_handler(
     auth_user: middleware::AuthUser,
-    Path(id): Path<String>,
-    patched: Json<database::person::CreatePersonRecord>,
-) -> Result<Json<database::person::PersonRecord>, StatusCode> {
-    if auth_user.role < auth::user::UserRole::Editor {
-        return Err(StatusCode::FORBIDDEN);
-    }
+) -> Result<Json<Vec<database::role::RoleRecord>>, StatusCode> {
+    auth_user.require(auth::permission::Permission::RolesManage)?;
 
-    let person_record = DATABASE.get_person(&id).await;
+    let roles = DATABASE.get_roles().await.map_err(|err| {
+        log::error!(
+            "`{} ({})` [GET /roles] got database error: {}",
+            auth_user.username,
+            auth_user.id,
+            err
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
 
-    match person_record {
-        Some(record) => {
-            // verifying if we have access
+    Ok(Json(roles))
+}
 
-            if auth_user.role < auth::user::UserRole::Admin && record.author != auth_user.username {
-                return Err(StatusCode::FORBIDDEN);
-            }
+/// POST `/roles`
+pub async fn roles_create_handler(
+    auth_user: middleware::AuthUser,
+    Json(payload): Json<database::role::CreateRoleRecord>,
+) -> Result<Json<database::role::RoleRecord>, StatusCode> {
+    auth_user.require(auth::permission::Permission::RolesManage)?;
 
-            let record = DATABASE
-                .update_person(id, patched.0.clone())
-                .await
-                .map_err(|err| {
-                    log::error!(
-                        "`{} ({})` [PATCH /persons/{{id}}] got database error: {}",
-                        auth_user.username,
-                        auth_user.id,
-                        err
-                    );
-                    StatusCode::INTERNAL_SERVER_ERROR
-                })?
-                .unwrap_or(record);
+    let name = payload.name.trim().to_lowercase();
 
-            log::info!(
-                "`{} ({})` [PATCH /persons/{{id}}] updated `{} {} {}` -> `{} {} {}`",
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if find_role(&auth_user, &name, "POST /roles").await?.is_some() {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let mut permissions = payload.permissions;
+    permissions.sort();
+    permissions.dedup();
+
+    check_permissions_escalation(&auth_user, &name, &permissions, "POST /roles")?;
+
+    let record = DATABASE
+        .create_role(database::role::RoleRecord {
+            id: None,
+            name,
+            permissions,
+            builtin: false,
+            creation_datetime: surrealdb::Datetime::from(chrono::Utc::now()),
+        })
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [POST /roles] got database error: {}",
                 auth_user.username,
                 auth_user.id,
-                record.surname,
-                record.name,
-                record.patronymic,
-                patched.surname,
-                patched.name,
-                patched.patronymic
+                err
             );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
 
-            Ok(Json(record))
-        }
-        None => Err(StatusCode::NOT_FOUND),
-    }
+    log::info!(
+        "`{} ({})` [POST /roles] created role `{}` with permissions: {:?}",
+        auth_user.username,
+        auth_user.id,
+        record.name,
+        record.permissions
+    );
+
+    Ok(Json(record))
 }
 
-/// DELETE `/persons/{id}`
-pub async fn persons_delete_handler(
+/// PATCH `/roles/{name}`
+pub async fn roles_name_patch_handler(
+    State(state): State<AppState>,
     auth_user: middleware::AuthUser,
-    Path(id): Path<String>,
-) -> Result<Json<database::person::PersonRecord>, StatusCode> {
-    if auth_user.role < auth::user::UserRole::Editor {
-        return Err(StatusCode::FORBIDDEN);
-    }
+    Path(name): Path<String>,
+    Json(payload): Json<database::role::PatchRoleRecord>,
+) -> Result<Json<database::role::RoleRecord>, StatusCode> {
+    auth_user.require(auth::permission::Permission::RolesManage)?;
 
-    let person_record = DATABASE.get_person(&id).await;
+    let role = find_role(&auth_user, &name, "PATCH /roles/{name}")
+        .await?
+        .ok_or(StatusCode::NOT_FOUND)?;
 
-    match person_record {
-        Some(record) => {
-            // verifying if we have access
+    // admin preset always keeps all permissions, so nobody gets locked out
+    if role.name == auth::permission::Preset::Admin.name() {
+        return Err(StatusCode::CONFLICT);
+    }
 
-            if auth_user.role < auth::user::UserRole::Admin && record.author != auth_user.username {
-                return Err(StatusCode::FORBIDDEN);
-            }
+    let mut permissions = payload.permissions;
+    permissions.sort();
+    permissions.dedup();
 
-            let _ = DATABASE.delete_person(id).await.map_err(|err| {
-                log::error!(
-                    "`{} ({})` [DELETE /persons/{{id}}] got database error: {}",
-                    auth_user.username,
-                    auth_user.id,
-                    err
-                );
-                StatusCode::INTERNAL_SERVER_ERROR
-            });
+    check_permissions_escalation(&auth_user, &role.name, &permissions, "PATCH /roles/{name}")?;
 
-            log::info!(
-                "`{} ({})` [DELETE /persons/{{id}}] deleted `{} {} {}`",
+    let record = DATABASE
+        .update_role_permissions(&role.name, permissions)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [PATCH /roles/{{name}}] got database error: {}",
                 auth_user.username,
                 auth_user.id,
-                record.surname,
-                record.name,
-                record.patronymic
+                err
             );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
 
-            Ok(Json(record))
-        }
-        None => Err(StatusCode::NOT_FOUND),
-    }
+    state.user_repo.invalidate_role(&record.name);
+
+    log::info!(
+        "`{} ({})` [PATCH /roles/{{name}}] updated role `{}` permissions: {:?} -> {:?}",
+        auth_user.username,
+        auth_user.id,
+        record.name,
+        role.permissions,
+        record.permissions
+    );
+
+    Ok(Json(record))
 }
 
-// INFO: Admins Routers
+/// DELETE `/roles/{name}`
+pub async fn roles_name_delete_handler(
+    State(state): State<AppState>,
+    auth_user: middleware::AuthUser,
+    Path(name): Path<String>,
+) -> Result<Json<database::role::RoleRecord>, StatusCode> {
+    auth_user.require(auth::permission::Permission::RolesManage)?;
+
+    let role = find_role(&auth_user, &name, "DELETE /roles/{name}")
+        .await?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if role.builtin {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let users_count = DATABASE.count_role_users(&role.name).await.map_err(|err| {
+        log::error!(
+            "`{} ({})` [DELETE /roles/{{name}}] got database error: {}",
+            auth_user.username,
+            auth_user.id,
+            err
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
 
-/// GET `/users`
-pub async fn users_handler(
-    auth_user: middleware::AuthUser,
-) -> Result<Json<Vec<database::user::UserRecord>>, StatusCode> {
-    if auth_user.role < auth::user::UserRole::Admin {
-        return Err(StatusCode::FORBIDDEN);
+    if users_count > 0 {
+        return Err(StatusCode::CONFLICT);
     }
 
-    let users_list = DATABASE.list_users().await.map_err(|err| {
+    DATABASE.delete_role(&role.name).await.map_err(|err| {
         log::error!(
-            "`{} ({})` [GET /users] got database error: {}",
+            "`{} ({})` [DELETE /roles/{{name}}] got database error: {}",
             auth_user.username,
             auth_user.id,
             err
@@ -342,36 +3736,45 @@ pub async fn users_handler(
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
-    Ok(Json(users_list))
-}
+    state.user_repo.invalidate_role(&role.name);
 
-/// POST `/users/create`
-pub async fn users_create_handler(
-    auth_user: middleware::AuthUser,
-    mut new_record: Json<database::user::CreateUserRecord>,
-) -> Result<Json<database::user::UserRecord>, StatusCode> {
-    if auth_user.role < auth::user::UserRole::Admin {
-        return Err(StatusCode::FORBIDDEN);
-    }
+    log::info!(
+        "`{} ({})` [DELETE /roles/{{name}}] deleted role `{}`",
+        auth_user.username,
+        auth_user.id,
+        role.name
+    );
 
-    // hashing password
-    new_record.password =
-        auth::UserRepository::hash_password(&new_record.password).map_err(|err| {
-            log::error!(
-                "`{} ({})` [POST /users/create] got HASHING ERROR: {}",
-                auth_user.username,
-                auth_user.id,
-                err
-            );
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+    Ok(Json(role))
+}
 
-    // replacing field with current username (to avoid replacements)
-    new_record.created_by = auth_user.username.clone();
+/// Resolves role record by its name
+async fn find_role(
+    auth_user: &middleware::AuthUser,
+    name: &str,
+    endpoint: &str,
+) -> Result<Option<database::role::RoleRecord>, StatusCode> {
+    DATABASE.get_role(name).await.map_err(|err| {
+        log::error!(
+            "`{} ({})` [{}] got database error: {}",
+            auth_user.username,
+            auth_user.id,
+            endpoint,
+            err
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
 
-    let option_record = DATABASE.create_user(new_record.0).await.map_err(|err| {
+/// GET `/tags`
+pub async fn tags_handler(
+    auth_user: middleware::AuthUser,
+) -> Result<Json<Vec<database::tag::TagUsageRecord>>, StatusCode> {
+    auth_user.require(auth::permission::Permission::PersonsRead)?;
+
+    let tags = DATABASE.list_tags().await.map_err(|err| {
         log::error!(
-            "`{} ({})` [POST /users/create] got database error: {}",
+            "`{} ({})` [GET /tags] got database error: {}",
             auth_user.username,
             auth_user.id,
             err
@@ -379,196 +3782,252 @@ pub async fn users_create_handler(
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
-    if option_record.is_none() {
+    Ok(Json(tags))
+}
+
+/// Whether tag name is used by tag other than `except_id`
+async fn tag_name_taken(
+    auth_user: &middleware::AuthUser,
+    name: &str,
+    except_id: Option<&str>,
+    endpoint: &str,
+) -> Result<bool, StatusCode> {
+    let tag = DATABASE.get_tag_by_name(name).await.map_err(|err| {
         log::error!(
-            "`{} ({})` [POST /users/create] got empty database response",
+            "`{} ({})` [{}] got database error: {}",
             auth_user.username,
-            auth_user.id
+            auth_user.id,
+            endpoint,
+            err
         );
-        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(tag.is_some_and(|tag| {
+        tag.id.map(|id| id.id.to_raw()).as_deref() != except_id
+    }))
+}
+
+/// POST `/tags`
+pub async fn tags_create_handler(
+    auth_user: middleware::AuthUser,
+    Json(payload): Json<database::tag::CreateTagRecord>,
+) -> Result<Json<database::tag::TagRecord>, StatusCode> {
+    auth_user.require(auth::permission::Permission::SettingsManage)?;
+
+    if !payload.is_valid() {
+        return Err(StatusCode::BAD_REQUEST);
     }
 
-    let user_record = option_record.unwrap();
+    if tag_name_taken(&auth_user, &payload.name(), None, "POST /tags").await? {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let record = DATABASE
+        .create_tag(&payload)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [POST /tags] got database error: {}",
+                auth_user.username,
+                auth_user.id,
+                err
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
 
     log::info!(
-        "`{} ({})` [POST /users/create] created `{} ({}) role: {}`",
+        "`{} ({})` [POST /tags] created tag `{}`",
         auth_user.username,
         auth_user.id,
-        user_record.username,
-        user_record
-            .id
-            .clone()
-            .map(|id| id.id.to_string())
-            .unwrap_or_default(),
-        user_record.role
+        record.name
     );
 
-    Ok(Json(user_record))
+    Ok(Json(record))
 }
 
-/// GET `/users/{username}`
-pub async fn users_username_handler(
+/// PATCH `/tags/{id}`
+pub async fn tags_id_patch_handler(
     auth_user: middleware::AuthUser,
-    Path(username): Path<String>,
-) -> Result<Json<database::user::UserRecord>, StatusCode> {
-    if auth_user.role < auth::user::UserRole::Admin && auth_user.username != username {
-        return Err(StatusCode::FORBIDDEN);
+    Path(id): Path<String>,
+    Json(payload): Json<database::tag::CreateTagRecord>,
+) -> Result<Json<database::tag::TagRecord>, StatusCode> {
+    auth_user.require(auth::permission::Permission::SettingsManage)?;
+
+    if !payload.is_valid() {
+        return Err(StatusCode::BAD_REQUEST);
     }
 
-    let option_record = DATABASE
-        .get_user_by_username(username)
+    let tag = DATABASE.get_tag(&id).await.ok_or(StatusCode::NOT_FOUND)?;
+
+    if tag_name_taken(&auth_user, &payload.name(), Some(&id), "PATCH /tags/{id}").await? {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let record = DATABASE
+        .update_tag(&id, &payload)
         .await
         .map_err(|err| {
             log::error!(
-                "`{} ({})` [GET /users/{{username}}] got database error: {}",
+                "`{} ({})` [PATCH /tags/{{id}}] got database error: {}",
                 auth_user.username,
                 auth_user.id,
                 err
             );
             StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
 
-    match option_record {
-        Some(record) => Ok(Json(record)),
-        None => Err(StatusCode::NOT_FOUND),
-    }
+    log::info!(
+        "`{} ({})` [PATCH /tags/{{id}}] updated tag `{}` -> `{}`",
+        auth_user.username,
+        auth_user.id,
+        tag.name,
+        record.name
+    );
+
+    Ok(Json(record))
 }
 
-/// DELETE `/users/{username}`
-pub async fn users_username_delete_handler(
+/// DELETE `/tags/{id}`
+pub async fn tags_id_delete_handler(
     auth_user: middleware::AuthUser,
-    Path(username): Path<String>,
-) -> Result<Json<database::user::UserRecord>, StatusCode> {
-    if auth_user.role < auth::user::UserRole::Admin {
-        return Err(StatusCode::FORBIDDEN);
-    }
+    Path(id): Path<String>,
+) -> Result<Json<database::tag::TagRecord>, StatusCode> {
+    auth_user.require(auth::permission::Permission::SettingsManage)?;
 
-    let user_record = DATABASE
-        .get_user_by_username(username)
+    let record = DATABASE
+        .delete_tag(&id)
         .await
         .map_err(|err| {
             log::error!(
-                "`{} ({})` [DELETE /users/{{username}}] got database error: {}",
+                "`{} ({})` [DELETE /tags/{{id}}] got database error: {}",
                 auth_user.username,
                 auth_user.id,
                 err
             );
             StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
 
-    match user_record {
-        Some(record) => {
-            let _ = DATABASE
-                .delete_user(record.id.clone().unwrap().id.to_string())
-                .await
-                .map_err(|err| {
-                    log::error!(
-                        "`{} ({})` [DELETE /users/{{username}}] got database error: {}",
-                        auth_user.username,
-                        auth_user.id,
-                        err
-                    );
-                    StatusCode::INTERNAL_SERVER_ERROR
-                })?;
+    log::info!(
+        "`{} ({})` [DELETE /tags/{{id}}] deleted tag `{}`",
+        auth_user.username,
+        auth_user.id,
+        record.name
+    );
 
-            log::info!(
-                "`{} ({})` [DELETE /users/{{username}}] deleted user `{} ({}) role: {}`",
-                auth_user.username,
-                auth_user.id,
-                record.username,
-                record.id.as_ref().map(|id| id.id.to_string()).unwrap(),
-                record.role
-            );
-            Ok(Json(record))
-        }
-        None => Err(StatusCode::NOT_FOUND),
-    }
+    Ok(Json(record))
 }
 
-/// PATCH `/users/{username}`
-pub async fn users_username_patch_handler(
+/// POST `/tags/{id}/merge`
+pub async fn tags_id_merge_handler(
     auth_user: middleware::AuthUser,
-    Path(username): Path<String>,
-    mut patched: Json<database::user::CreateUserRecord>,
-) -> Result<Json<database::user::UserRecord>, StatusCode> {
-    if auth_user.role < auth::user::UserRole::Admin {
-        return Err(StatusCode::FORBIDDEN);
+    Path(id): Path<String>,
+    Json(payload): Json<database::tag::MergeTagRecord>,
+) -> Result<Json<database::tag::TagRecord>, StatusCode> {
+    auth_user.require(auth::permission::Permission::SettingsManage)?;
+
+    if payload.into == id {
+        return Err(StatusCode::BAD_REQUEST);
     }
 
-    let user_record = DATABASE
-        .get_user_by_username(username)
+    let tag = DATABASE.get_tag(&id).await.ok_or(StatusCode::NOT_FOUND)?;
+
+    let record = DATABASE
+        .merge_tags(&id, &payload.into)
         .await
         .map_err(|err| {
             log::error!(
-                "`{} ({})` [PATCH /users/{{username}}] got database error: {}",
+                "`{} ({})` [POST /tags/{{id}}/merge] got database error: {}",
                 auth_user.username,
                 auth_user.id,
                 err
             );
             StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
 
-    match user_record {
-        Some(record) => {
-            // disabling `created_by` field patching
+    log::info!(
+        "`{} ({})` [POST /tags/{{id}}/merge] merged tag `{}` into `{}`",
+        auth_user.username,
+        auth_user.id,
+        tag.name,
+        record.name
+    );
 
-            patched.created_by = record.created_by.clone();
-            
-            if patched.password.is_empty() {
-                patched.password = record.password.clone();
-            } else {
-                patched.password = auth::UserRepository::hash_password(&patched.password).map_err(|err| {
-                    log::error!(
-                        "`{} ({})` [POST /users/create] got HASHING ERROR: {}",
-                        auth_user.username,
-                        auth_user.id,
-                        err
-                    );
-                    StatusCode::INTERNAL_SERVER_ERROR
-                })?;
-            }
+    Ok(Json(record))
+}
 
-            let _ = DATABASE
-                .update_user(record.id.clone().unwrap().id.to_string(), patched.0.clone())
-                .await
-                .map_err(|err| {
-                    log::error!(
-                        "`{} ({})` [PATCH /users/{{username}}] got database error: {}",
-                        auth_user.username,
-                        auth_user.id,
-                        err
-                    );
-                    StatusCode::INTERNAL_SERVER_ERROR
-                })?;
+/// GET `/settings/security`
+pub async fn settings_security_handler(
+    auth_user: middleware::AuthUser,
+) -> Result<Json<database::settings::SecuritySettings>, StatusCode> {
+    auth_user.require(auth::permission::Permission::SettingsManage)?;
+
+    let settings = DATABASE.get_security_settings().await.map_err(|err| {
+        log::error!(
+            "`{} ({})` [GET /settings/security] got database error: {}",
+            auth_user.username,
+            auth_user.id,
+            err
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(settings))
+}
 
-            let password_changed =
-                record.password != patched.password && !patched.password.is_empty();
+/// PATCH `/settings/security`
+pub async fn settings_security_patch_handler(
+    auth_user: middleware::AuthUser,
+    Json(mut patched): Json<database::settings::SecuritySettings>,
+) -> Result<Json<database::settings::SecuritySettings>, StatusCode> {
+    auth_user.require(auth::permission::Permission::SettingsManage)?;
 
-            log::info!(
-                "`{} ({})` [PATCH /users/{{username}}] updated user `{} ({}), role: {}` -> `{}, role: {}, password changed: {}`",
+    for role in patched.require_2fa_roles.iter_mut() {
+        *role = role.trim().to_lowercase();
+    }
+
+    patched.require_2fa_roles.sort();
+    patched.require_2fa_roles.dedup();
+
+    for role in &patched.require_2fa_roles {
+        find_role(&auth_user, role, "PATCH /settings/security")
+            .await?
+            .ok_or(StatusCode::BAD_REQUEST)?;
+    }
+
+    let settings = DATABASE
+        .update_security_settings(patched)
+        .await
+        .map_err(|err| {
+            log::error!(
+                "`{} ({})` [PATCH /settings/security] got database error: {}",
                 auth_user.username,
                 auth_user.id,
-                record.username,
-                record.id.as_ref().map(|id| id.id.to_string()).unwrap(),
-                record.role,
-                patched.username,
-                patched.role,
-                password_changed
+                err
             );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .unwrap_or_default();
 
-            Ok(Json(record))
-        }
-        None => Err(StatusCode::NOT_FOUND),
-    }
+    log::info!(
+        "`{} ({})` [PATCH /settings/security] two-factor required for roles: {:?}",
+        auth_user.username,
+        auth_user.id,
+        settings.require_2fa_roles
+    );
+
+    Ok(Json(settings))
 }
 
 /// POST `/signature-keygen`
 pub async fn signature_keygen_handler(
     auth_user: middleware::AuthUser,
 ) -> Result<Json<String>, StatusCode> {
-    if auth_user.role < auth::user::UserRole::Admin {
-        return Err(StatusCode::FORBIDDEN);
-    }
+    auth_user.require(auth::permission::Permission::SignaturesSign)?;
 
     let user = DATABASE.get_user(&auth_user.id).await;
 
@@ -579,12 +4038,12 @@ pub async fn signature_keygen_handler(
             }
 
             let (private_key, public_key) = signatures::generate_signing_keypair();
-            let _ = DATABASE.update_user_pubkey(&auth_user.id, Some(public_key)).await.or_else(|err| {
+            let _ = DATABASE.update_user_pubkey(&auth_user.id, Some(public_key)).await.map_err(|err| {
                 log::error!("`{} ({})` [POST /signature-keygen] got database error: {}", auth_user.username, auth_user.id, err);
-                Err(StatusCode::INTERNAL_SERVER_ERROR)
+                StatusCode::INTERNAL_SERVER_ERROR
             })?;
 
-            return Ok(Json(private_key));
+            Ok(Json(private_key))
         },
         None => Err(StatusCode::NOT_FOUND)
     }
@@ -594,9 +4053,7 @@ pub async fn signature_keygen_handler(
 pub async fn signature_reset_handler(
     auth_user: middleware::AuthUser,
 ) -> Result<(), StatusCode> {
-    if auth_user.role < auth::user::UserRole::Admin {
-        return Err(StatusCode::FORBIDDEN);
-    }
+    auth_user.require(auth::permission::Permission::SignaturesSign)?;
 
     let user = DATABASE.get_user(&auth_user.id).await;
 
@@ -606,12 +4063,12 @@ pub async fn signature_reset_handler(
                 return Err(StatusCode::NO_CONTENT);
             }
 
-            let _ = DATABASE.update_user_pubkey(&auth_user.id, None).await.or_else(|err| {
+            let _ = DATABASE.update_user_pubkey(&auth_user.id, None).await.map_err(|err| {
                 log::error!("`{} ({})` [POST /signature-keygen] got database error: {}", auth_user.username, auth_user.id, err);
-                Err(StatusCode::INTERNAL_SERVER_ERROR)
+                StatusCode::INTERNAL_SERVER_ERROR
             })?;
 
-            return Ok(());
+            Ok(())
         },
         None => Err(StatusCode::NOT_FOUND)
     }
@@ -623,18 +4080,15 @@ pub async fn persons_id_sign_handler(
     Path(id): Path<String>,
     payload: Json<database::signature::SignRecordPayload>,
 ) -> Result<Json<database::signature::RecordSignatureRecord>, StatusCode> {
-    if auth_user.role < auth::user::UserRole::Admin {
-        return Err(StatusCode::FORBIDDEN);
-    }
+    auth_user.require(auth::permission::Permission::SignaturesSign)?;
 
     // verifying that record isn't unsigned yet
     
     let existing_signature = DATABASE.get_signature(&id).await;
 
-    if let Ok(existing_signature) = existing_signature {
-        if existing_signature.is_some() {
-            return Err(StatusCode::CONFLICT);
-        }
+    if let Ok(existing_signature) = existing_signature
+    && existing_signature.is_some() {
+        return Err(StatusCode::CONFLICT);
     }
 
     // now signing this record
@@ -643,14 +4097,14 @@ pub async fn persons_id_sign_handler(
 
     match record {
         Some(record) => {
-            let signature = signatures::sign_record(record.clone(), payload.private_key.clone()).or_else(|err| {
+            let signature = signatures::sign_record(record.clone(), payload.private_key.clone()).map_err(|err| {
                 log::error!("`{} ({})` [POST /persons/{{id}}] got signature error: {}", auth_user.username, auth_user.id, err);
-                Err(StatusCode::BAD_REQUEST)
+                StatusCode::BAD_REQUEST
             })?;
 
-            let db_result = DATABASE.add_signature(signature, &auth_user.username).await.or_else(|err| {
+            let db_result = DATABASE.add_signature(signature, &auth_user.username).await.map_err(|err| {
                 log::error!("`{} ({})` [POST /persons/{{id}}] got database error: {}", auth_user.username, auth_user.id, err);
-                Err(StatusCode::INTERNAL_SERVER_ERROR)               
+                StatusCode::INTERNAL_SERVER_ERROR               
             })?.unwrap();
 
             log::info!("`{} ({})` [POST /persons/{{id}}] signed record `{}`", auth_user.username, auth_user.id, record.id.map(|x| x.id.to_string()).unwrap_or_default());
@@ -666,27 +4120,23 @@ pub async fn persons_id_unsign_handler(
     auth_user: middleware::AuthUser,
     Path(id): Path<String>,
 ) -> Result<(), StatusCode> {
-    if auth_user.role < auth::user::UserRole::Admin {
-        return Err(StatusCode::FORBIDDEN);
-    }
+    auth_user.require(auth::permission::Permission::SignaturesSign)?;
 
     // verifying that record isn't unsigned yet or signature is invalid
     
-    let existing_signature = DATABASE.get_signature(&id).await.or_else(|err| {
+    let existing_signature = DATABASE.get_signature(&id).await.map_err(|err| {
         log::error!("`{} ({})` [DELETE /persons/{{id}}/unsign] got database error: {}", auth_user.username, auth_user.id, err);
-        Err(StatusCode::INTERNAL_SERVER_ERROR)
+        StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
     if let Some(signature) = existing_signature {
-        let static_admin = std::env::var("FOLKERS_STATIC_ADMIN_USERNAME").unwrap_or_default();
-
-        if auth_user.username != signature.signed_by && auth_user.username != static_admin {
+        if auth_user.username != signature.signed_by && !auth_user.protected {
             // making some verifications to ensure that signature is still valid
             
             let person_record = DATABASE.get_person(&id).await;
-            let author_record = DATABASE.get_user_by_username(signature.signed_by.clone()).await.or_else(|err| {
+            let author_record = DATABASE.get_user_by_username(signature.signed_by.clone()).await.map_err(|err| {
                 log::error!("`{} ({})` [GET /persons/{{id}}/verify] got database error: {}", auth_user.username, auth_user.id, err);
-                Err(StatusCode::INTERNAL_SERVER_ERROR)
+                StatusCode::INTERNAL_SERVER_ERROR
             })?;
 
             if let Some(author) = author_record
@@ -705,9 +4155,9 @@ pub async fn persons_id_unsign_handler(
             }
         }
 
-        let _ = DATABASE.delete_signature(&id).await.or_else(|err| {
+        let _ = DATABASE.delete_signature(&id).await.map_err(|err| {
             log::error!("`{} ({})` [DELETE /persons/{{id}}/unsign] got database error: {}", auth_user.username, auth_user.id, err);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
         log::info!("`{} ({})` [DELETE /persons/{{id}}/unsign] deleted signature on record `{}`", auth_user.username, auth_user.id, signature.record_id);
@@ -715,7 +4165,7 @@ pub async fn persons_id_unsign_handler(
         return Ok(());
     }
 
-    return Err(StatusCode::NOT_FOUND);
+    Err(StatusCode::NOT_FOUND)
 }
 
 
@@ -724,19 +4174,21 @@ pub async fn persons_id_verify_handler(
     auth_user: middleware::AuthUser,
     Path(id): Path<String>,
 ) -> Result<Json<database::signature::RecordSignatureRecord>, StatusCode> {
+    auth_user.require(auth::permission::Permission::PersonsRead)?;
+
     let record = DATABASE.get_person(&id).await;
 
     match record {
         Some(record) => {
-            let signature_record = DATABASE.get_signature(&id).await.or_else(|err| {
+            let signature_record = DATABASE.get_signature(&id).await.map_err(|err| {
                 log::error!("`{} ({})` [GET /persons/{{id}}/verify] got database error: {}", auth_user.username, auth_user.id, err);
-                Err(StatusCode::INTERNAL_SERVER_ERROR)
+                StatusCode::INTERNAL_SERVER_ERROR
             })?;
 
             if let Some(signature) = signature_record {
-                let author_record = DATABASE.get_user_by_username(signature.signed_by.clone()).await.or_else(|err| {
+                let author_record = DATABASE.get_user_by_username(signature.signed_by.clone()).await.map_err(|err| {
                     log::error!("`{} ({})` [GET /persons/{{id}}/verify] got database error: {}", auth_user.username, auth_user.id, err);
-                    Err(StatusCode::INTERNAL_SERVER_ERROR)
+                    StatusCode::INTERNAL_SERVER_ERROR
                 })?;
 
                 if let Some(author) = author_record {
@@ -748,9 +4200,7 @@ pub async fn persons_id_verify_handler(
                         record_id: signature.record_id.clone(),
                         base64: signature.base64.clone(),
                         pubkey: author.public_key.unwrap()
-                    }).or_else(|_| {
-                        return Err(StatusCode::FORBIDDEN)
-                    })?;
+                    }).map_err(|_| StatusCode::FORBIDDEN)?;
                     
                     if !verification {
                         return Err(StatusCode::FORBIDDEN)
@@ -760,7 +4210,7 @@ pub async fn persons_id_verify_handler(
                 }
             }
 
-            return Err(StatusCode::NOT_FOUND);
+            Err(StatusCode::NOT_FOUND)
         },
         None => Err(StatusCode::NOT_FOUND)
     }
@@ -21,10 +21,10 @@ pub fn generate_signing_keypair() -> (String, String) {
     let mut csprng = OsRng;
     let signing_key: SigningKey = SigningKey::generate(&mut csprng);
 
-    return (
+    (
         general_purpose::STANDARD.encode(signing_key.to_bytes()),
         general_purpose::STANDARD.encode(signing_key.verifying_key().to_bytes())
-    );
+    )
 }
 
 pub fn sign_record(
@@ -81,9 +81,5 @@ pub fn verify_record(
 
 
 fn vector_to_sized<T, const N: usize>(vector: Vec<T>) -> anyhow::Result<[T; N]> {
-    Ok(
-        vector.try_into().or_else(|_| {
-            Err(anyhow!("Unable to get sized slice of vector"))
-        })?
-    )
+    vector.try_into().map_err(|_| anyhow!("Unable to get sized slice of vector"))
 }
@@ -0,0 +1,306 @@
+//! Directory login against OpenLDAP server seeded with `ldap/bootstrap.ldif`.
+//!
+//! Tests are skipped unless `FOLKERS_TEST_LDAP_URL` is set, start the same server as
+//! `compose.ldap.yaml` with published port and run them from `folkers-backend` directory:
+//!
+//! ```sh
+//! docker run --rm -d --name folkers-test-ldap -p 389:389 \
+//!     -e LDAP_ORGANISATION=Folkers -e LDAP_DOMAIN=example.org -e LDAP_ADMIN_PASSWORD=admin \
+//!     -v "$PWD/../ldap/bootstrap.ldif:/container/service/slapd/assets/config/bootstrap/ldif/custom/50-bootstrap.ldif:ro" \
+//!     osixia/openldap:1.5.0 --copy-service
+//! FOLKERS_TEST_LDAP_URL=ldap://localhost:389 cargo test ldap
+//! ```
+
+use ldap3::{Ldap, LdapConnAsync, Mod};
+use reqwest::StatusCode as Status;
+use std::collections::HashSet;
+
+use super::TestApp;
+use crate::auth::backend::{self, ldap::LdapBackend, ldap::LdapConfig};
+
+/// Directory server address, tests are skipped without it
+const URL_VARIABLE: &str = "FOLKERS_TEST_LDAP_URL";
+const ADMIN_DN: &str = "cn=admin,dc=example,dc=org";
+const ADMIN_PASSWORD: &str = "admin";
+const PEOPLE_DN: &str = "ou=people,dc=example,dc=org";
+const GROUPS_DN: &str = "ou=groups,dc=example,dc=org";
+/// Password of every directory user
+const PASSWORD: &str = "folkers";
+
+fn url() -> Option<String> {
+    std::env::var(URL_VARIABLE)
+        .ok()
+        .filter(|url| !url.is_empty())
+}
+
+/// Runs test if directory server is configured
+fn run(test: impl Future<Output = ()>) {
+    if url().is_none() {
+        eprintln!("skipped: `{URL_VARIABLE}` is not set, see module documentation");
+        return;
+    }
+
+    super::run(test);
+}
+
+/// Same configuration as in `compose.ldap.yaml`
+fn config() -> LdapConfig {
+    LdapConfig {
+        url: url().unwrap_or_default(),
+        starttls: false,
+        bind_dn: Some(ADMIN_DN.to_owned()),
+        bind_password: Some(ADMIN_PASSWORD.to_owned()),
+        base_dn: PEOPLE_DN.to_owned(),
+        username_attribute: String::from("uid"),
+        user_filter: None,
+        group_base_dn: GROUPS_DN.to_owned(),
+        group_filter: String::from("(|(member={dn})(uniqueMember={dn})(memberUid={username}))"),
+        role_mapping: vec![
+            (String::from("folkers-admins"), String::from("admin")),
+            (String::from("folkers-editors"), String::from("editor")),
+        ],
+        default_role: None,
+        auto_provision: true,
+        link_existing: false,
+    }
+}
+
+/// Directory users are tried first, static admin stays local
+async fn start() -> TestApp {
+    // unavailable directory is skipped by login, so it must fail here
+    directory().await;
+
+    let backends: Vec<Box<dyn backend::AuthBackend>> = vec![
+        Box::new(LdapBackend::new(config())),
+        Box::new(backend::local::LocalBackend),
+    ];
+
+    TestApp::start(crate::routers::AppState {
+        user_repo: crate::auth::UserRepository::new(backends),
+        ..super::state()
+    })
+    .await
+}
+
+fn user_dn(uid: &str) -> String {
+    format!("uid={uid},{PEOPLE_DN}")
+}
+
+fn group_dn(group: &str) -> String {
+    format!("cn={group},{GROUPS_DN}")
+}
+
+/// Directory administrator connection, used to add and change users during tests
+async fn directory() -> Ldap {
+    let (connection, mut ldap) = LdapConnAsync::new(&url().unwrap_or_default())
+        .await
+        .expect("unable to connect to LDAP server");
+    ldap3::drive!(connection);
+
+    ldap.simple_bind(ADMIN_DN, ADMIN_PASSWORD)
+        .await
+        .and_then(|result| result.success())
+        .expect("unable to bind as directory administrator");
+
+    ldap
+}
+
+/// Creates user with [PASSWORD] (replaces one left by previous run) and adds it to groups
+async fn add_user(ldap: &mut Ldap, uid: &str, groups: &[&str]) {
+    delete_user(ldap, uid, groups).await;
+
+    ldap.add(
+        &user_dn(uid),
+        vec![
+            ("objectClass", HashSet::from(["inetOrgPerson"])),
+            ("uid", HashSet::from([uid])),
+            ("cn", HashSet::from([uid])),
+            ("sn", HashSet::from([uid])),
+            ("userPassword", HashSet::from([PASSWORD])),
+        ],
+    )
+    .await
+    .and_then(|result| result.success())
+    .expect("unable to add directory user");
+
+    for group in groups {
+        set_membership(ldap, uid, group, true).await;
+    }
+}
+
+async fn set_membership(ldap: &mut Ldap, uid: &str, group: &str, member: bool) {
+    let dn = user_dn(uid);
+    let values = HashSet::from([dn.as_str()]);
+    let modification = if member {
+        Mod::Add("member", values)
+    } else {
+        Mod::Delete("member", values)
+    };
+
+    ldap.modify(&group_dn(group), vec![modification])
+        .await
+        .and_then(|result| result.success())
+        .expect("unable to change group membership");
+}
+
+/// Removes user and its memberships (missing ones are ignored)
+async fn delete_user(ldap: &mut Ldap, uid: &str, groups: &[&str]) {
+    let dn = user_dn(uid);
+
+    for group in groups {
+        let _ = ldap
+            .modify(
+                &group_dn(group),
+                vec![Mod::Delete("member", HashSet::from([dn.as_str()]))],
+            )
+            .await;
+    }
+
+    let _ = ldap.delete(&dn).await;
+}
+
+/// Password login, returns the whole auth response
+async fn login(app: &TestApp, username: &str) -> reqwest::Response {
+    app.http
+        .post(app.url("/login"))
+        .json(&serde_json::json!({ "username": username, "password": PASSWORD }))
+        .send()
+        .await
+        .expect("login request failed")
+}
+
+async fn refresh(app: &TestApp, refresh_token: &str) -> reqwest::Response {
+    app.http
+        .post(app.url("/refresh"))
+        .json(&serde_json::json!({ "refresh_token": refresh_token }))
+        .send()
+        .await
+        .expect("refresh request failed")
+}
+
+#[test]
+fn login_binds_as_directory_user() {
+    run(async {
+        let app = start().await;
+
+        let token = app.login("alice", PASSWORD).await;
+        let me = app.me(&token).await;
+
+        assert_eq!(me["username"], "alice");
+        assert_eq!(me["external_id"], "ldap:alice");
+        assert_eq!(me["role"], "admin");
+
+        // wrong directory password isn't passed to local backend
+        let response = app
+            .http
+            .post(app.url("/login"))
+            .json(&serde_json::json!({ "username": "alice", "password": "wrong" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), Status::UNAUTHORIZED);
+
+        // users unknown by directory are still found locally
+        app.login(super::ADMIN_USERNAME, super::ADMIN_PASSWORD)
+            .await;
+    });
+}
+
+#[test]
+fn login_maps_groups_to_roles() {
+    run(async {
+        let app = start().await;
+
+        let token = app.login("bob", PASSWORD).await;
+        assert_eq!(app.me(&token).await["role"], "editor");
+
+        // no mapped group and no default role
+        let mut ldap = directory().await;
+        add_user(&mut ldap, "ldap-ungrouped", &[]).await;
+
+        let response = login(&app, "ldap-ungrouped").await;
+        assert_eq!(response.status(), Status::FORBIDDEN);
+
+        delete_user(&mut ldap, "ldap-ungrouped", &[]).await;
+    });
+}
+
+#[test]
+fn refresh_applies_current_groups() {
+    run(async {
+        let app = start().await;
+        let groups = ["folkers-editors", "folkers-admins"];
+
+        let mut ldap = directory().await;
+        add_user(&mut ldap, "ldap-promoted", &groups[..1]).await;
+
+        let response = login(&app, "ldap-promoted").await;
+        assert_eq!(response.status(), Status::OK);
+
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(
+            app.me(body["token"].as_str().unwrap()).await["role"],
+            "editor"
+        );
+
+        set_membership(&mut ldap, "ldap-promoted", "folkers-editors", false).await;
+        set_membership(&mut ldap, "ldap-promoted", "folkers-admins", true).await;
+
+        let response = refresh(&app, body["refresh_token"].as_str().unwrap()).await;
+        assert_eq!(response.status(), Status::OK);
+        assert_eq!(app.me(&super::token(response).await).await["role"], "admin");
+
+        delete_user(&mut ldap, "ldap-promoted", &groups).await;
+    });
+}
+
+#[test]
+fn leaver_loses_access() {
+    run(async {
+        let app = start().await;
+
+        let mut ldap = directory().await;
+        add_user(&mut ldap, "ldap-leaver", &["folkers-editors"]).await;
+
+        let response = login(&app, "ldap-leaver").await;
+        assert_eq!(response.status(), Status::OK);
+
+        let body: serde_json::Value = response.json().await.unwrap();
+        let token = body["token"].as_str().unwrap();
+
+        let api_token: serde_json::Value = app
+            .http
+            .post(app.url("/me/tokens"))
+            .bearer_auth(token)
+            .json(&serde_json::json!({ "name": "script", "role": "watcher" }))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .expect("unable to create personal access token")
+            .json()
+            .await
+            .unwrap();
+        let api_token = api_token["token"].as_str().unwrap();
+
+        // user lost every mapped group
+        set_membership(&mut ldap, "ldap-leaver", "folkers-editors", false).await;
+
+        let response = app
+            .http
+            .get(app.url("/persons"))
+            .bearer_auth(api_token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), Status::UNAUTHORIZED);
+
+        let response = refresh(&app, body["refresh_token"].as_str().unwrap()).await;
+        assert_eq!(response.status(), Status::UNAUTHORIZED);
+
+        // user was removed from directory
+        delete_user(&mut ldap, "ldap-leaver", &[]).await;
+
+        let response = login(&app, "ldap-leaver").await;
+        assert_eq!(response.status(), Status::UNAUTHORIZED);
+    });
+}
@@ -0,0 +1,184 @@
+//! Integration tests - API is served on random local port with embedded in-memory database.
+//!
+//! Every test starts its own server (with its own state), database is shared, so every test
+//! works with its own users. External services are mocked, except LDAP directory.
+
+use std::{net::SocketAddr, sync::LazyLock};
+use tokio::{runtime::Runtime, sync::OnceCell};
+
+use crate::{DATABASE, auth, database, routers};
+
+mod ldap;
+mod oidc;
+mod persons;
+mod sessions;
+mod throttle;
+mod two_factor;
+mod users;
+mod webauthn;
+
+pub const ADMIN_USERNAME: &str = "static-admin";
+pub const ADMIN_PASSWORD: &str = "static-admin-password";
+/// Password of users created by tests
+pub const PASSWORD: &str = "correct horse battery staple";
+
+/// Database connection and servers are bound to runtime they were started on,
+/// so every test runs on the same one
+static RUNTIME: LazyLock<Runtime> = LazyLock::new(|| {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("unable to start tests runtime")
+});
+
+static SETUP: OnceCell<()> = OnceCell::const_new();
+
+/// Runs test after environment and database are set up
+pub fn run(test: impl Future<Output = ()>) {
+    RUNTIME.block_on(async {
+        SETUP.get_or_init(setup).await;
+        test.await;
+    })
+}
+
+async fn setup() {
+    let keys_dir = std::env::temp_dir().join(format!("folkers-tests-{}", std::process::id()));
+
+    // SAFETY: variables are set before any test reads them, tests wait until setup is finished
+    unsafe {
+        std::env::set_var("FOLKERS_JWT_KEYS_DIR", &keys_dir);
+        std::env::set_var("FOLKERS_STATIC_ADMIN_USERNAME", ADMIN_USERNAME);
+        std::env::set_var("FOLKERS_STATIC_ADMIN_PASSWORD", ADMIN_PASSWORD);
+        // password hashing with default parameters is too slow for debug builds
+        std::env::set_var("FOLKERS_ARGON2_MEMORY_COST", "1024");
+        std::env::set_var("FOLKERS_ARGON2_TIME_COST", "1");
+        // tests with failed logins send their own `X-Real-IP`, so they don't lock each other out
+        std::env::set_var("FOLKERS_TRUST_PROXY_HEADERS", "true");
+    }
+
+    DATABASE
+        .setup("mem://", "folkers", "tests", "root", "root")
+        .await
+        .expect("unable to set up database");
+
+    // signing key is generated once, so servers don't race for it
+    auth::jwt::JwtConfig::new().expect("unable to generate JWT signing key");
+}
+
+/// State with local authentication only, single sign-on and passkeys are disabled
+pub fn state() -> routers::AppState {
+    routers::AppState {
+        user_repo: auth::UserRepository::new(vec![Box::new(auth::backend::local::LocalBackend)]),
+        jwt_service: auth::jwt::JwtService::new(
+            auth::jwt::JwtConfig::new().expect("unable to load JWT signing key"),
+        ),
+        oidc: None,
+        webauthn: None,
+        cookies: None,
+    }
+}
+
+/// Local user with [PASSWORD]
+pub async fn create_user(username: &str, role: &str) {
+    DATABASE
+        .create_user(
+            database::user::CreateUserRecord {
+                username: username.to_owned(),
+                password: auth::UserRepository::hash_password(PASSWORD)
+                    .expect("unable to hash password"),
+                role: role.to_owned(),
+                created_by: String::from("tests"),
+                expires_at: None,
+            },
+            false,
+        )
+        .await
+        .expect("unable to create user")
+        .expect("user is not created");
+}
+
+/// Serves listener on tests runtime until the end of tests
+pub fn spawn(listener: tokio::net::TcpListener, router: axum::Router) {
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            router.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .expect("test server failed");
+    });
+}
+
+/// Random local port listener
+pub async fn listener() -> (tokio::net::TcpListener, SocketAddr) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("unable to bind test server");
+    let address = listener.local_addr().expect("test server has no address");
+
+    (listener, address)
+}
+
+/// Backend API served for a single test
+pub struct TestApp {
+    pub address: SocketAddr,
+    pub http: reqwest::Client,
+}
+
+impl TestApp {
+    pub async fn start(state: routers::AppState) -> Self {
+        let (listener, address) = listener().await;
+        spawn(listener, crate::router(state));
+
+        Self {
+            address,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{path}", self.address)
+    }
+
+    /// Password login, returns access token
+    pub async fn login(&self, username: &str, password: &str) -> String {
+        let response = self
+            .http
+            .post(self.url("/login"))
+            .json(&serde_json::json!({ "username": username, "password": password }))
+            .send()
+            .await
+            .expect("login request failed");
+
+        assert_eq!(
+            response.status(),
+            reqwest::StatusCode::OK,
+            "login of `{username}`"
+        );
+        token(response).await
+    }
+
+    /// Current user (`GET /me`)
+    pub async fn me(&self, token: &str) -> serde_json::Value {
+        self.http
+            .get(self.url("/me"))
+            .bearer_auth(token)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .expect("`/me` request failed")
+            .json()
+            .await
+            .expect("invalid `/me` response")
+    }
+}
+
+/// Access token of [AuthResponse](auth::AuthResponse)
+pub async fn token(response: reqwest::Response) -> String {
+    let body: serde_json::Value = response.json().await.expect("invalid auth response");
+
+    body["token"]
+        .as_str()
+        .expect("auth response has no token")
+        .to_owned()
+}
@@ -0,0 +1,518 @@
+//! Single sign-on against mock identity provider (discovery, keys and token endpoint).
+
+use axum::{
+    Form, Json, Router,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing,
+};
+use base64::{Engine as _, engine::general_purpose};
+use ed25519_dalek::{SigningKey, pkcs8::EncodePrivateKey};
+use jsonwebtoken::{
+    EncodingKey, Header,
+    jwk::{
+        AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+        OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse,
+    },
+};
+use rand_core::OsRng;
+use reqwest::StatusCode as Status;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use super::{ADMIN_USERNAME, TestApp, run};
+use crate::{DATABASE, auth::oidc};
+
+const CLIENT_ID: &str = "folkers";
+const KEY_ID: &str = "mock-idp";
+
+/// Authorization code issued by identity provider
+struct Grant {
+    claims: serde_json::Value,
+    code_challenge: String,
+}
+
+struct IdpState {
+    issuer: String,
+    key: EncodingKey,
+    jwks: JwkSet,
+    grants: Mutex<HashMap<String, Grant>>,
+}
+
+/// Identity provider which approves every login it's asked for
+struct MockIdp {
+    state: Arc<IdpState>,
+}
+
+impl MockIdp {
+    async fn start() -> Self {
+        let (listener, address) = super::listener().await;
+
+        let signing_key = SigningKey::generate(&mut OsRng);
+        let der = signing_key
+            .to_pkcs8_der()
+            .expect("unable to encode signing key");
+
+        let state = Arc::new(IdpState {
+            issuer: format!("http://{address}"),
+            key: EncodingKey::from_ed_der(der.as_bytes()),
+            jwks: JwkSet {
+                keys: vec![Jwk {
+                    common: CommonParameters {
+                        public_key_use: Some(PublicKeyUse::Signature),
+                        key_algorithm: Some(KeyAlgorithm::EdDSA),
+                        key_id: Some(KEY_ID.to_owned()),
+                        ..Default::default()
+                    },
+                    algorithm: AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                        key_type: OctetKeyPairType::OctetKeyPair,
+                        curve: EllipticCurve::Ed25519,
+                        x: general_purpose::URL_SAFE_NO_PAD
+                            .encode(signing_key.verifying_key().as_bytes()),
+                    }),
+                }],
+            },
+            grants: Mutex::new(HashMap::new()),
+        });
+
+        let router = Router::new()
+            .route(
+                "/.well-known/openid-configuration",
+                routing::get(discovery_handler),
+            )
+            .route("/jwks", routing::get(jwks_handler))
+            .route("/token", routing::post(token_handler))
+            .with_state(state.clone());
+
+        super::spawn(listener, router);
+
+        Self { state }
+    }
+
+    /// Configuration with `folkers-admins` and `folkers-editors` groups mapped to roles
+    fn config(&self) -> oidc::OidcConfig {
+        oidc::OidcConfig {
+            issuer: self.state.issuer.clone(),
+            client_id: CLIENT_ID.to_owned(),
+            client_secret: None,
+            redirect_uri: String::from("http://localhost:5173/oidc/callback"),
+            scopes: String::from("openid profile groups"),
+            authorization_endpoint: None,
+            username_claim: String::from("preferred_username"),
+            groups_claim: String::from("groups"),
+            role_mapping: vec![
+                (String::from("folkers-admins"), String::from("admin")),
+                (String::from("folkers-editors"), String::from("editor")),
+            ],
+            default_role: None,
+            auto_provision: true,
+            link_existing: false,
+        }
+    }
+
+    /// Signs user in on identity provider page, returns authorization code
+    fn approve(
+        &self,
+        authorization_url: &str,
+        subject: &str,
+        username: &str,
+        groups: &[&str],
+    ) -> String {
+        let url = reqwest::Url::parse(authorization_url).expect("invalid authorization URL");
+        let params = url.query_pairs().into_owned().collect::<HashMap<_, _>>();
+
+        assert_eq!(params["client_id"], CLIENT_ID);
+        assert_eq!(params["code_challenge_method"], "S256");
+
+        let now = chrono::Utc::now().timestamp();
+        let code = crate::auth::webauthn::ceremony_id();
+
+        self.state.grants.lock().unwrap().insert(
+            code.clone(),
+            Grant {
+                claims: serde_json::json!({
+                    "iss": self.state.issuer,
+                    "aud": CLIENT_ID,
+                    "sub": subject,
+                    "iat": now,
+                    "exp": now + 300,
+                    "nonce": params["nonce"],
+                    "preferred_username": username,
+                    "groups": groups,
+                }),
+                code_challenge: params["code_challenge"].clone(),
+            },
+        );
+
+        code
+    }
+}
+
+async fn discovery_handler(State(state): State<Arc<IdpState>>) -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "issuer": state.issuer,
+        "authorization_endpoint": format!("{}/authorize", state.issuer),
+        "token_endpoint": format!("{}/token", state.issuer),
+        "jwks_uri": format!("{}/jwks", state.issuer),
+    }))
+}
+
+async fn jwks_handler(State(state): State<Arc<IdpState>>) -> Json<JwkSet> {
+    Json(state.jwks.clone())
+}
+
+async fn token_handler(
+    State(state): State<Arc<IdpState>>,
+    Form(form): Form<HashMap<String, String>>,
+) -> Response {
+    let grant = form
+        .get("code")
+        .and_then(|code| state.grants.lock().unwrap().remove(code));
+
+    let verifier = form.get("code_verifier").cloned().unwrap_or_default();
+    let challenge = general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+
+    let Some(grant) = grant.filter(|grant| grant.code_challenge == challenge) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "invalid_grant" })),
+        )
+            .into_response();
+    };
+
+    let mut header = Header::new(jsonwebtoken::Algorithm::EdDSA);
+    header.kid = Some(KEY_ID.to_owned());
+
+    let id_token =
+        jsonwebtoken::encode(&header, &grant.claims, &state.key).expect("unable to sign ID token");
+
+    Json(serde_json::json!({
+        "id_token": id_token,
+        "access_token": "mock-access-token",
+        "token_type": "Bearer",
+    }))
+    .into_response()
+}
+
+async fn start(config: oidc::OidcConfig) -> TestApp {
+    TestApp::start(crate::routers::AppState {
+        oidc: Some(oidc::OidcProvider::new(config).expect("unable to build OIDC provider")),
+        ..super::state()
+    })
+    .await
+}
+
+/// Starts login on backend, returns authorization URL and state
+async fn authorize(app: &TestApp) -> (String, String) {
+    let body: serde_json::Value = app
+        .http
+        .get(app.url("/oidc/authorize"))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .expect("`/oidc/authorize` request failed")
+        .json()
+        .await
+        .expect("invalid `/oidc/authorize` response");
+
+    let authorization_url = body["authorization_url"]
+        .as_str()
+        .expect("authorization URL is missing")
+        .to_owned();
+
+    let state = reqwest::Url::parse(&authorization_url)
+        .expect("invalid authorization URL")
+        .query_pairs()
+        .find(|(name, _)| name == "state")
+        .map(|(_, value)| value.into_owned())
+        .expect("state is missing");
+
+    (authorization_url, state)
+}
+
+async fn callback(app: &TestApp, code: &str, state: &str) -> reqwest::Response {
+    app.http
+        .post(app.url("/oidc/callback"))
+        .json(&serde_json::json!({ "code": code, "state": state }))
+        .send()
+        .await
+        .expect("`/oidc/callback` request failed")
+}
+
+/// Complete login: backend -> identity provider -> backend callback
+async fn sign_in(
+    app: &TestApp,
+    idp: &MockIdp,
+    subject: &str,
+    username: &str,
+    groups: &[&str],
+) -> reqwest::Response {
+    let (authorization_url, state) = authorize(app).await;
+    let code = idp.approve(&authorization_url, subject, username, groups);
+
+    callback(app, &code, &state).await
+}
+
+#[test]
+fn callback_provisions_user_with_mapped_role() {
+    run(async {
+        let idp = MockIdp::start().await;
+        let app = start(idp.config()).await;
+
+        let response = sign_in(
+            &app,
+            &idp,
+            "sub-provisioned",
+            "oidc-provisioned",
+            &["folkers-editors"],
+        )
+        .await;
+        assert_eq!(response.status(), Status::OK);
+
+        let me = app.me(&super::token(response).await).await;
+        assert_eq!(me["username"], "oidc-provisioned");
+        assert_eq!(me["role"], "editor");
+        assert_eq!(me["external_id"], "oidc:sub-provisioned");
+    });
+}
+
+#[test]
+fn callback_applies_current_groups_on_every_login() {
+    run(async {
+        let idp = MockIdp::start().await;
+        let app = start(idp.config()).await;
+
+        let response = sign_in(
+            &app,
+            &idp,
+            "sub-promoted",
+            "oidc-promoted",
+            &["folkers-editors"],
+        )
+        .await;
+        assert_eq!(response.status(), Status::OK);
+
+        // the first mapped group wins
+        let response = sign_in(
+            &app,
+            &idp,
+            "sub-promoted",
+            "oidc-promoted",
+            &["folkers-editors", "folkers-admins"],
+        )
+        .await;
+        assert_eq!(response.status(), Status::OK);
+        assert_eq!(app.me(&super::token(response).await).await["role"], "admin");
+
+        // groups without mapping and without default role deny access
+        let response = sign_in(&app, &idp, "sub-promoted", "oidc-promoted", &["accounting"]).await;
+        assert_eq!(response.status(), Status::FORBIDDEN);
+    });
+}
+
+#[test]
+fn callback_grants_default_role_to_unmapped_groups() {
+    run(async {
+        let idp = MockIdp::start().await;
+        let app = start(oidc::OidcConfig {
+            default_role: Some(String::from("watcher")),
+            ..idp.config()
+        })
+        .await;
+
+        let response = sign_in(&app, &idp, "sub-watcher", "oidc-watcher", &[]).await;
+        assert_eq!(response.status(), Status::OK);
+        assert_eq!(
+            app.me(&super::token(response).await).await["role"],
+            "watcher"
+        );
+    });
+}
+
+#[test]
+fn callback_requires_account_if_auto_provisioning_is_disabled() {
+    run(async {
+        let idp = MockIdp::start().await;
+        let app = start(oidc::OidcConfig {
+            auto_provision: false,
+            ..idp.config()
+        })
+        .await;
+
+        let response = sign_in(
+            &app,
+            &idp,
+            "sub-unknown",
+            "oidc-unknown",
+            &["folkers-editors"],
+        )
+        .await;
+        assert_eq!(response.status(), Status::FORBIDDEN);
+
+        let record = DATABASE
+            .get_user_by_username(String::from("oidc-unknown"))
+            .await
+            .unwrap();
+        assert!(record.is_none());
+    });
+}
+
+#[test]
+fn callback_links_existing_local_user() {
+    run(async {
+        super::create_user("oidc-linked", "watcher").await;
+
+        let idp = MockIdp::start().await;
+        let app = start(oidc::OidcConfig {
+            link_existing: true,
+            ..idp.config()
+        })
+        .await;
+
+        let local = DATABASE
+            .get_user_by_username(String::from("oidc-linked"))
+            .await
+            .unwrap()
+            .unwrap();
+
+        let response = sign_in(
+            &app,
+            &idp,
+            "sub-linked",
+            "oidc-linked",
+            &["folkers-editors"],
+        )
+        .await;
+        assert_eq!(response.status(), Status::OK);
+
+        let linked = DATABASE
+            .get_user_by_username(String::from("oidc-linked"))
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(linked.id, local.id);
+        assert_eq!(linked.external_id.as_deref(), Some("oidc:sub-linked"));
+        assert_eq!(linked.role, "editor");
+
+        // the same account is found by subject on the next login
+        let response = sign_in(
+            &app,
+            &idp,
+            "sub-linked",
+            "oidc-linked",
+            &["folkers-editors"],
+        )
+        .await;
+        assert_eq!(response.status(), Status::OK);
+    });
+}
+
+#[test]
+fn callback_keeps_local_user_unlinked_by_default() {
+    run(async {
+        super::create_user("oidc-taken", "watcher").await;
+
+        let idp = MockIdp::start().await;
+        let app = start(idp.config()).await;
+
+        let response = sign_in(&app, &idp, "sub-taken", "oidc-taken", &["folkers-admins"]).await;
+        assert_eq!(response.status(), Status::CONFLICT);
+
+        let record = DATABASE
+            .get_user_by_username(String::from("oidc-taken"))
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(record.external_id, None);
+        assert_eq!(record.role, "watcher");
+    });
+}
+
+#[test]
+fn callback_refuses_to_link_protected_user() {
+    run(async {
+        let idp = MockIdp::start().await;
+        let app = start(oidc::OidcConfig {
+            link_existing: true,
+            ..idp.config()
+        })
+        .await;
+
+        let response = sign_in(
+            &app,
+            &idp,
+            "sub-static-admin",
+            ADMIN_USERNAME,
+            &["folkers-editors"],
+        )
+        .await;
+        assert_eq!(response.status(), Status::CONFLICT);
+
+        let record = DATABASE
+            .get_user_by_username(ADMIN_USERNAME.to_owned())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(record.external_id, None);
+        assert_eq!(record.role, "admin");
+    });
+}
+
+#[test]
+fn callback_accepts_every_login_only_once() {
+    run(async {
+        let idp = MockIdp::start().await;
+        let app = start(idp.config()).await;
+
+        let (authorization_url, state) = authorize(&app).await;
+
+        let code = idp.approve(
+            &authorization_url,
+            "sub-replayed",
+            "oidc-replayed",
+            &["folkers-editors"],
+        );
+        assert_eq!(callback(&app, &code, &state).await.status(), Status::OK);
+
+        let code = idp.approve(
+            &authorization_url,
+            "sub-replayed",
+            "oidc-replayed",
+            &["folkers-editors"],
+        );
+        assert_eq!(
+            callback(&app, &code, &state).await.status(),
+            Status::UNAUTHORIZED
+        );
+    });
+}
+
+#[test]
+fn callback_rejects_code_issued_for_another_login() {
+    run(async {
+        let idp = MockIdp::start().await;
+        let app = start(idp.config()).await;
+
+        let (authorization_url, _) = authorize(&app).await;
+        let (_, state) = authorize(&app).await;
+
+        // PKCE verifier and nonce belong to the other login
+        let code = idp.approve(
+            &authorization_url,
+            "sub-swapped",
+            "oidc-swapped",
+            &["folkers-admins"],
+        );
+        assert_eq!(
+            callback(&app, &code, &state).await.status(),
+            Status::UNAUTHORIZED
+        );
+    });
+}
@@ -0,0 +1,86 @@
+//! Person records and their history.
+
+use reqwest::StatusCode as Status;
+
+use super::{ADMIN_PASSWORD, ADMIN_USERNAME, PASSWORD, TestApp, run};
+
+/// Creates person as user, returns record's identifier
+async fn create_person(app: &TestApp, token: &str, surname: &str) -> String {
+    let record: serde_json::Value = app
+        .http
+        .post(app.url("/persons/create"))
+        .bearer_auth(token)
+        .json(&serde_json::json!({
+            "name": "Ivan",
+            "surname": surname,
+            "patronymic": "Ivanovich",
+            "birthday": "2000-01-01T00:00:00Z",
+            "city": "",
+            "intented_address": "",
+            "summary": "",
+            "past": "",
+            "traits_good": "",
+            "traits_bad": "",
+            "avatar": "",
+            "media": [],
+        }))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .expect("`/persons/create` request failed")
+        .json()
+        .await
+        .unwrap();
+
+    record["id"]["id"]["String"]
+        .as_str()
+        .expect("person record has no id")
+        .to_owned()
+}
+
+async fn get(app: &TestApp, token: &str, path: &str) -> Status {
+    app.http
+        .get(app.url(path))
+        .bearer_auth(token)
+        .send()
+        .await
+        .expect("request failed")
+        .status()
+}
+
+#[test]
+fn history_of_trashed_person_is_hidden() {
+    run(async {
+        super::create_user("history-editor", "editor").await;
+
+        let app = TestApp::start(super::state()).await;
+        let token = app.login("history-editor", PASSWORD).await;
+        let admin = app.login(ADMIN_USERNAME, ADMIN_PASSWORD).await;
+
+        let id = create_person(&app, &token, "History").await;
+        let paths = [
+            format!("/persons/{id}/revisions"),
+            format!("/persons/{id}/revisions/1"),
+            format!("/persons/{id}/revisions/diff?from=1&to=1"),
+        ];
+
+        for path in &paths {
+            assert_eq!(get(&app, &token, path).await, Status::OK, "{path}");
+        }
+
+        let response = app
+            .http
+            .delete(app.url(&format!("/persons/{id}")))
+            .bearer_auth(&token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), Status::OK);
+
+        for path in &paths {
+            assert_eq!(get(&app, &token, path).await, Status::NOT_FOUND, "{path}");
+            // trash managers still see it
+            assert_eq!(get(&app, &admin, path).await, Status::OK, "{path}");
+        }
+    });
+}
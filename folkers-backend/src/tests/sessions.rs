@@ -0,0 +1,104 @@
+//! Refresh token rotation, sessions and personal access tokens.
+
+use reqwest::StatusCode as Status;
+
+use super::{PASSWORD, TestApp, run};
+
+async fn refresh(app: &TestApp, refresh_token: &str) -> reqwest::Response {
+    app.http
+        .post(app.url("/refresh"))
+        .json(&serde_json::json!({ "refresh_token": refresh_token }))
+        .send()
+        .await
+        .expect("refresh request failed")
+}
+
+async fn refresh_token(response: reqwest::Response) -> String {
+    let body: serde_json::Value = response.json().await.expect("invalid auth response");
+
+    body["refresh_token"]
+        .as_str()
+        .expect("auth response has no refresh token")
+        .to_owned()
+}
+
+#[test]
+fn reused_refresh_token_revokes_chain() {
+    run(async {
+        super::create_user("sessions-rotated", "watcher").await;
+
+        let app = TestApp::start(super::state()).await;
+
+        let response = app
+            .http
+            .post(app.url("/login"))
+            .json(&serde_json::json!({ "username": "sessions-rotated", "password": PASSWORD }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), Status::OK);
+        let first = refresh_token(response).await;
+
+        let response = refresh(&app, &first).await;
+        assert_eq!(response.status(), Status::OK);
+        let second = refresh_token(response).await;
+        assert_ne!(first, second, "refresh token is rotated");
+
+        // rotated token is used again (stolen copy), so the whole chain is revoked
+        let response = refresh(&app, &first).await;
+        assert_eq!(response.status(), Status::UNAUTHORIZED);
+
+        let response = refresh(&app, &second).await;
+        assert_eq!(response.status(), Status::UNAUTHORIZED);
+    });
+}
+
+#[test]
+fn password_change_revokes_api_tokens() {
+    run(async {
+        super::create_user("sessions-password", "watcher").await;
+
+        let app = TestApp::start(super::state()).await;
+        let token = app.login("sessions-password", PASSWORD).await;
+
+        let api_token: serde_json::Value = app
+            .http
+            .post(app.url("/me/tokens"))
+            .bearer_auth(&token)
+            .json(&serde_json::json!({ "name": "script", "role": "watcher" }))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .expect("unable to create personal access token")
+            .json()
+            .await
+            .unwrap();
+        let api_token = api_token["token"].as_str().unwrap();
+        assert_eq!(app.me(api_token).await["username"], "sessions-password");
+
+        let response = app
+            .http
+            .post(app.url("/me/password"))
+            .bearer_auth(&token)
+            .json(&serde_json::json!({
+                "current_password": PASSWORD,
+                "new_password": "Tr0ub4dor&3 horse battery",
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), Status::OK);
+
+        let response = app
+            .http
+            .get(app.url("/me"))
+            .bearer_auth(api_token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), Status::UNAUTHORIZED);
+
+        // session which changed password is kept
+        assert_eq!(app.me(&token).await["username"], "sessions-password");
+    });
+}
@@ -0,0 +1,103 @@
+//! Brute-force protection of login: back-off, reset and unlock.
+
+use reqwest::StatusCode as Status;
+
+use super::{ADMIN_PASSWORD, ADMIN_USERNAME, PASSWORD, TestApp, run};
+use crate::{DATABASE, auth::throttle};
+
+/// Failures allowed before back-off starts
+const FREE_ATTEMPTS: usize = 3;
+
+/// Password login from client IP (trusted proxy header)
+async fn login(app: &TestApp, ip: &str, username: &str, password: &str) -> reqwest::Response {
+    app.http
+        .post(app.url("/login"))
+        .header("x-real-ip", ip)
+        .json(&serde_json::json!({ "username": username, "password": password }))
+        .send()
+        .await
+        .expect("login request failed")
+}
+
+async fn unlock(app: &TestApp, token: &str, username: &str, ip: &str) -> Status {
+    app.http
+        .delete(app.url(&format!("/users/{username}/lockout?ip={ip}")))
+        .bearer_auth(token)
+        .send()
+        .await
+        .expect("unlock request failed")
+        .status()
+}
+
+#[test]
+fn failed_logins_lock_until_unlocked() {
+    run(async {
+        const IP: &str = "192.0.2.41";
+
+        super::create_user("throttle-locked", "watcher").await;
+        super::create_user("throttle-admin", "admin").await;
+
+        let app = TestApp::start(super::state()).await;
+
+        for _ in 0..=FREE_ATTEMPTS {
+            let response = login(&app, IP, "throttle-locked", "wrong").await;
+            assert_eq!(response.status(), Status::UNAUTHORIZED);
+        }
+
+        // even correct password waits for back-off
+        let response = login(&app, IP, "throttle-locked", PASSWORD).await;
+        assert_eq!(response.status(), Status::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key("retry-after"));
+
+        // user is managed by its creator only
+        let admin = app.login("throttle-admin", PASSWORD).await;
+        assert_eq!(
+            unlock(&app, &admin, "throttle-locked", IP).await,
+            Status::FORBIDDEN
+        );
+
+        let admin = app.login(ADMIN_USERNAME, ADMIN_PASSWORD).await;
+        assert_eq!(
+            unlock(&app, &admin, "throttle-locked", IP).await,
+            Status::OK
+        );
+
+        let response = login(&app, IP, "throttle-locked", PASSWORD).await;
+        assert_eq!(response.status(), Status::OK);
+    });
+}
+
+#[test]
+fn successful_login_resets_client_ip() {
+    run(async {
+        const IP: &str = "192.0.2.42";
+
+        super::create_user("throttle-reset", "watcher").await;
+
+        let app = TestApp::start(super::state()).await;
+
+        for _ in 0..FREE_ATTEMPTS {
+            let response = login(&app, IP, "throttle-unknown", "wrong").await;
+            assert_eq!(response.status(), Status::UNAUTHORIZED);
+        }
+
+        assert!(
+            DATABASE
+                .get_login_attempt(throttle::ip_key(IP))
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        let response = login(&app, IP, "throttle-reset", PASSWORD).await;
+        assert_eq!(response.status(), Status::OK);
+
+        assert!(
+            DATABASE
+                .get_login_attempt(throttle::ip_key(IP))
+                .await
+                .unwrap()
+                .is_none()
+        );
+    });
+}
@@ -0,0 +1,168 @@
+//! Login with TOTP second factor.
+
+use reqwest::StatusCode as Status;
+use totp_rs::{Algorithm, Secret, TOTP};
+
+use super::{PASSWORD, TestApp, run};
+use crate::{DATABASE, auth};
+
+/// Enables two-factor authentication for user, returns user identifier and TOTP secret
+async fn enable_totp(username: &str) -> (String, String) {
+    let record = DATABASE
+        .get_user_by_username(username.to_owned())
+        .await
+        .unwrap()
+        .expect("user not found");
+    let id = record.id.unwrap().id.to_string();
+    let secret = auth::totp::generate_secret();
+
+    DATABASE
+        .update_user_totp(&id, Some(secret.clone()), true, Vec::new())
+        .await
+        .expect("unable to enable two-factor authentication");
+
+    (id, secret)
+}
+
+/// Authenticator app code for time step
+fn code(secret: &str, step: u64) -> String {
+    let secret = Secret::Encoded(secret.to_owned()).to_bytes().unwrap();
+    let totp = TOTP::new(
+        Algorithm::SHA1,
+        6,
+        0,
+        30,
+        secret,
+        None,
+        String::from("tests"),
+    )
+    .unwrap();
+
+    totp.generate(step * 30)
+}
+
+fn current_step() -> u64 {
+    chrono::Utc::now().timestamp() as u64 / 30
+}
+
+/// Failed attempts are counted for client IP, so every test uses its own one
+async fn post(app: &TestApp, path: &str, ip: &str, body: serde_json::Value) -> reqwest::Response {
+    app.http
+        .post(app.url(path))
+        .header("x-real-ip", ip)
+        .json(&body)
+        .send()
+        .await
+        .expect("request failed")
+}
+
+/// Password login, returns challenge token
+async fn challenge(app: &TestApp, username: &str, ip: &str) -> String {
+    let response = post(
+        app,
+        "/login",
+        ip,
+        serde_json::json!({ "username": username, "password": PASSWORD }),
+    )
+    .await;
+    assert_eq!(response.status(), Status::ACCEPTED);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["challenge"], "totp");
+
+    body["challenge_token"].as_str().unwrap().to_owned()
+}
+
+async fn answer(app: &TestApp, ip: &str, challenge_token: &str, code: &str) -> Status {
+    post(
+        app,
+        "/login/2fa",
+        ip,
+        serde_json::json!({ "challenge_token": challenge_token, "code": code }),
+    )
+    .await
+    .status()
+}
+
+#[test]
+fn challenge_is_answered_once() {
+    run(async {
+        const IP: &str = "192.0.2.31";
+
+        super::create_user("2fa-challenge", "watcher").await;
+        let (_, secret) = enable_totp("2fa-challenge").await;
+
+        let app = TestApp::start(super::state()).await;
+        let token = challenge(&app, "2fa-challenge", IP).await;
+
+        // mistyped code doesn't use up the challenge
+        assert_eq!(
+            answer(&app, IP, &token, "abcdef").await,
+            Status::UNAUTHORIZED
+        );
+
+        let step = current_step();
+        assert_eq!(
+            answer(&app, IP, &token, &code(&secret, step)).await,
+            Status::OK
+        );
+        assert_eq!(
+            answer(&app, IP, &token, &code(&secret, step + 1)).await,
+            Status::UNAUTHORIZED
+        );
+    });
+}
+
+#[test]
+fn totp_code_is_accepted_once() {
+    run(async {
+        const IP: &str = "192.0.2.32";
+
+        super::create_user("2fa-replay", "watcher").await;
+        let (_, secret) = enable_totp("2fa-replay").await;
+
+        let app = TestApp::start(super::state()).await;
+        let step = current_step();
+
+        let token = challenge(&app, "2fa-replay", IP).await;
+        assert_eq!(
+            answer(&app, IP, &token, &code(&secret, step)).await,
+            Status::OK
+        );
+
+        let token = challenge(&app, "2fa-replay", IP).await;
+        assert_eq!(
+            answer(&app, IP, &token, &code(&secret, step)).await,
+            Status::UNAUTHORIZED
+        );
+        assert_eq!(
+            answer(&app, IP, &token, &code(&secret, step + 1)).await,
+            Status::OK
+        );
+    });
+}
+
+#[test]
+fn challenge_must_match_two_factor_state() {
+    run(async {
+        const IP: &str = "192.0.2.33";
+
+        super::create_user("2fa-state", "watcher").await;
+        let (id, secret) = enable_totp("2fa-state").await;
+
+        let app = TestApp::start(super::state()).await;
+        let token = challenge(&app, "2fa-state", IP).await;
+
+        // two-factor authentication was reset to pending enrollment meanwhile
+        DATABASE
+            .update_user_totp(&id, Some(secret.clone()), false, Vec::new())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            answer(&app, IP, &token, &code(&secret, current_step())).await,
+            Status::UNAUTHORIZED
+        );
+        assert!(!DATABASE.get_user(&id).await.unwrap().totp_enabled);
+    });
+}
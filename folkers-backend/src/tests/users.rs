@@ -0,0 +1,64 @@
+//! User management by administrators.
+
+use reqwest::StatusCode as Status;
+
+use super::{ADMIN_PASSWORD, ADMIN_USERNAME, TestApp, run};
+
+async fn patch_user(
+    app: &TestApp,
+    token: &str,
+    username: &str,
+    payload: serde_json::Value,
+) -> reqwest::Response {
+    app.http
+        .patch(app.url(&format!("/users/{username}")))
+        .bearer_auth(token)
+        .json(&payload)
+        .send()
+        .await
+        .expect("`PATCH /users/{username}` request failed")
+}
+
+#[test]
+fn patch_keeps_username_and_returns_updated_user() {
+    run(async {
+        super::create_user("users-patched", "watcher").await;
+
+        let app = TestApp::start(super::state()).await;
+        let admin = app.login(ADMIN_USERNAME, ADMIN_PASSWORD).await;
+
+        // records and signatures refer to username
+        let response = patch_user(
+            &app,
+            &admin,
+            "users-patched",
+            serde_json::json!({
+                "username": "users-renamed",
+                "password": "",
+                "role": "watcher",
+                "created_by": "",
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), Status::BAD_REQUEST);
+
+        let response = patch_user(
+            &app,
+            &admin,
+            "users-patched",
+            serde_json::json!({
+                "username": "users-patched",
+                "password": "",
+                "role": "editor",
+                "created_by": "",
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), Status::OK);
+
+        let user: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(user["username"], "users-patched");
+        assert_eq!(user["role"], "editor");
+        assert_eq!(user["created_by"], "tests");
+    });
+}
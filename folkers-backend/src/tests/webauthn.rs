@@ -0,0 +1,304 @@
+//! Passkey registration and login with software authenticator.
+
+use reqwest::StatusCode as Status;
+use webauthn_authenticator_rs::{AuthenticatorBackend, softtoken::SoftToken};
+use webauthn_rs::prelude::{CreationChallengeResponse, RequestChallengeResponse, Url};
+
+use super::{PASSWORD, TestApp, run};
+use crate::auth::webauthn::WebauthnConfig;
+
+const ORIGIN: &str = "http://localhost:5173";
+/// Time for user to interact with authenticator (milliseconds)
+const TIMEOUT: u32 = 60_000;
+
+/// Authenticator which verifies user (passkeys require user verification)
+fn authenticator() -> SoftToken {
+    let (token, _) = SoftToken::new(true).expect("unable to create software authenticator");
+    token
+}
+
+/// Copy of authenticator with its keys and current signature counter
+fn clone(token: &SoftToken) -> SoftToken {
+    let data = token
+        .to_cbor()
+        .expect("unable to export software authenticator");
+    SoftToken::from_cbor(&data).expect("unable to import software authenticator")
+}
+
+fn origin() -> Url {
+    Url::parse(ORIGIN).unwrap()
+}
+
+async fn start() -> TestApp {
+    let config = WebauthnConfig {
+        rp_id: String::from("localhost"),
+        rp_origin: origin(),
+        rp_name: String::from("Folkers"),
+    };
+
+    TestApp::start(crate::routers::AppState {
+        webauthn: Some(std::sync::Arc::new(
+            config.build().expect("unable to build relying party"),
+        )),
+        ..super::state()
+    })
+    .await
+}
+
+/// Registers authenticator for user, returns passkey record
+async fn register(
+    app: &TestApp,
+    token: &str,
+    authenticator: &mut SoftToken,
+    name: &str,
+) -> serde_json::Value {
+    let challenge: serde_json::Value = app
+        .http
+        .post(app.url("/me/passkeys/register"))
+        .bearer_auth(token)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .expect("`/me/passkeys/register` request failed")
+        .json()
+        .await
+        .unwrap();
+
+    let options: CreationChallengeResponse =
+        serde_json::from_value(challenge["options"].clone()).expect("invalid creation options");
+    let credential = authenticator
+        .perform_register(origin(), options.public_key, TIMEOUT)
+        .expect("authenticator refused registration");
+
+    app.http
+        .post(app.url("/me/passkeys"))
+        .bearer_auth(token)
+        .json(&serde_json::json!({
+            "ceremony_id": challenge["ceremony_id"],
+            "name": name,
+            "credential": credential,
+        }))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .expect("`/me/passkeys` request failed")
+        .json()
+        .await
+        .unwrap()
+}
+
+/// Passkey login, `None` if login can't be started (user has no passkeys)
+async fn login(
+    app: &TestApp,
+    username: &str,
+    authenticator: &mut SoftToken,
+) -> Option<reqwest::Response> {
+    let response = app
+        .http
+        .post(app.url("/login/passkey"))
+        .json(&serde_json::json!({ "username": username }))
+        .send()
+        .await
+        .expect("`/login/passkey` request failed");
+
+    if response.status() == Status::UNAUTHORIZED {
+        return None;
+    }
+
+    let challenge: serde_json::Value = response
+        .error_for_status()
+        .expect("`/login/passkey` request failed")
+        .json()
+        .await
+        .unwrap();
+
+    let options: RequestChallengeResponse =
+        serde_json::from_value(challenge["options"].clone()).expect("invalid request options");
+    let credential = authenticator
+        .perform_auth(origin(), options.public_key, TIMEOUT)
+        .expect("authenticator refused authentication");
+
+    Some(
+        app.http
+            .post(app.url("/login/passkey/finish"))
+            .json(&serde_json::json!({
+                "ceremony_id": challenge["ceremony_id"],
+                "credential": credential,
+            }))
+            .send()
+            .await
+            .expect("`/login/passkey/finish` request failed"),
+    )
+}
+
+fn passkey_id(record: &serde_json::Value) -> &str {
+    record["id"]["id"]["String"]
+        .as_str()
+        .expect("passkey record has no id")
+}
+
+#[test]
+fn registered_passkey_logs_user_in() {
+    run(async {
+        super::create_user("passkey-user", "watcher").await;
+
+        let app = start().await;
+        let token = app.login("passkey-user", PASSWORD).await;
+        let mut authenticator = authenticator();
+
+        let record = register(&app, &token, &mut authenticator, "Laptop").await;
+        assert_eq!(record["name"], "Laptop");
+        assert_eq!(record["passkey"], "", "credential internals are redacted");
+
+        let response = login(&app, "passkey-user", &mut authenticator)
+            .await
+            .expect("passkey login wasn't started");
+        assert_eq!(response.status(), Status::OK);
+
+        let token = super::token(response).await;
+        assert_eq!(app.me(&token).await["username"], "passkey-user");
+
+        let passkeys: Vec<serde_json::Value> = app
+            .http
+            .get(app.url("/me/passkeys"))
+            .bearer_auth(&token)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+
+        assert_eq!(passkeys.len(), 1);
+        assert!(!passkeys[0]["last_used"].is_null());
+
+        // passkey belongs to its user only
+        let response = login(&app, "passkey-user-unknown", &mut authenticator).await;
+        assert!(response.is_none());
+    });
+}
+
+#[test]
+fn registration_challenge_is_answered_once() {
+    run(async {
+        super::create_user("passkey-replay", "watcher").await;
+
+        let app = start().await;
+        let token = app.login("passkey-replay", PASSWORD).await;
+
+        let challenge: serde_json::Value = app
+            .http
+            .post(app.url("/me/passkeys/register"))
+            .bearer_auth(&token)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+
+        let options: CreationChallengeResponse =
+            serde_json::from_value(challenge["options"].clone()).unwrap();
+        let credential = authenticator()
+            .perform_register(origin(), options.public_key, TIMEOUT)
+            .unwrap();
+
+        let payload = serde_json::json!({
+            "ceremony_id": challenge["ceremony_id"],
+            "name": "Key",
+            "credential": credential,
+        });
+
+        for expected in [Status::OK, Status::BAD_REQUEST] {
+            let response = app
+                .http
+                .post(app.url("/me/passkeys"))
+                .bearer_auth(&token)
+                .json(&payload)
+                .send()
+                .await
+                .unwrap();
+
+            assert_eq!(response.status(), expected);
+        }
+    });
+}
+
+#[test]
+fn cloned_authenticator_is_rejected() {
+    run(async {
+        super::create_user("passkey-cloned", "watcher").await;
+
+        let app = start().await;
+        let token = app.login("passkey-cloned", PASSWORD).await;
+        let mut authenticator = authenticator();
+
+        register(&app, &token, &mut authenticator, "Security key").await;
+
+        // copy made right after registration keeps the old signature counter
+        let mut cloned = clone(&authenticator);
+
+        for _ in 0..2 {
+            let response = login(&app, "passkey-cloned", &mut authenticator)
+                .await
+                .unwrap();
+            assert_eq!(response.status(), Status::OK);
+        }
+
+        let response = login(&app, "passkey-cloned", &mut cloned).await.unwrap();
+        assert_eq!(response.status(), Status::UNAUTHORIZED);
+
+        // stored counter isn't moved back by rejected login
+        let response = login(&app, "passkey-cloned", &mut authenticator)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), Status::OK);
+    });
+}
+
+#[test]
+fn deleted_passkey_no_longer_logs_in() {
+    run(async {
+        super::create_user("passkey-deleted", "watcher").await;
+        super::create_user("passkey-stranger", "watcher").await;
+
+        let app = start().await;
+        let token = app.login("passkey-deleted", PASSWORD).await;
+        let mut authenticator = authenticator();
+
+        let record = register(&app, &token, &mut authenticator, "Phone").await;
+        let url = app.url(&format!("/me/passkeys/{}", passkey_id(&record)));
+
+        // passkey of another user looks like missing one
+        let stranger = app.login("passkey-stranger", PASSWORD).await;
+        let response = app
+            .http
+            .delete(&url)
+            .bearer_auth(&stranger)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), Status::NOT_FOUND);
+
+        let response = app
+            .http
+            .delete(&url)
+            .bearer_auth(&token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), Status::OK);
+
+        let response = app
+            .http
+            .delete(&url)
+            .bearer_auth(&token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), Status::NOT_FOUND);
+
+        let response = login(&app, "passkey-deleted", &mut authenticator).await;
+        assert!(response.is_none());
+    });
+}
@@ -0,0 +1,47 @@
+//! Trash bin - deleted persons records are kept in trash (hidden from lists, search and record
+//! endpoints) and can be restored by admins. Records older than retention period are purged
+//! permanently by background task.
+
+use std::time::Duration;
+
+use crate::DATABASE;
+
+/// Default number of days records are kept in trash
+const DEFAULT_RETENTION_DAYS: i64 = 30;
+/// How often trash is checked for outdated records
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Reads retention period from `FOLKERS_TRASH_RETENTION_DAYS` (`None` if automatic purge is disabled with `0`)
+pub fn retention_from_env() -> Result<Option<chrono::Duration>, anyhow::Error> {
+    let days = match std::env::var("FOLKERS_TRASH_RETENTION_DAYS") {
+        Ok(value) => value.parse::<i64>().map_err(|error| {
+            anyhow::Error::msg(format!("Unable to parse FOLKERS_TRASH_RETENTION_DAYS: {error}"))
+        })?,
+        Err(_) => DEFAULT_RETENTION_DAYS,
+    };
+
+    match days {
+        ..0 => Err(anyhow::Error::msg(
+            "FOLKERS_TRASH_RETENTION_DAYS can't be negative",
+        )),
+        0 => Ok(None),
+        days => Ok(Some(chrono::Duration::days(days))),
+    }
+}
+
+/// Periodically purges records which are in trash longer than `retention`
+pub fn spawn_purge_task(retention: chrono::Duration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            match DATABASE.purge_trashed_persons(retention).await {
+                Ok(0) => {}
+                Ok(purged) => log::info!("[TRASH] purged {purged} outdated record(s)"),
+                Err(err) => log::error!("[TRASH] got database error while purging: {}", err),
+            }
+        }
+    });
+}
@@ -1,4 +1,11 @@
-import { getToken } from "$lib/stores/auth";
+import {
+	getToken,
+	getRefreshToken,
+	getCsrfToken,
+	setToken,
+	setRefreshToken
+} from "$lib/stores/auth";
+import type { AuthResponse } from "$lib/types/auth";
 import { ApiClientError } from "./error";
 
 export const API_ENDPOINT =
@@ -6,12 +13,48 @@ export const API_ENDPOINT =
 
 class ApiClient {
 	private baseUrl: string;
+	private refreshing: Promise<boolean> | null = null;
 
 	constructor(baseUrl: string) {
 		this.baseUrl = baseUrl;
 	}
 
-	async fetch(endpoint: string, options: RequestInit = {}): Promise<Response> {
+	// exchanges refresh token for a new token pair, concurrent calls share one request
+	async refresh(): Promise<boolean> {
+		const refreshToken = getRefreshToken();
+		const csrfToken = getCsrfToken();
+
+		// in cookie session mode refresh token cookie is sent by browser
+		if (!refreshToken && !csrfToken) return false;
+
+		if (!this.refreshing) {
+			this.refreshing = fetch(`${this.baseUrl}/refresh`, {
+				method: "POST",
+				headers: {
+					"Content-Type": "application/json",
+					...(csrfToken ? { "X-CSRF-Token": csrfToken } : {})
+				},
+				body: JSON.stringify({ refresh_token: refreshToken ?? "" })
+			})
+				.then(async (response) => {
+					if (!response.ok) return false;
+
+					const data = (await response.json()) as AuthResponse;
+					if (data.token) setToken(data.token);
+					if (data.refresh_token) setRefreshToken(data.refresh_token);
+
+					return true;
+				})
+				.catch(() => false)
+				.finally(() => {
+					this.refreshing = null;
+				});
+		}
+
+		return this.refreshing;
+	}
+
+	async fetch(endpoint: string, options: RequestInit = {}, retry = true): Promise<Response> {
 		const token = getToken();
 		const url = `${this.baseUrl}${endpoint}`;
 
@@ -27,10 +70,22 @@ class ApiClient {
 			(config.headers as Record<string, string>).Authorization = `Bearer ${token}`;
 		}
 
-		return await fetch(url, config);
+		const csrfToken = getCsrfToken();
+
+		if (csrfToken) {
+			(config.headers as Record<string, string>)["X-CSRF-Token"] = csrfToken;
+		}
+
+		const response = await fetch(url, config);
+
+		if (response.status === 401 && retry && (await this.refresh())) {
+			return this.fetch(endpoint, options, false);
+		}
+
+		return response;
 	}
 
-	async request<T>(endpoint: string, options: RequestInit = {}): Promise<T> {
+	async request<T>(endpoint: string, options: RequestInit = {}, retry = true): Promise<T> {
 		const token = getToken();
 		const url = `${this.baseUrl}${endpoint}`;
 
@@ -46,9 +101,19 @@ class ApiClient {
 			(config.headers as Record<string, string>).Authorization = `Bearer ${token}`;
 		}
 
+		const csrfToken = getCsrfToken();
+
+		if (csrfToken) {
+			(config.headers as Record<string, string>)["X-CSRF-Token"] = csrfToken;
+		}
+
 		const response = await fetch(url, config);
 
 		if (response.status === 401) {
+			if (retry && (await this.refresh())) {
+				return this.request<T>(endpoint, options, false);
+			}
+
 			// handleTokenExpired();
 			throw new ApiClientError("Authentication required", 401);
 		}
@@ -79,6 +144,12 @@ class ApiClient {
 			headers.Authorization = `Bearer ${token}`;
 		}
 
+		const csrfToken = getCsrfToken();
+
+		if (csrfToken) {
+			headers["X-CSRF-Token"] = csrfToken;
+		}
+
 		const response = await fetch(url, {
 			method: "POST",
 			headers: headers,
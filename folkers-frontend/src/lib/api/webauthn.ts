@@ -0,0 +1,97 @@
+// WebAuthn options and credentials are sent as JSON, binary fields are base64url encoded
+
+type JsonObject = Record<string, unknown>;
+
+function fromBase64Url(value: string): ArrayBuffer {
+	const base64 = value.replace(/-/g, "+").replace(/_/g, "/");
+	const padded = base64.padEnd(base64.length + ((4 - (base64.length % 4)) % 4), "=");
+	const binary = atob(padded);
+
+	const bytes = new Uint8Array(binary.length);
+	for (let i = 0; i < binary.length; i++) bytes[i] = binary.charCodeAt(i);
+
+	return bytes.buffer;
+}
+
+function toBase64Url(buffer: ArrayBuffer | null): string | null {
+	if (!buffer) return null;
+
+	let binary = "";
+	for (const byte of new Uint8Array(buffer)) binary += String.fromCharCode(byte);
+
+	return btoa(binary).replace(/\+/g, "-").replace(/\//g, "_").replace(/=+$/, "");
+}
+
+function decodeCredentialList(list: unknown): PublicKeyCredentialDescriptor[] | undefined {
+	if (!Array.isArray(list)) return undefined;
+
+	return list.map((item: JsonObject) => ({
+		...item,
+		id: fromBase64Url(item.id as string)
+	})) as PublicKeyCredentialDescriptor[];
+}
+
+export function isPasskeySupported(): boolean {
+	return typeof window !== "undefined" && !!window.PublicKeyCredential;
+}
+
+// `navigator.credentials.create()` with options of POST /me/passkeys/register
+export async function createPasskey(options: { publicKey: JsonObject }): Promise<JsonObject> {
+	const publicKey = options.publicKey;
+	const user = publicKey.user as JsonObject;
+
+	const credential = (await navigator.credentials.create({
+		publicKey: {
+			...publicKey,
+			challenge: fromBase64Url(publicKey.challenge as string),
+			user: { ...user, id: fromBase64Url(user.id as string) },
+			excludeCredentials: decodeCredentialList(publicKey.excludeCredentials)
+		} as unknown as PublicKeyCredentialCreationOptions
+	})) as PublicKeyCredential | null;
+
+	if (!credential) throw new Error("Passkey creation was cancelled");
+
+	const response = credential.response as AuthenticatorAttestationResponse;
+
+	return {
+		id: credential.id,
+		rawId: toBase64Url(credential.rawId),
+		type: credential.type,
+		response: {
+			attestationObject: toBase64Url(response.attestationObject),
+			clientDataJSON: toBase64Url(response.clientDataJSON),
+			transports: response.getTransports?.() ?? []
+		},
+		extensions: credential.getClientExtensionResults()
+	};
+}
+
+// `navigator.credentials.get()` with options of POST /login/passkey
+export async function getPasskey(options: { publicKey: JsonObject }): Promise<JsonObject> {
+	const publicKey = options.publicKey;
+
+	const credential = (await navigator.credentials.get({
+		publicKey: {
+			...publicKey,
+			challenge: fromBase64Url(publicKey.challenge as string),
+			allowCredentials: decodeCredentialList(publicKey.allowCredentials)
+		} as unknown as PublicKeyCredentialRequestOptions
+	})) as PublicKeyCredential | null;
+
+	if (!credential) throw new Error("Passkey login was cancelled");
+
+	const response = credential.response as AuthenticatorAssertionResponse;
+
+	return {
+		id: credential.id,
+		rawId: toBase64Url(credential.rawId),
+		type: credential.type,
+		response: {
+			authenticatorData: toBase64Url(response.authenticatorData),
+			clientDataJSON: toBase64Url(response.clientDataJSON),
+			signature: toBase64Url(response.signature),
+			userHandle: toBase64Url(response.userHandle)
+		},
+		extensions: credential.getClientExtensionResults()
+	};
+}
@@ -1,12 +1,12 @@
-import { getToken, type TokenPayload } from "$lib/stores/auth";
+import { getToken, isCookieSession, type TokenPayload } from "$lib/stores/auth";
 import { api } from "$lib/api/client";
 import { ADMIN_ROLE } from "$lib";
 
 export async function authGuard(): Promise<boolean> {
 	try {
-		const token = getToken();
+		const authenticated = getToken() !== null || isCookieSession() || (await api.refresh());
 
-		if (!token) {
+		if (!authenticated) {
 			return false;
 		}
 
@@ -1,24 +1,77 @@
 import { api } from "$lib/api/client";
+import { ApiClientError } from "$lib/api/error";
+import { getPasskey } from "$lib/api/webauthn";
 import { get } from "svelte/store";
-import { setToken, loggedUser, initializeAuth } from "$lib/stores/auth";
-import { type AuthResponse, type LoginCredentials, type User } from "$lib/types/auth";
+import { setToken, setRefreshToken, loggedUser, initializeAuth } from "$lib/stores/auth";
+import {
+	type AuthResponse,
+	type ChangePassword,
+	type LoginCredentials,
+	type OidcAuthorization,
+	type OidcCallback,
+	type PasskeyChallenge
+} from "$lib/types/auth";
+
+// in cookie session mode tokens are set by backend
+function saveTokens(response: AuthResponse): void {
+	if (response.token) setToken(response.token);
+	if (response.refresh_token) setRefreshToken(response.refresh_token);
+}
 
 export class AuthService {
-	static async login(credentials: LoginCredentials): Promise<User> {
+	static async login(credentials: LoginCredentials): Promise<AuthResponse> {
 		const response = await api.post<AuthResponse>("/login", credentials);
 
-		setToken(response.token);
+		saveTokens(response);
 		await initializeAuth();
 
-		const logged_user = get(loggedUser);
-
-		if (logged_user) {
-			return logged_user;
+		if (get(loggedUser)) {
+			return response;
 		}
 
 		throw new Error("Error loading logged user data");
 	}
 
+	static async passkeyLogin(username: string): Promise<AuthResponse> {
+		const challenge = await api.post<PasskeyChallenge>("/login/passkey", { username });
+		const credential = await getPasskey(challenge.options);
+
+		const response = await api.post<AuthResponse>("/login/passkey/finish", {
+			ceremony_id: challenge.ceremony_id,
+			credential
+		});
+
+		saveTokens(response);
+		await initializeAuth();
+
+		return response;
+	}
+
+	static async oidcAuthorize(): Promise<string> {
+		const response = await api.get<OidcAuthorization>("/oidc/authorize");
+		return response.authorization_url;
+	}
+
+	static async oidcCallback(payload: OidcCallback): Promise<AuthResponse> {
+		const response = await api.post<AuthResponse>("/oidc/callback", payload);
+
+		saveTokens(response);
+		await initializeAuth();
+
+		return response;
+	}
+
+	static async changePassword(payload: ChangePassword): Promise<void> {
+		const response = await api.fetch("/me/password", {
+			method: "POST",
+			body: JSON.stringify(payload)
+		});
+
+		if (!response.ok) {
+			throw new ApiClientError(response.statusText, response.status);
+		}
+	}
+
 	static async signatureKeygen(): Promise<string> {
 		return (
 			await api.fetch("/signature-keygen", {
@@ -1,10 +1,38 @@
 import { api } from "$lib/api/client";
-import type { CreatePersonRecord, PersonRecord } from "$lib/types/person";
+import type {
+	CreatePersonRecord,
+	PersonRecord,
+	FilterPersonQuery,
+	PersonPage,
+	PersonSearchHit,
+	SearchPersonQuery,
+	TextSearchPersonQuery,
+	TrashedPersonRecord
+} from "$lib/types/person";
+import type {
+	CreateRelationRecord,
+	PersonConnectionRecord,
+	PersonNetwork,
+	PersonPath,
+	PersonRelationRecord
+} from "$lib/types/relation";
+import type { PersonFieldChange, PersonRevisionRecord } from "$lib/types/revision";
+import type { TagRecord } from "$lib/types/tag";
 import type { RecordSignatureRecord, SignRecordPayload } from "$lib/types/signature";
 
 export class PersonService {
-	static async list_persons(): Promise<PersonRecord[]> {
-		return await api.get<PersonRecord[]>("/persons");
+	static async list_persons(query: FilterPersonQuery = {}): Promise<PersonPage> {
+		return await api.get<PersonPage>(`/persons?${queryParams(query)}`);
+	}
+
+	static async search_persons(query: SearchPersonQuery): Promise<PersonPage> {
+		return await api.get<PersonPage>(`/persons/search?${queryParams(query)}`);
+	}
+
+	static async text_search_persons(
+		query: TextSearchPersonQuery
+	): Promise<PersonPage<PersonSearchHit>> {
+		return await api.get<PersonPage<PersonSearchHit>>(`/persons/search/text?${queryParams(query)}`);
 	}
 
 	static async get_person(id: string): Promise<PersonRecord> {
@@ -23,6 +51,67 @@ export class PersonService {
 		return await api.delete<PersonRecord>(`/persons/${id}`);
 	}
 
+	static async list_trash(): Promise<TrashedPersonRecord[]> {
+		return await api.get<TrashedPersonRecord[]>("/persons/trash");
+	}
+
+	static async restore_person(id: string): Promise<PersonRecord> {
+		return await api.post<PersonRecord>(`/persons/trash/${id}/restore`);
+	}
+
+	static async purge_person(id: string): Promise<PersonRecord> {
+		return await api.delete<PersonRecord>(`/persons/trash/${id}`);
+	}
+
+	static async list_revisions(id: string): Promise<PersonRevisionRecord[]> {
+		return await api.get<PersonRevisionRecord[]>(`/persons/${id}/revisions`);
+	}
+
+	static async get_revision(id: string, revision: number): Promise<PersonRevisionRecord> {
+		return await api.get<PersonRevisionRecord>(`/persons/${id}/revisions/${revision}`);
+	}
+
+	static async diff_revisions(id: string, from: number, to: number): Promise<PersonFieldChange[]> {
+		return await api.get<PersonFieldChange[]>(
+			`/persons/${id}/revisions/diff?${queryParams({ from, to })}`
+		);
+	}
+
+	static async revert_revision(id: string, revision: number): Promise<PersonRecord> {
+		return await api.post<PersonRecord>(`/persons/${id}/revisions/${revision}/revert`);
+	}
+
+	static async list_relations(id: string): Promise<PersonConnectionRecord[]> {
+		return await api.get<PersonConnectionRecord[]>(`/persons/${id}/relations`);
+	}
+
+	static async add_relation(
+		id: string,
+		payload: CreateRelationRecord
+	): Promise<PersonRelationRecord> {
+		return await api.post<PersonRelationRecord>(`/persons/${id}/relations`, payload);
+	}
+
+	static async remove_relation(id: string, relationId: string): Promise<PersonRelationRecord> {
+		return await api.delete<PersonRelationRecord>(`/persons/${id}/relations/${relationId}`);
+	}
+
+	static async get_network(id: string, depth: number = 1): Promise<PersonNetwork> {
+		return await api.get<PersonNetwork>(`/persons/${id}/network?${queryParams({ depth })}`);
+	}
+
+	static async find_path(id: string, targetId: string): Promise<PersonPath> {
+		return await api.get<PersonPath>(`/persons/${id}/path/${targetId}`);
+	}
+
+	static async get_tags(id: string): Promise<TagRecord[]> {
+		return await api.get<TagRecord[]>(`/persons/${id}/tags`);
+	}
+
+	static async set_tags(id: string, tags: string[]): Promise<TagRecord[]> {
+		return await api.post<TagRecord[]>(`/persons/${id}/tags`, { tags });
+	}
+
 	static async verify_person(id: string): Promise<RecordSignatureRecord> {
 		return await api.get<RecordSignatureRecord>(`/persons/${id}/verify`);
 	}
@@ -37,3 +126,13 @@ export class PersonService {
 		});
 	}
 }
+
+function queryParams(query: object): URLSearchParams {
+	const params = new URLSearchParams();
+
+	for (const [key, value] of Object.entries(query)) {
+		if (value !== undefined && value !== "") params.set(key, String(value));
+	}
+
+	return params;
+}
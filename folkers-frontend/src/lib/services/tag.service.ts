@@ -0,0 +1,24 @@
+import { api } from "$lib/api/client";
+import type { CreateTagRecord, TagRecord, TagUsageRecord } from "$lib/types/tag";
+
+export class TagService {
+	static async list_tags(): Promise<TagUsageRecord[]> {
+		return await api.get<TagUsageRecord[]>("/tags");
+	}
+
+	static async create_tag(payload: CreateTagRecord): Promise<TagRecord> {
+		return await api.post<TagRecord>("/tags", payload);
+	}
+
+	static async update_tag(id: string, payload: CreateTagRecord): Promise<TagRecord> {
+		return await api.patch<TagRecord>(`/tags/${id}`, payload);
+	}
+
+	static async delete_tag(id: string): Promise<TagRecord> {
+		return await api.delete<TagRecord>(`/tags/${id}`);
+	}
+
+	static async merge_tags(id: string, into: string): Promise<TagRecord> {
+		return await api.post<TagRecord>(`/tags/${id}/merge`, { into });
+	}
+}
@@ -1,5 +1,15 @@
 import { api } from "$lib/api/client";
-import type { CreateUser, User } from "$lib/types/auth";
+import { setToken } from "$lib/stores/auth";
+import { createPasskey } from "$lib/api/webauthn";
+import type {
+	CreateUser,
+	ImpersonationResponse,
+	Passkey,
+	PasskeyChallenge,
+	ReactivateUser,
+	SuspendUser,
+	User
+} from "$lib/types/auth";
 
 export class UserService {
 	static async list_users(): Promise<User[]> {
@@ -21,4 +31,41 @@ export class UserService {
 	static async delete_user(username: string): Promise<User> {
 		return await api.delete(`/users/${username}`);
 	}
+
+	static async suspend_user(username: string, payload: SuspendUser): Promise<User> {
+		return await api.post(`/users/${username}/suspend`, payload);
+	}
+
+	// replaces access token only, refresh token still belongs to admin
+	static async impersonate_user(username: string): Promise<void> {
+		const response = await api.post<ImpersonationResponse>(`/users/${username}/impersonate`);
+		setToken(response.token);
+	}
+
+	static async reactivate_user(username: string, payload: ReactivateUser): Promise<User> {
+		return await api.post(`/users/${username}/reactivate`, payload);
+	}
+
+	static async list_passkeys(): Promise<Passkey[]> {
+		return await api.get("/me/passkeys");
+	}
+
+	static async register_passkey(name: string): Promise<Passkey> {
+		const challenge = await api.post<PasskeyChallenge>("/me/passkeys/register");
+		const credential = await createPasskey(challenge.options);
+
+		return await api.post("/me/passkeys", {
+			ceremony_id: challenge.ceremony_id,
+			name,
+			credential
+		});
+	}
+
+	static async rename_passkey(id: string, name: string): Promise<Passkey> {
+		return await api.patch(`/me/passkeys/${id}`, { name });
+	}
+
+	static async delete_passkey(id: string): Promise<Passkey> {
+		return await api.delete(`/me/passkeys/${id}`);
+	}
 }
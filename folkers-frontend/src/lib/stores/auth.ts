@@ -9,9 +9,18 @@ export interface TokenPayload {
 	sub: string;
 	username: string;
 	role: string;
+	jti: string;
+	sid: string;
+	act?: { sub: string; username: string };
 }
 
 export const accessTokenStorage = "access_token";
+export const refreshTokenStorage = "refresh_token";
+export const refreshTokenLifetimeDays = 14;
+
+// cookie session mode: tokens are kept by backend in HttpOnly cookies, only CSRF token is readable
+export const sessionCookie = "folkers_session";
+export const csrfCookie = "folkers_csrf";
 
 export const loggedUser = writable<User | null>(null);
 export const isAuthenticated = writable<boolean>(false);
@@ -63,22 +72,70 @@ export function getToken(): string | null {
 	return match ? match[1] : null;
 }
 
+export function setRefreshToken(token: string): void {
+	if (!browser) return;
+
+	const expires = new Date(Date.now() + refreshTokenLifetimeDays * 24 * 60 * 60 * 1000).toUTCString();
+	document.cookie = `${refreshTokenStorage}=${token}; Path=/; Expires=${expires}; SameSite=Lax; ${cookieSecure()}`;
+}
+
+export function getRefreshToken(): string | null {
+	if (!browser) return null;
+
+	const cookieString = document.cookie;
+	const match = cookieString.match(new RegExp(`${refreshTokenStorage}=([^;]+)`));
+	return match ? match[1] : null;
+}
+
+export function getCsrfToken(): string | null {
+	if (!browser) return null;
+
+	const cookieString = document.cookie;
+	const match = cookieString.match(new RegExp(`${csrfCookie}=([^;]+)`));
+	return match ? match[1] : null;
+}
+
+export function isCookieSession(): boolean {
+	return getCsrfToken() !== null;
+}
+
 export function clearAuth(): void {
 	if (browser) {
 		document.cookie = `${accessTokenStorage}=; Path=/; Expires=Thu, 01 Jan 1970 00:00:00 GMT; SameSite=Lax; ${cookieSecure()}`;
+		document.cookie = `${refreshTokenStorage}=; Path=/; Expires=Thu, 01 Jan 1970 00:00:00 GMT; SameSite=Lax; ${cookieSecure()}`;
 	}
 
 	isAuthenticated.set(false);
 	loggedUser.set(null);
 }
 
-export function logout(): void {
+export async function logout(): Promise<void> {
+	if (getToken() || isCookieSession()) {
+		await api
+			.fetch("/logout", { method: "POST" })
+			.catch((error) => console.error("Logout error: ", error));
+	}
+
 	clearAuth();
 	if (browser) {
 		window.location.href = "/login";
 	}
 }
 
+// ends impersonation session, admin's access token is restored with the kept refresh token
+export async function stopImpersonation(): Promise<void> {
+	if (getToken()) {
+		await api
+			.fetch("/logout", { method: "POST" })
+			.catch((error) => console.error("Logout error: ", error));
+	}
+
+	if (browser) {
+		document.cookie = `${accessTokenStorage}=; Path=/; Expires=Thu, 01 Jan 1970 00:00:00 GMT; SameSite=Lax; ${cookieSecure()}`;
+		window.location.href = "/users";
+	}
+}
+
 export function handleTokenExpired(): void {
 	clearAuth();
 	if (browser) {
@@ -88,9 +145,9 @@ export function handleTokenExpired(): void {
 
 export async function initializeAuth(): Promise<void> {
 	if (browser) {
-		const token = getToken();
+		const authenticated = getToken() !== null || isCookieSession() || (await api.refresh());
 
-		if (token) {
+		if (authenticated) {
 			try {
 				const response = await api.get<User>("/me");
 				isAuthenticated.set(true);
@@ -8,6 +8,11 @@ export interface User {
 	created_by: string;
 	creation_datetime: Date;
 	public_key: string | null;
+	must_change_password: boolean;
+	protected: boolean;
+	disabled: boolean;
+	disabled_reason: string | null;
+	expires_at: Date | null;
 }
 
 export interface LoginCredentials {
@@ -15,9 +20,47 @@ export interface LoginCredentials {
 	password: string;
 }
 
+// tokens are omitted in cookie session mode
 export interface AuthResponse {
+	token?: string;
+	token_type: string;
+	expires_in: number;
+	refresh_token?: string;
+	password_change_required: boolean;
+}
+
+export interface ImpersonationResponse {
 	token: string;
 	token_type: string;
+	expires_in: number;
+}
+
+export interface OidcAuthorization {
+	authorization_url: string;
+}
+
+export interface OidcCallback {
+	code: string;
+	state: string;
+}
+
+export interface Passkey {
+	id: SurrealThing;
+	name: string;
+	credential_id: string;
+	creation_datetime: Date;
+	last_used: Date | null;
+}
+
+// `options` are passed to `navigator.credentials` (see $lib/api/webauthn)
+export interface PasskeyChallenge {
+	ceremony_id: string;
+	options: { publicKey: Record<string, unknown> };
+}
+
+export interface ChangePassword {
+	current_password: string;
+	new_password: string;
 }
 
 export interface CreateUser {
@@ -25,4 +68,13 @@ export interface CreateUser {
 	password: string;
 	role: string;
 	created_by: string;
+	expires_at?: Date | null;
+}
+
+export interface SuspendUser {
+	reason: string | null;
+}
+
+export interface ReactivateUser {
+	expires_at: Date | null;
 }
@@ -1,4 +1,5 @@
 import type { SurrealThing } from "./surreal";
+import type { TagRecord, TagsMode } from "./tag";
 
 export interface PersonRecord {
 	id: SurrealThing;
@@ -38,3 +39,90 @@ export interface CreatePersonRecord {
 	avatar: string | null;
 	media: string[];
 }
+
+export interface PersonSummaryRecord {
+	id: SurrealThing;
+	name: string;
+	surname: string;
+	patronymic: string;
+
+	birthday: string;
+	city: string;
+	avatar: string | null;
+
+	author: string;
+	creation_datetime: string;
+	signed: boolean;
+	tags: TagRecord[];
+}
+
+export interface TrashedPersonRecord {
+	id: SurrealThing;
+	name: string;
+	surname: string;
+	patronymic: string;
+
+	author: string;
+	deleted_at: string;
+	deleted_by: string;
+}
+
+export type PersonSortField =
+	| "surname"
+	| "name"
+	| "patronymic"
+	| "birthday"
+	| "city"
+	| "creation_datetime";
+
+export interface PageQuery {
+	sort?: PersonSortField;
+	order?: "asc" | "desc";
+	page?: number;
+	limit?: number;
+}
+
+export interface TagsQuery {
+	/** Comma-separated tag names */
+	tags?: string;
+	tags_mode?: TagsMode;
+}
+
+export interface SearchPersonQuery extends PageQuery, TagsQuery {
+	q: string;
+}
+
+export interface FilterPersonQuery extends PageQuery, TagsQuery {
+	city?: string;
+	author?: string;
+	birthday_from?: string;
+	birthday_to?: string;
+	has_avatar?: boolean;
+	signed?: boolean;
+}
+
+export interface TextSearchPersonQuery extends TagsQuery {
+	q: string;
+	page?: number;
+	limit?: number;
+}
+
+export type PersonNarrativeField = "summary" | "past" | "traits_good" | "traits_bad";
+
+export interface PersonHighlight {
+	field: PersonNarrativeField;
+	/** HTML escaped fragment, matches are wrapped in `<mark>` */
+	snippet: string;
+}
+
+export interface PersonSearchHit extends PersonSummaryRecord {
+	score: number;
+	highlights: PersonHighlight[];
+}
+
+export interface PersonPage<T = PersonSummaryRecord> {
+	items: T[];
+	total: number;
+	page: number;
+	limit: number;
+}
@@ -0,0 +1,77 @@
+import type { SurrealThing } from "./surreal";
+
+export type RelationKind = "relative" | "spouse" | "colleague" | "friend" | "custom";
+
+export type RelationDirection = "outgoing" | "incoming";
+
+export interface CreateRelationRecord {
+	person_id: string;
+	kind: RelationKind;
+	/** Required for `custom` relations */
+	label?: string;
+	since?: string;
+	until?: string;
+	note?: string;
+}
+
+export interface PersonRelationRecord {
+	id: SurrealThing;
+	in: SurrealThing;
+	out: SurrealThing;
+
+	kind: RelationKind;
+	label: string | null;
+	since: string | null;
+	until: string | null;
+	note: string | null;
+
+	author: string;
+	creation_datetime: string;
+}
+
+export interface RelatedPersonRecord {
+	id: SurrealThing;
+	name: string;
+	surname: string;
+	patronymic: string;
+	avatar: string | null;
+}
+
+export interface PersonConnectionRecord {
+	id: SurrealThing;
+	direction: RelationDirection;
+	person: RelatedPersonRecord;
+
+	kind: RelationKind;
+	label: string | null;
+	since: string | null;
+	until: string | null;
+	note: string | null;
+
+	author: string;
+	creation_datetime: string;
+}
+
+/** Persons are referenced with record identifiers */
+export interface RelationEdge {
+	id: SurrealThing;
+	from: string;
+	to: string;
+	kind: RelationKind;
+	label: string | null;
+}
+
+export interface NetworkNode extends RelatedPersonRecord {
+	distance: number;
+}
+
+export interface PersonNetwork {
+	nodes: NetworkNode[];
+	edges: RelationEdge[];
+}
+
+export interface PersonPath {
+	persons: RelatedPersonRecord[];
+	/** `relations[i]` connects `persons[i]` and `persons[i + 1]` */
+	relations: RelationEdge[];
+}
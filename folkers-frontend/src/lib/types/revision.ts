@@ -0,0 +1,24 @@
+import type { PersonRecord } from "./person";
+import type { SurrealThing } from "./surreal";
+
+export type RevisionAction = "create" | "update" | "delete" | "restore" | "revert" | "tag";
+
+export interface PersonRevisionRecord {
+	id: SurrealThing;
+	person_id: string;
+	revision: number;
+	action: RevisionAction;
+	author: string;
+	creation_datetime: string;
+	reverted_from: number | null;
+	/** Omitted in revisions list */
+	snapshot?: PersonRecord;
+	/** Names of person's tags */
+	tags: string[];
+}
+
+export interface PersonFieldChange {
+	field: string;
+	from: unknown;
+	to: unknown;
+}
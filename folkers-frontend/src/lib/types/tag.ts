@@ -0,0 +1,20 @@
+import type { SurrealThing } from "./surreal";
+
+export interface TagRecord {
+	id: SurrealThing;
+	name: string;
+	/** `#rrggbb` */
+	color: string;
+}
+
+export interface TagUsageRecord extends TagRecord {
+	/** Number of tagged records */
+	count: number;
+}
+
+export interface CreateTagRecord {
+	name: string;
+	color: string;
+}
+
+export type TagsMode = "any" | "all";
@@ -1,12 +1,15 @@
 import type { LayoutServerLoad } from "./$types";
-import { accessTokenStorage } from "$lib/stores/auth";
+import { accessTokenStorage, sessionCookie } from "$lib/stores/auth";
 import { redirect } from "@sveltejs/kit";
 
 export const load: LayoutServerLoad = async ({ cookies, url }) => {
 	const loginEndpoint = "/login";
-	const token = cookies.get(accessTokenStorage);
+	const token = cookies.get(accessTokenStorage) ?? cookies.get(sessionCookie);
 
-	if (!token && url.pathname !== loginEndpoint) {
+	const loginPage =
+		url.pathname === loginEndpoint || url.pathname.startsWith(`${loginEndpoint}/`);
+
+	if (!token && !loginPage) {
 		throw redirect(302, "/login");
 	}
 
@@ -5,7 +5,7 @@ import { initializeAuth } from "$lib/stores/auth";
 export const load: LayoutLoad = async ({ url }) => {
 	const loginEndpoint = "/login";
 
-	if (url.pathname === loginEndpoint) return;
+	if (url.pathname === loginEndpoint || url.pathname.startsWith(`${loginEndpoint}/`)) return;
 
 	if (typeof window !== "undefined") {
 		const ok = await authGuard();
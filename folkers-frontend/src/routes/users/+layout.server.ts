@@ -1,11 +1,11 @@
 import type { LayoutServerLoad } from "./$types";
 import { error, redirect } from "@sveltejs/kit";
 import { adminGuardServer } from "$lib/guards/auth.guard";
-import { accessTokenStorage } from "$lib/stores/auth";
+import { accessTokenStorage, sessionCookie } from "$lib/stores/auth";
 import { ApiClientError } from "$lib/api/error";
 
 export const load: LayoutServerLoad = async ({ cookies }) => {
-	const token = cookies.get(accessTokenStorage);
+	const token = cookies.get(accessTokenStorage) ?? cookies.get(sessionCookie);
 
 	if (!token) {
 		throw redirect(302, "/login");